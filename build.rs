@@ -11,7 +11,14 @@ fn get_config() -> PathBuf {
 
 #[cfg(target_os = "linux")]
 fn get_config() -> PathBuf {
-    Config::new("native/liblinuxbridge").build()
+    Config::new("native/liblinuxbridge")
+        .define("ESPANSO_ATSPI", if is_atspi_enabled() { "ON" } else { "OFF" })
+        .build()
+}
+
+#[cfg(target_os = "linux")]
+fn is_atspi_enabled() -> bool {
+    std::env::var("CARGO_FEATURE_ATSPI").is_ok()
 }
 
 #[cfg(target_os = "macos")]
@@ -37,6 +44,12 @@ fn print_config() {
     println!("cargo:rustc-link-lib=dylib=X11");
     println!("cargo:rustc-link-lib=dylib=Xtst");
     println!("cargo:rustc-link-lib=dylib=xdo");
+
+    if is_atspi_enabled() {
+        println!("cargo:rustc-link-lib=dylib=atspi");
+        println!("cargo:rustc-link-lib=dylib=gobject-2.0");
+        println!("cargo:rustc-link-lib=dylib=glib-2.0");
+    }
 }
 
 #[cfg(target_os = "macos")]
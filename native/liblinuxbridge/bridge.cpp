@@ -256,9 +256,15 @@ void event_callback(XPointer p, XRecordInterceptData *hook)
         case KeyPress:
             //printf ("%d %d %s\n", key_code, res, buffer.data());
             if (res > 0 && key_code != 22) {  // Printable character, but not backspace
-                keypress_callback(context_instance, buffer.data(), buffer.size(), 0, key_code);
+                keypress_callback(context_instance, buffer.data(), buffer.size(), 0, key_code, 1);
             }else{ // Modifier key
-                keypress_callback(context_instance, NULL, 0, 1, key_code);
+                keypress_callback(context_instance, NULL, 0, 1, key_code, 1);
+            }
+            break;
+        case KeyRelease:
+            // The release is needed to know how long a modifier was held
+            if (res <= 0 || key_code == 22) {
+                keypress_callback(context_instance, NULL, 0, 1, key_code, 0);
             }
             break;
         default:
@@ -284,6 +290,24 @@ void left_arrow(int32_t count) {
     }
 }
 
+void right_arrow(int32_t count) {
+    for (int i = 0; i<count; i++) {
+        xdo_send_keysequence_window(xdo_context, CURRENTWINDOW, "Right", 8000);
+    }
+}
+
+void up_arrow(int32_t count) {
+    for (int i = 0; i<count; i++) {
+        xdo_send_keysequence_window(xdo_context, CURRENTWINDOW, "Up", 8000);
+    }
+}
+
+void down_arrow(int32_t count) {
+    for (int i = 0; i<count; i++) {
+        xdo_send_keysequence_window(xdo_context, CURRENTWINDOW, "Down", 8000);
+    }
+}
+
 void trigger_paste() {
     // Before sending the paste shortcut, trigger the press and release of the Shift key
     // this is needed because for some triggers, for example ending with ":", the user
@@ -497,4 +521,52 @@ int32_t is_current_window_special() {
     return 0;
 }
 
+// SECURE INPUT
+
+#ifdef ESPANSO_ATSPI
+
+#include <atomic>
+#include <atspi/atspi.h>
+
+std::atomic<int32_t> password_field_focused(0);
+
+void on_focus_changed(const AtspiEvent *event) {
+    // detail1 is 0 when the element loses the focus, the next focused one will update the flag
+    if (event->detail1 == 0 || event->source == NULL) {
+        return;
+    }
+
+    AtspiRole role = atspi_accessible_get_role(event->source, NULL);
+    password_field_focused = (role == ATSPI_ROLE_PASSWORD_TEXT) ? 1 : 0;
+}
+
+int32_t start_secure_input_monitor() {
+    if (atspi_init() < 0) {
+        return -2;
+    }
+
+    AtspiEventListener *listener = atspi_event_listener_new_simple(on_focus_changed, NULL);
+    if (!atspi_event_listener_register(listener, "object:state-changed:focused", NULL)) {
+        return -3;
+    }
+
+    atspi_event_main();
+
+    return 1;
+}
+
+int32_t is_password_field_focused() {
+    return password_field_focused;
+}
+
+#else
+
+int32_t start_secure_input_monitor() {
+    return -1;
+}
+
+int32_t is_password_field_focused() {
+    return 0;
+}
 
+#endif
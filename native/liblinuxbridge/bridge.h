@@ -46,9 +46,9 @@ extern "C" void cleanup();
 
 /*
  * Called when a new keypress is made, the first argument is an char array,
- * while the second is the size of the array.
+ * while the second is the size of the array. Releases are only reported for the modifiers.
  */
-typedef void (*KeypressCallback)(void * self, const char *buffer, int32_t len, int32_t is_modifier, int32_t key_code);
+typedef void (*KeypressCallback)(void * self, const char *buffer, int32_t len, int32_t is_modifier, int32_t key_code, int32_t is_key_down);
 
 extern KeypressCallback keypress_callback;
 
@@ -72,6 +72,21 @@ extern "C" void delete_string(int32_t count);
  */
 extern "C" void left_arrow(int32_t count);
 
+/*
+ * Send the right arrow keypress, *count* times.
+ */
+extern "C" void right_arrow(int32_t count);
+
+/*
+ * Send the up arrow keypress, *count* times.
+ */
+extern "C" void up_arrow(int32_t count);
+
+/*
+ * Send the down arrow keypress, *count* times.
+ */
+extern "C" void down_arrow(int32_t count);
+
 /*
  * Trigger normal paste ( Pressing CTRL+V )
  */
@@ -119,4 +134,17 @@ extern "C" int32_t get_active_window_executable(char * buffer, int32_t size);
  */
 extern "C" int32_t is_current_window_special();
 
+// SECURE INPUT
+
+/*
+ * Monitor the focused elements through AT-SPI, blocking the calling thread.
+ * Return a negative value if espanso was built without AT-SPI support or it's not available.
+ */
+extern "C" int32_t start_secure_input_monitor();
+
+/*
+ * Return a value greater than 0 if the focused element is a password field.
+ */
+extern "C" int32_t is_password_field_focused();
+
 #endif //ESPANSO_BRIDGE_H
@@ -45,7 +45,6 @@
             int len = event.characters.length;
 
             keypress_callback(context_instance, chars, len, 0, event.keyCode);
-            //NSLog(@"keydown: %@, %d", event.characters, event.keyCode);
         }else{
             // Because this event is triggered for both the press and release of a modifier, trigger the callback
             // only on release
@@ -54,8 +53,6 @@
 
                 keypress_callback(context_instance, NULL, 0, 1, event.keyCode);
             }
-
-            //NSLog(@"keydown: %d", event.keyCode);
         }
     }];
 }
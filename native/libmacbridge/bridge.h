@@ -158,6 +158,28 @@ int32_t set_clipboard(char * text);
  */
 int32_t set_clipboard_image(char * path);
 
+/*
+ * Set the clipboard HTML, with a plain text fallback
+ */
+int32_t set_clipboard_html(char * html, char * text_fallback);
+
+/*
+ * Write the types of the first clipboard item in the buffer, separated by newlines.
+ * Return the length of the written string.
+ */
+int32_t get_clipboard_types(char * buffer, int32_t size);
+
+/*
+ * Copy the clipboard data for the given type in the buffer, returning the size of the data.
+ * If the buffer is too small, nothing is copied, so it can be used to query the size.
+ */
+int32_t get_clipboard_type_data(char * type, uint8_t * buffer, int32_t size);
+
+/*
+ * Replace the clipboard content with the given types and data
+ */
+int32_t set_clipboard_types_data(char ** types, uint8_t ** data, int32_t * sizes, int32_t count);
+
 
 };
 #endif //ESPANSO_BRIDGE_H
@@ -271,6 +271,70 @@ int32_t set_clipboard(char * text) {
     [pasteboard setString:nsText forType:NSPasteboardTypeString];
 }
 
+int32_t set_clipboard_html(char * html, char * text_fallback) {
+    NSPasteboard *pasteboard = [NSPasteboard generalPasteboard];
+    NSArray *array = @[NSPasteboardTypeHTML, NSPasteboardTypeString];
+    [pasteboard declareTypes:array owner:nil];
+
+    NSString *nsHtml = [NSString stringWithUTF8String:html];
+    [pasteboard setString:nsHtml forType:NSPasteboardTypeHTML];
+    NSString *nsText = [NSString stringWithUTF8String:text_fallback];
+    [pasteboard setString:nsText forType:NSPasteboardTypeString];
+
+    return 1;
+}
+
+int32_t get_clipboard_types(char * buffer, int32_t size) {
+    NSPasteboard *pasteboard = [NSPasteboard generalPasteboard];
+    NSPasteboardItem *item = [[pasteboard pasteboardItems] firstObject];
+    if (item == nil) {
+        return 0;
+    }
+
+    NSString *types = [[item types] componentsJoinedByString:@"\n"];
+    const char *types_str = [types UTF8String];
+    if (strlen(types_str) >= (size_t) size) {
+        return -1;
+    }
+    strcpy(buffer, types_str);
+
+    return (int32_t) strlen(types_str);
+}
+
+int32_t get_clipboard_type_data(char * type, uint8_t * buffer, int32_t size) {
+    NSPasteboard *pasteboard = [NSPasteboard generalPasteboard];
+    NSPasteboardItem *item = [[pasteboard pasteboardItems] firstObject];
+    if (item == nil) {
+        return -1;
+    }
+
+    NSData *data = [item dataForType:[NSString stringWithUTF8String:type]];
+    if (data == nil) {
+        return -2;
+    }
+
+    if (buffer != NULL && (NSUInteger) size >= [data length]) {
+        [data getBytes:buffer length:[data length]];
+    }
+
+    return (int32_t) [data length];
+}
+
+int32_t set_clipboard_types_data(char ** types, uint8_t ** data, int32_t * sizes, int32_t count) {
+    NSPasteboard *pasteboard = [NSPasteboard generalPasteboard];
+    NSPasteboardItem *item = [[NSPasteboardItem alloc] init];
+
+    for (int32_t i = 0; i < count; i++) {
+        NSData *itemData = [NSData dataWithBytes:data[i] length:sizes[i]];
+        [item setData:itemData forType:[NSString stringWithUTF8String:types[i]]];
+    }
+
+    [pasteboard clearContents];
+    BOOL result = [pasteboard writeObjects:@[item]];
+
+    return result ? 1 : -1;
+}
+
 int32_t set_clipboard_image(char *path) {
     NSString *pathString = [NSString stringWithUTF8String:path];
     NSImage *image = [[NSImage alloc] initWithContentsOfFile:pathString];
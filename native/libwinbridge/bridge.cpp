@@ -212,12 +212,15 @@ LRESULT CALLBACK window_procedure(HWND window, unsigned int msg, WPARAM wp, LPAR
             // Make sure it's a keyboard type event, relative to a key press.
             if (raw->header.dwType == RIM_TYPEKEYBOARD)
             {
+                // Pressing ALT generates a system key event, needed to know how long it's held
+                int is_alt_down = raw->data.keyboard.Message == WM_SYSKEYDOWN && raw->data.keyboard.VKey == VK_MENU;
+
                 // We only want KEY UP AND KEY DOWN events
-                if (raw->data.keyboard.Message != WM_KEYDOWN && raw->data.keyboard.Message != WM_KEYUP) {
+                if (raw->data.keyboard.Message != WM_KEYDOWN && raw->data.keyboard.Message != WM_KEYUP && !is_alt_down) {
                     return 0;
                 }
 
-                int is_key_down = raw->data.keyboard.Message == WM_KEYDOWN;
+                int is_key_down = raw->data.keyboard.Message == WM_KEYDOWN || is_alt_down;
 
                 DWORD currentTick = GetTickCount();
 
@@ -253,8 +256,6 @@ LRESULT CALLBACK window_procedure(HWND window, unsigned int msg, WPARAM wp, LPAR
 
                     int result = ToUnicodeEx(raw->data.keyboard.VKey, raw->data.keyboard.MakeCode, lpKeyState.data(), buffer.data(), buffer.size(), flags, currentKeyboardLayout);
 
-                    //std::cout << result << " " << buffer[0] << " " << raw->data.keyboard.VKey << std::endl;
-
                     // We need to call the callback in two different ways based on the type of key
                     // The only modifier we use that has a result > 0 is the BACKSPACE, so we have to consider it.
                     if (result >= 1 && raw->data.keyboard.VKey != VK_BACK) {
@@ -725,3 +726,116 @@ int32_t set_clipboard_image(wchar_t *path) {
     return result;
 }
 
+int32_t set_clipboard_html(char *html, wchar_t *text_fallback) {
+    // Get the right format for the HTML clipboard
+    UINT cf_html = RegisterClipboardFormat(L"HTML Format");
+    if (cf_html == 0) {
+        return -1;
+    }
+
+    // Prepare the HTML content
+    const size_t html_len = strlen(html) + 1;
+    HGLOBAL hMemHtml = GlobalAlloc(GMEM_MOVEABLE, html_len * sizeof(char));
+    memcpy(GlobalLock(hMemHtml), html, html_len * sizeof(char));
+    GlobalUnlock(hMemHtml);
+
+    // Prepare the fallback text content
+    const size_t fallback_len = wcslen(text_fallback) + 1;
+    HGLOBAL hMemFallback = GlobalAlloc(GMEM_MOVEABLE, fallback_len * sizeof(wchar_t));
+    memcpy(GlobalLock(hMemFallback), text_fallback, fallback_len * sizeof(wchar_t));
+    GlobalUnlock(hMemFallback);
+
+    if (!OpenClipboard(NULL)) {
+        return -2;
+    }
+
+    EmptyClipboard();
+
+    int32_t result = 1;
+    if (!SetClipboardData(cf_html, hMemHtml)) {
+        result = -3;
+    }
+
+    if (!SetClipboardData(CF_UNICODETEXT, hMemFallback)) {
+        result = -4;
+    }
+
+    CloseClipboard();
+
+    return result;
+}
+
+// Formats backed by GDI handles instead of global memory, which can't be copied with GlobalLock
+bool is_gdi_clipboard_format(UINT format) {
+    return format == CF_BITMAP || format == CF_METAFILEPICT || format == CF_PALETTE ||
+           format == CF_ENHMETAFILE || format == CF_OWNERDISPLAY || format == CF_DSPBITMAP ||
+           format == CF_DSPMETAFILEPICT || format == CF_DSPENHMETAFILE;
+}
+
+int32_t get_clipboard_formats(uint32_t *buffer, int32_t size) {
+    if (!OpenClipboard(NULL)) {
+        return -1;
+    }
+
+    int32_t count = 0;
+    UINT format = EnumClipboardFormats(0);
+    while (format != 0 && count < size) {
+        if (!is_gdi_clipboard_format(format)) {
+            buffer[count] = format;
+            count++;
+        }
+        format = EnumClipboardFormats(format);
+    }
+
+    CloseClipboard();
+
+    return count;
+}
+
+int32_t get_clipboard_format_data(uint32_t format, uint8_t *buffer, int32_t size) {
+    if (!OpenClipboard(NULL)) {
+        return -1;
+    }
+
+    int32_t result = -2;
+    HANDLE hData = GetClipboardData(format);
+    if (hData) {
+        SIZE_T data_size = GlobalSize(hData);
+        void *data = GlobalLock(hData);
+        if (data) {
+            if (buffer != NULL && (SIZE_T) size >= data_size) {
+                memcpy(buffer, data, data_size);
+            }
+            result = (int32_t) data_size;
+            GlobalUnlock(hData);
+        }
+    }
+
+    CloseClipboard();
+
+    return result;
+}
+
+int32_t set_clipboard_formats_data(uint32_t *formats, uint8_t **data, int32_t *sizes, int32_t count) {
+    if (!OpenClipboard(NULL)) {
+        return -1;
+    }
+
+    EmptyClipboard();
+
+    int32_t result = 1;
+    for (int32_t i = 0; i < count; i++) {
+        HGLOBAL hMem = GlobalAlloc(GMEM_MOVEABLE, sizes[i]);
+        memcpy(GlobalLock(hMem), data[i], sizes[i]);
+        GlobalUnlock(hMem);
+
+        if (!SetClipboardData(formats[i], hMem)) {
+            GlobalFree(hMem);
+            result = -2;
+        }
+    }
+
+    CloseClipboard();
+
+    return result;
+}
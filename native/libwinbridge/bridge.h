@@ -156,4 +156,27 @@ extern "C" int32_t set_clipboard(wchar_t * text);
  */
 extern "C" int32_t set_clipboard_image(wchar_t * path);
 
+/*
+ * Set the clipboard HTML, with a plain text fallback.
+ * The html must be already in the CF_HTML format, encoded in UTF-8
+ */
+extern "C" int32_t set_clipboard_html(char * html, wchar_t * text_fallback);
+
+/*
+ * Write the formats currently available in the clipboard in the given buffer,
+ * returning their number. Only the formats backed by global memory are considered.
+ */
+extern "C" int32_t get_clipboard_formats(uint32_t * buffer, int32_t size);
+
+/*
+ * Copy the clipboard data for the given format in the buffer, returning the size of the data.
+ * If the buffer is too small, nothing is copied, so it can be used to query the size.
+ */
+extern "C" int32_t get_clipboard_format_data(uint32_t format, uint8_t * buffer, int32_t size);
+
+/*
+ * Replace the clipboard content with the given formats and data
+ */
+extern "C" int32_t set_clipboard_formats_data(uint32_t * formats, uint8_t ** data, int32_t * sizes, int32_t count);
+
 #endif //ESPANSO_BRIDGE_H
\ No newline at end of file
@@ -33,13 +33,20 @@ extern {
     pub fn get_active_window_executable(buffer: *mut c_char, size: i32) -> i32;
     pub fn is_current_window_special() -> i32;
 
+    // Secure input
+    pub fn start_secure_input_monitor() -> i32;
+    pub fn is_password_field_focused() -> i32;
+
     // Keyboard
     pub fn register_keypress_callback(cb: extern fn(_self: *mut c_void, *const u8,
-                                                i32, i32, i32));
+                                                i32, i32, i32, i32));
 
     pub fn send_string(string: *const c_char);
     pub fn delete_string(count: i32);
     pub fn left_arrow(count: i32);
+    pub fn right_arrow(count: i32);
+    pub fn up_arrow(count: i32);
+    pub fn down_arrow(count: i32);
     pub fn trigger_paste();
     pub fn trigger_terminal_paste();
     pub fn trigger_shift_ins_paste();
@@ -44,6 +44,11 @@ extern {
     pub fn get_clipboard(buffer: *mut c_char, size: i32) -> i32;
     pub fn set_clipboard(text: *const c_char) -> i32;
     pub fn set_clipboard_image(path: *const c_char) -> i32;
+    pub fn set_clipboard_html(html: *const c_char, text_fallback: *const c_char) -> i32;
+    pub fn get_clipboard_types(buffer: *mut c_char, size: i32) -> i32;
+    pub fn get_clipboard_type_data(clipboard_type: *const c_char, buffer: *mut u8, size: i32) -> i32;
+    pub fn set_clipboard_types_data(types: *const *const c_char, data: *const *const u8,
+                                    sizes: *const i32, count: i32) -> i32;
 
     // UI
     pub fn register_icon_click_callback(cb: extern fn(_self: *mut c_void));
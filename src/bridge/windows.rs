@@ -17,7 +17,7 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::os::raw::{c_void};
+use std::os::raw::{c_void, c_char};
 
 #[repr(C)]
 pub struct WindowsMenuItem {
@@ -48,6 +48,11 @@ extern {
     pub fn get_clipboard(buffer: *mut u16, size: i32) -> i32;
     pub fn set_clipboard(payload: *const u16) -> i32;
     pub fn set_clipboard_image(path: *const u16) -> i32;
+    pub fn set_clipboard_html(html: *const c_char, text_fallback: *const u16) -> i32;
+    pub fn get_clipboard_formats(buffer: *mut u32, size: i32) -> i32;
+    pub fn get_clipboard_format_data(format: u32, buffer: *mut u8, size: i32) -> i32;
+    pub fn set_clipboard_formats_data(formats: *const u32, data: *const *const u8,
+                                      sizes: *const i32, count: i32) -> i32;
 
     // KEYBOARD
     pub fn register_keypress_callback(cb: extern fn(_self: *mut c_void, *const u16,
@@ -35,13 +35,15 @@ pub fn check_dependencies() -> bool {
         result = false;
     }
 
-    // Make sure xclip is installed
-    let status = Command::new("xclip")
-        .arg("-version")
-        .output();
-    if status.is_err() {
-        println!("Error: 'xclip' command is needed for espanso to work correctly, please install it.");
-        result = false;
+    // On Wayland the clipboard is managed with wl-clipboard
+    if crate::clipboard::is_wayland_session() {
+        let status = Command::new("wl-paste")
+            .arg("--version")
+            .output();
+        if status.is_err() {
+            println!("Error: 'wl-clipboard' is needed for espanso to work correctly on Wayland, please install it.");
+            result = false;
+        }
     }
 
     result
@@ -17,52 +17,26 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use std::process::{Command, Stdio};
-use std::io::{Write};
 use log::{error, warn};
 use std::path::Path;
+use super::{ClipboardSnapshot, ClipboardEntry, text_entries};
+use super::xselection::{X11Clipboard, META_TARGETS, TEXT_TARGETS};
+use std::time::Duration;
 
-pub struct LinuxClipboardManager {}
+pub struct LinuxClipboardManager {
+    clipboard: X11Clipboard,
+}
 
 impl super::ClipboardManager for LinuxClipboardManager {
     fn get_clipboard(&self) -> Option<String>  {
-        let res = Command::new("xclip")
-            .args(&["-o", "-sel", "clip"])
-            .output();
-
-        if let Ok(output) = res {
-            if output.status.success() {
-                let s = String::from_utf8_lossy(&output.stdout);
-                return Some((*s).to_owned());
-            }
-        }
-
-        None
+        TEXT_TARGETS.iter()
+            .filter_map(|target| self.clipboard.get_target(target))
+            .next()
+            .map(|data| String::from_utf8_lossy(&data).into_owned())
     }
 
     fn set_clipboard(&self, payload: &str) {
-        let res = Command::new("xclip")
-            .args(&["-sel", "clip"])
-            .stdin(Stdio::piped())
-            .spawn();
-
-        if let Ok(mut child) = res {
-            let stdin = child.stdin.as_mut();
-
-            if let Some(output) = stdin {
-                let res = output.write_all(payload.as_bytes());
-
-                if let Err(e) = res {
-                    error!("Could not set clipboard: {}", e);
-                }
-
-                let res = child.wait();
-
-                if let Err(e) = res {
-                    error!("Could not set clipboard: {}", e);
-                }
-            }
-        }
+        self.clipboard.set_entries(text_entries(payload));
     }
 
     fn set_clipboard_image(&self, image_path: &Path) {
@@ -81,16 +55,63 @@ impl super::ClipboardManager for LinuxClipboardManager {
             None => {"image/png"},
         };
 
-        let image_path = image_path.to_string_lossy().into_owned();
+        match std::fs::read(image_path) {
+            Ok(data) => {
+                self.clipboard.set_entries(vec![ClipboardEntry {
+                    format: mime.to_owned(),
+                    data,
+                }]);
+            },
+            Err(e) => {
+                error!("Could not read image {:?}: {}", image_path, e);
+            },
+        }
+    }
+
+    fn set_clipboard_html(&self, html: &str, text_fallback: &str) {
+        let mut entries = vec![ClipboardEntry {
+            format: "text/html".to_owned(),
+            data: html.as_bytes().to_vec(),
+        }];
+        entries.extend(text_entries(text_fallback));
+
+        self.clipboard.set_entries(entries);
+    }
+
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot> {
+        let targets = self.clipboard.get_targets()?;
+
+        let entries : Vec<ClipboardEntry> = targets.iter()
+            .filter(|target| !META_TARGETS.contains(&target.as_str()))
+            .filter_map(|target| {
+                self.clipboard.get_target(target).map(|data| ClipboardEntry {
+                    format: target.to_owned(),
+                    data,
+                })
+            })
+            .collect();
 
-        let res = Command::new("xclip")
-            .args(&["-selection", "clipboard", "-t", mime, "-i", &image_path])
-            .spawn();
+        if entries.is_empty() {
+            None
+        }else{
+            Some(ClipboardSnapshot { entries })
+        }
+    }
+
+    fn restore_snapshot(&self, snapshot: &ClipboardSnapshot) {
+        if !self.clipboard.set_entries(snapshot.entries.clone()) {
+            warn!("Could not restore the clipboard content");
+        }
+    }
+
+    fn wait_for_paste(&self, timeout: Duration) -> bool {
+        self.clipboard.wait_for_request(timeout)
     }
 }
 
 impl LinuxClipboardManager {
-    pub fn new() -> LinuxClipboardManager {
-        LinuxClipboardManager{}
+    pub fn new() -> Result<LinuxClipboardManager, String> {
+        let clipboard = X11Clipboard::new()?;
+        Ok(LinuxClipboardManager{clipboard})
     }
-}
\ No newline at end of file
+}
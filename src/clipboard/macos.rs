@@ -22,6 +22,7 @@ use crate::bridge::macos::*;
 use std::ffi::{CStr, CString};
 use std::path::Path;
 use log::{error, warn};
+use super::{ClipboardSnapshot, ClipboardEntry};
 
 pub struct MacClipboardManager {
 
@@ -67,6 +68,78 @@ impl super::ClipboardManager for MacClipboardManager {
             }
         }
     }
+
+    fn set_clipboard_html(&self, html: &str, text_fallback: &str) {
+        let html = CString::new(html);
+        let text_fallback = CString::new(text_fallback);
+        if let (Ok(html), Ok(text_fallback)) = (html, text_fallback) {
+            unsafe {
+                let result = set_clipboard_html(html.as_ptr(), text_fallback.as_ptr());
+                if result != 1 {
+                    warn!("Couldn't set clipboard html")
+                }
+            }
+        }
+    }
+
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot> {
+        let types = unsafe {
+            let mut buffer : [c_char; 4000] = [0; 4000];
+            let res = get_clipboard_types(buffer.as_mut_ptr(), buffer.len() as i32);
+            if res <= 0 {
+                return None;
+            }
+
+            CStr::from_ptr(buffer.as_ptr()).to_string_lossy().into_owned()
+        };
+
+        let entries: Vec<ClipboardEntry> = types.lines().filter_map(|clipboard_type| {
+            let type_c = CString::new(clipboard_type).ok()?;
+
+            // The first call is used to query the size of the data
+            let size = unsafe { get_clipboard_type_data(type_c.as_ptr(), std::ptr::null_mut(), 0) };
+            if size < 0 {
+                return None;
+            }
+
+            let mut data = vec![0u8; size as usize];
+            let res = unsafe {
+                get_clipboard_type_data(type_c.as_ptr(), data.as_mut_ptr(), data.len() as i32)
+            };
+            if res != size {
+                return None;
+            }
+
+            Some(ClipboardEntry {
+                format: clipboard_type.to_owned(),
+                data,
+            })
+        }).collect();
+
+        Some(ClipboardSnapshot { entries })
+    }
+
+    fn restore_snapshot(&self, snapshot: &ClipboardSnapshot) {
+        let types: Vec<CString> = snapshot.entries.iter().filter_map(|entry| {
+            CString::new(entry.format.clone()).ok()
+        }).collect();
+        if types.len() != snapshot.entries.len() || types.is_empty() {
+            error!("Couldn't restore clipboard content, invalid types");
+            return;
+        }
+
+        let types_ptr: Vec<*const c_char> = types.iter().map(|t| t.as_ptr()).collect();
+        let data: Vec<*const u8> = snapshot.entries.iter().map(|entry| entry.data.as_ptr()).collect();
+        let sizes: Vec<i32> = snapshot.entries.iter().map(|entry| entry.data.len() as i32).collect();
+
+        unsafe {
+            let result = set_clipboard_types_data(types_ptr.as_ptr(), data.as_ptr(),
+                                                  sizes.as_ptr(), types.len() as i32);
+            if result != 1 {
+                warn!("Couldn't restore clipboard content")
+            }
+        }
+    }
 }
 
 impl MacClipboardManager {
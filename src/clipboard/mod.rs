@@ -18,6 +18,9 @@
  */
 
 use std::path::Path;
+use std::time::Duration;
+#[cfg(target_os = "linux")]
+use log::error;
 
 #[cfg(target_os = "windows")]
 mod windows;
@@ -25,6 +28,12 @@ mod windows;
 #[cfg(target_os = "linux")]
 mod linux;
 
+#[cfg(target_os = "linux")]
+mod xselection;
+
+#[cfg(target_os = "linux")]
+mod wayland;
+
 #[cfg(target_os = "macos")]
 mod macos;
 
@@ -32,12 +41,137 @@ pub trait ClipboardManager {
     fn get_clipboard(&self) -> Option<String>;
     fn set_clipboard(&self, payload: &str);
     fn set_clipboard_image(&self, image_path: &Path);
+
+    // Set the clipboard as rich text, the fallback is used by the applications
+    // that only accept plain text
+    fn set_clipboard_html(&self, html: &str, text_fallback: &str);
+
+    // Save the clipboard content in all the formats it is offered in
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot>;
+    fn restore_snapshot(&self, snapshot: &ClipboardSnapshot);
+
+    // Wait until the content of the last set_clipboard* call has been pasted, returning
+    // false if it could not be confirmed. Not all the platforms support it.
+    fn wait_for_paste(&self, _timeout: Duration) -> bool {
+        false
+    }
+}
+
+// Used to choose the clipboard manager at runtime
+impl <T: ClipboardManager + ?Sized> ClipboardManager for Box<T> {
+    fn get_clipboard(&self) -> Option<String> {
+        (**self).get_clipboard()
+    }
+
+    fn set_clipboard(&self, payload: &str) {
+        (**self).set_clipboard(payload)
+    }
+
+    fn set_clipboard_image(&self, image_path: &Path) {
+        (**self).set_clipboard_image(image_path)
+    }
+
+    fn set_clipboard_html(&self, html: &str, text_fallback: &str) {
+        (**self).set_clipboard_html(html, text_fallback)
+    }
+
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot> {
+        (**self).get_snapshot()
+    }
+
+    fn restore_snapshot(&self, snapshot: &ClipboardSnapshot) {
+        (**self).restore_snapshot(snapshot)
+    }
+
+    fn wait_for_paste(&self, timeout: Duration) -> bool {
+        (**self).wait_for_paste(timeout)
+    }
+}
+
+/// Content of the clipboard, in all the formats it was offered in.
+/// The format names are platform specific.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardSnapshot {
+    pub entries: Vec<ClipboardEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClipboardEntry {
+    pub format: String,
+    pub data: Vec<u8>,
 }
 
 // LINUX IMPLEMENTATION
 #[cfg(target_os = "linux")]
 pub fn get_manager() -> impl ClipboardManager {
-    linux::LinuxClipboardManager::new()
+    let manager : Box<dyn ClipboardManager> = if wayland::is_wayland_session() {
+        Box::new(wayland::WaylandClipboardManager::new())
+    }else{
+        match linux::LinuxClipboardManager::new() {
+            Ok(manager) => Box::new(manager),
+            Err(e) => {
+                error!("Unable to initialize the X11 clipboard: {}, clipboard expansions are disabled", e);
+                Box::new(UnavailableClipboardManager{})
+            },
+        }
+    };
+    manager
+}
+
+/// Used when the clipboard can't be accessed, so that the other expansions keep working
+#[cfg(target_os = "linux")]
+struct UnavailableClipboardManager {}
+
+#[cfg(target_os = "linux")]
+impl ClipboardManager for UnavailableClipboardManager {
+    fn get_clipboard(&self) -> Option<String> {
+        None
+    }
+
+    fn set_clipboard(&self, _payload: &str) {}
+
+    fn set_clipboard_image(&self, _image_path: &Path) {}
+
+    fn set_clipboard_html(&self, _html: &str, _text_fallback: &str) {}
+
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot> {
+        None
+    }
+
+    fn restore_snapshot(&self, _snapshot: &ClipboardSnapshot) {}
+}
+
+#[cfg(target_os = "linux")]
+pub use wayland::is_wayland_session;
+
+/// Choose the entry preserving most of the content: images first, then rich text and plain text.
+/// Used when only a single entry can be offered.
+#[cfg(target_os = "linux")]
+fn preferred_entry(entries: &[ClipboardEntry]) -> Option<&ClipboardEntry> {
+    use xselection::TEXT_TARGETS;
+
+    let priority = |format: &str| -> usize {
+        if format.starts_with("image/") {
+            0
+        }else if format == "text/html" {
+            1
+        }else if TEXT_TARGETS.contains(&format) {
+            2 + TEXT_TARGETS.iter().position(|t| *t == format).unwrap_or(0)
+        }else{
+            10
+        }
+    };
+
+    entries.iter().min_by_key(|entry| priority(&entry.format))
+}
+
+/// Entries serving the given text in all the common text targets
+#[cfg(target_os = "linux")]
+fn text_entries(text: &str) -> Vec<ClipboardEntry> {
+    xselection::TEXT_TARGETS.iter().map(|target| ClipboardEntry {
+        format: (*target).to_owned(),
+        data: text.as_bytes().to_vec(),
+    }).collect()
 }
 
 // WINDOWS IMPLEMENTATION
@@ -50,4 +184,43 @@ pub fn get_manager() -> impl ClipboardManager {
 #[cfg(target_os = "macos")]
 pub fn get_manager() -> impl ClipboardManager {
     macos::MacClipboardManager::new()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+#[cfg(target_os = "linux")]
+mod tests {
+    use super::*;
+    use super::xselection::TEXT_TARGETS;
+
+    fn entry(format: &str) -> ClipboardEntry {
+        ClipboardEntry {
+            format: format.to_owned(),
+            data: vec![],
+        }
+    }
+
+    #[test]
+    fn test_preferred_entry_image() {
+        let entries = vec![entry("text/plain"), entry("text/html"), entry("image/png")];
+        assert_eq!(preferred_entry(&entries).unwrap().format, "image/png");
+    }
+
+    #[test]
+    fn test_preferred_entry_text() {
+        let entries = vec![entry("application/x-custom"), entry("text/plain"), entry("text/plain;charset=utf-8")];
+        assert_eq!(preferred_entry(&entries).unwrap().format, "text/plain;charset=utf-8");
+    }
+
+    #[test]
+    fn test_preferred_entry_empty() {
+        assert!(preferred_entry(&[]).is_none());
+    }
+
+    #[test]
+    fn test_text_entries() {
+        let entries = text_entries("espanso");
+        assert_eq!(entries.len(), TEXT_TARGETS.len());
+        assert!(entries.iter().all(|entry| entry.data == b"espanso".to_vec()));
+        assert_eq!(entries[0].format, "UTF8_STRING");
+    }
+}
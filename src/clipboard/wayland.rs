@@ -0,0 +1,287 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Clipboard backend for Wayland sessions, based on the wl-copy and wl-paste tools.
+// wl-copy can only offer a single type, so the contents with multiple types are set
+// through XWayland, when the compositor shares its clipboard with the Wayland clients.
+// See: https://github.com/bugaevc/wl-clipboard
+
+use std::cell::Cell;
+use std::process::{Command, Stdio};
+use std::io::Write;
+use std::time::Duration;
+use log::{error, warn};
+use std::path::Path;
+use super::{ClipboardSnapshot, ClipboardEntry, preferred_entry, text_entries};
+use super::xselection::{X11Clipboard, META_TARGETS, TEXT_TARGETS};
+
+// Time the compositor has to share a content set through XWayland
+const XWAYLAND_SYNC_TIMEOUT : Duration = Duration::from_millis(200);
+
+/// Check if espanso is running in a Wayland session
+pub fn is_wayland_session() -> bool {
+    match std::env::var("WAYLAND_DISPLAY") {
+        Ok(display) => !display.is_empty(),
+        Err(_) => false,
+    }
+}
+
+pub struct WaylandClipboardManager {
+    xwayland: Option<X11Clipboard>,
+
+    // Whether the compositor shares the XWayland clipboard, checked on the first use
+    xwayland_shared: Cell<Option<bool>>,
+}
+
+impl super::ClipboardManager for WaylandClipboardManager {
+    fn get_clipboard(&self) -> Option<String>  {
+        let data = read_type(None)?;
+        Some(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    fn set_clipboard(&self, payload: &str) {
+        write_type(None, payload.as_bytes());
+    }
+
+    fn set_clipboard_image(&self, image_path: &Path) {
+        // wl-copy detects the mime type from the content
+        match std::fs::read(image_path) {
+            Ok(data) => write_type(None, &data),
+            Err(e) => error!("Could not read image {:?}: {}", image_path, e),
+        }
+    }
+
+    fn set_clipboard_html(&self, html: &str, text_fallback: &str) {
+        let mut entries = vec![ClipboardEntry {
+            format: "text/html".to_owned(),
+            data: html.as_bytes().to_vec(),
+        }];
+        entries.extend(text_entries(text_fallback));
+
+        self.set_entries(&entries);
+    }
+
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot> {
+        let output = Command::new("wl-paste")
+            .arg("--list-types")
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let types = String::from_utf8_lossy(&output.stdout);
+        let entries : Vec<ClipboardEntry> = types.lines()
+            .map(|mime| mime.trim())
+            .filter(|mime| !mime.is_empty() && !META_TARGETS.contains(mime))
+            .filter_map(|mime| {
+                read_type(Some(mime)).map(|data| ClipboardEntry {
+                    format: mime.to_owned(),
+                    data,
+                })
+            })
+            .collect();
+
+        if entries.is_empty() {
+            None
+        }else{
+            Some(ClipboardSnapshot { entries })
+        }
+    }
+
+    fn restore_snapshot(&self, snapshot: &ClipboardSnapshot) {
+        self.set_entries(&snapshot.entries);
+    }
+}
+
+impl WaylandClipboardManager {
+    pub fn new() -> WaylandClipboardManager {
+        // XWayland is not always available, for example when it's started on demand
+        let xwayland = match X11Clipboard::new() {
+            Ok(clipboard) => Some(clipboard),
+            Err(e) => {
+                warn!("XWayland clipboard not available: {}, only a single type will be offered", e);
+                None
+            },
+        };
+
+        WaylandClipboardManager {
+            xwayland,
+            xwayland_shared: Cell::new(None),
+        }
+    }
+
+    /// Offer all the given entries through XWayland if possible, otherwise
+    /// only the richest one is set with wl-copy
+    fn set_entries(&self, entries: &[ClipboardEntry]) {
+        let preferred = match preferred_entry(entries) {
+            Some(entry) => entry,
+            None => {
+                warn!("Could not set the clipboard content, no entries available");
+                return;
+            },
+        };
+
+        if let Some(clipboard) = &self.xwayland {
+            if self.xwayland_shared.get() != Some(false) && clipboard.set_entries(entries.to_vec()) {
+                if self.xwayland_shared.get().is_none() {
+                    let shared = is_shared_with_wayland(preferred);
+                    if !shared {
+                        warn!("The compositor doesn't share the XWayland clipboard, only a single type will be offered");
+                    }
+                    self.xwayland_shared.set(Some(shared));
+                }
+
+                if self.xwayland_shared.get() == Some(true) {
+                    return;
+                }
+            }
+        }
+
+        write_type(entry_type(preferred), &preferred.data);
+    }
+}
+
+/// Check if the Wayland clients can read the given entry, after it was set through XWayland
+fn is_shared_with_wayland(entry: &ClipboardEntry) -> bool {
+    let deadline = std::time::Instant::now() + XWAYLAND_SYNC_TIMEOUT;
+    loop {
+        if read_type(entry_type(entry)).as_ref() == Some(&entry.data) {
+            return true;
+        }
+
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// The mime type passed to wl-copy and wl-paste, the text one is left to them
+fn entry_type(entry: &ClipboardEntry) -> Option<&str> {
+    if TEXT_TARGETS.contains(&entry.format.as_str()) {
+        None
+    }else{
+        Some(&entry.format)
+    }
+}
+
+/// Read the content of the clipboard for the given mime type, or the default text one
+fn read_type(mime: Option<&str>) -> Option<Vec<u8>> {
+    let mut command = Command::new("wl-paste");
+    command.arg("--no-newline");
+    if let Some(mime) = mime {
+        command.args(&["--type", mime]);
+    }
+
+    let output = command.output().ok()?;
+    if output.status.success() && !output.stdout.is_empty() {
+        Some(output.stdout)
+    }else{
+        None
+    }
+}
+
+/// Set the clipboard content with the given mime type, or let wl-copy detect it
+fn write_type(mime: Option<&str>, data: &[u8]) {
+    let mut command = Command::new("wl-copy");
+    if let Some(mime) = mime {
+        command.args(&["--type", mime]);
+    }
+
+    // wl-copy forks in the background to serve the content, so waiting is safe
+    let res = command.stdin(Stdio::piped()).spawn();
+    match res {
+        Ok(mut child) => {
+            if let Some(stdin) = child.stdin.as_mut() {
+                if let Err(e) = stdin.write_all(data) {
+                    error!("Could not set clipboard: {}", e);
+                }
+            }
+
+            // Close stdin, so that wl-copy knows the content is complete
+            drop(child.stdin.take());
+
+            if let Err(e) = child.wait() {
+                error!("Could not set clipboard: {}", e);
+            }
+        },
+        Err(e) => {
+            error!("Could not run wl-copy: {}", e);
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::ClipboardManager;
+
+    fn entry(format: &str) -> ClipboardEntry {
+        ClipboardEntry {
+            format: format.to_owned(),
+            data: vec![],
+        }
+    }
+
+    #[test]
+    fn test_entry_type() {
+        assert_eq!(entry_type(&entry("UTF8_STRING")), None);
+        assert_eq!(entry_type(&entry("text/plain;charset=utf-8")), None);
+        assert_eq!(entry_type(&entry("text/html")), Some("text/html"));
+        assert_eq!(entry_type(&entry("image/png")), Some("image/png"));
+    }
+
+    // These tests require a Wayland compositor and wl-clipboard, run them with a headless one:
+    // weston --backend=headless-backend.so & WAYLAND_DISPLAY=wayland-0 cargo test -- --ignored
+
+    #[test]
+    #[ignore]
+    fn test_set_and_get_clipboard() {
+        let manager = WaylandClipboardManager::new();
+        manager.set_clipboard("espanso");
+        assert_eq!(manager.get_clipboard().unwrap(), "espanso");
+    }
+
+    #[test]
+    #[ignore]
+    fn test_snapshot_roundtrip() {
+        let manager = WaylandClipboardManager::new();
+        manager.set_clipboard_html("<b>espanso</b>", "espanso");
+
+        let snapshot = manager.get_snapshot().unwrap();
+        manager.set_clipboard("other");
+        manager.restore_snapshot(&snapshot);
+
+        let restored = manager.get_snapshot().unwrap();
+        let html = restored.entries.iter().find(|entry| entry.format == "text/html").unwrap();
+        assert_eq!(html.data, b"<b>espanso</b>".to_vec());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_html_offers_plain_text() {
+        // Requires XWayland as well
+        let manager = WaylandClipboardManager::new();
+        manager.set_clipboard_html("<b>espanso</b>", "espanso");
+
+        assert_eq!(read_type(Some("text/html")).unwrap(), b"<b>espanso</b>".to_vec());
+        assert_eq!(read_type(Some("text/plain")).unwrap(), b"espanso".to_vec());
+    }
+}
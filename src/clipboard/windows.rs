@@ -18,8 +18,11 @@
  */
 
 use widestring::U16CString;
-use crate::bridge::windows::{set_clipboard, get_clipboard, set_clipboard_image};
+use crate::bridge::windows::*;
+use super::{ClipboardSnapshot, ClipboardEntry};
 use std::path::Path;
+use std::ffi::CString;
+use log::warn;
 
 pub struct WindowsClipboardManager {
 
@@ -62,4 +65,117 @@ impl super::ClipboardManager for WindowsClipboardManager {
             set_clipboard_image(payload_c.as_ptr());
         }
     }
+
+    fn set_clipboard_html(&self, html: &str, text_fallback: &str) {
+        let html_c = CString::new(to_cf_html(html));
+        let fallback_c = U16CString::from_str(text_fallback);
+        if let (Ok(html_c), Ok(fallback_c)) = (html_c, fallback_c) {
+            unsafe {
+                let result = set_clipboard_html(html_c.as_ptr(), fallback_c.as_ptr());
+                if result != 1 {
+                    warn!("Couldn't set clipboard html: {}", result)
+                }
+            }
+        }
+    }
+
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot> {
+        let mut formats : [u32; 64] = [0; 64];
+        let count = unsafe {
+            get_clipboard_formats(formats.as_mut_ptr(), formats.len() as i32)
+        };
+        if count <= 0 {
+            return None;
+        }
+
+        let entries: Vec<ClipboardEntry> = formats[..count as usize].iter().filter_map(|format| {
+            // The first call is used to query the size of the data
+            let size = unsafe { get_clipboard_format_data(*format, std::ptr::null_mut(), 0) };
+            if size < 0 {
+                return None;
+            }
+
+            let mut data = vec![0u8; size as usize];
+            let res = unsafe {
+                get_clipboard_format_data(*format, data.as_mut_ptr(), data.len() as i32)
+            };
+            if res != size {
+                return None;
+            }
+
+            Some(ClipboardEntry {
+                format: format.to_string(),
+                data,
+            })
+        }).collect();
+
+        Some(ClipboardSnapshot { entries })
+    }
+
+    fn restore_snapshot(&self, snapshot: &ClipboardSnapshot) {
+        let entries: Vec<&ClipboardEntry> = snapshot.entries.iter().filter(|entry| {
+            entry.format.parse::<u32>().is_ok()
+        }).collect();
+        if entries.is_empty() {
+            return;
+        }
+
+        let formats: Vec<u32> = entries.iter().map(|entry| entry.format.parse().unwrap()).collect();
+        let data: Vec<*const u8> = entries.iter().map(|entry| entry.data.as_ptr()).collect();
+        let sizes: Vec<i32> = entries.iter().map(|entry| entry.data.len() as i32).collect();
+
+        unsafe {
+            let result = set_clipboard_formats_data(formats.as_ptr(), data.as_ptr(),
+                                                    sizes.as_ptr(), entries.len() as i32);
+            if result != 1 {
+                warn!("Couldn't restore clipboard content: {}", result)
+            }
+        }
+    }
+}
+
+/// Wrap the html fragment in the CF_HTML format, which requires a header
+/// with the byte offsets of the document and the fragment.
+/// See: https://docs.microsoft.com/en-us/windows/win32/dataxchg/html-clipboard-format
+fn to_cf_html(html: &str) -> String {
+    const HEADER_TEMPLATE: &str = "Version:0.9\r\nStartHTML:{sh}\r\nEndHTML:{eh}\r\nStartFragment:{sf}\r\nEndFragment:{ef}\r\n";
+    const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+    const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+
+    // The offsets are formatted with a fixed width, so the header length is constant
+    let format_offset = |offset: usize| format!("{:010}", offset);
+    let header_len = HEADER_TEMPLATE.len() - "{sh}{eh}{sf}{ef}".len() + 4 * 10;
+
+    let start_html = header_len;
+    let start_fragment = start_html + PREFIX.len();
+    let end_fragment = start_fragment + html.len();
+    let end_html = end_fragment + SUFFIX.len();
+
+    let header = HEADER_TEMPLATE
+        .replace("{sh}", &format_offset(start_html))
+        .replace("{eh}", &format_offset(end_html))
+        .replace("{sf}", &format_offset(start_fragment))
+        .replace("{ef}", &format_offset(end_fragment));
+
+    format!("{}{}{}{}", header, PREFIX, html, SUFFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_cf_html_offsets() {
+        let html = "<b>espanso</b>";
+        let cf_html = to_cf_html(html);
+
+        let offset = |name: &str| -> usize {
+            let start = cf_html.find(name).unwrap() + name.len() + 1;
+            cf_html[start..start+10].parse().unwrap()
+        };
+
+        assert_eq!(&cf_html[offset("StartFragment")..offset("EndFragment")], html);
+        assert!(cf_html[offset("StartHTML")..].starts_with("<html>"));
+        assert_eq!(offset("EndHTML"), cf_html.len());
+    }
 }
\ No newline at end of file
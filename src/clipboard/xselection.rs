@@ -0,0 +1,839 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Native implementation of the X11 CLIPBOARD selection, which replaces the xclip dependency.
+// The selection is owned by a hidden window managed by a dedicated thread, so that the content
+// is served for as long as espanso is running, in all the targets it was set with.
+// See: https://tronche.com/gui/x/icccm/sec-2.html
+
+use std::cell::{Cell, RefCell};
+use std::convert::TryInto;
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::os::raw::{c_int, c_long, c_short, c_uchar, c_ulong, c_void};
+use std::os::unix::io::RawFd;
+use std::ptr;
+use std::sync::mpsc::{channel, Sender, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+use log::{error, warn};
+use x11::xlib;
+use super::ClipboardEntry;
+
+// Maximum time to wait for the selection owner to answer a request
+const CONVERSION_TIMEOUT : Duration = Duration::from_millis(1000);
+
+// Contents bigger than this are served incrementally, in chunks of this size
+const INCR_CHUNK_SIZE : usize = 64 * 1024;
+
+// Incremental transfers the requestor stopped reading are dropped after this time
+const INCR_TIMEOUT : Duration = Duration::from_secs(5);
+
+// Targets describing the selection itself, rather than its content
+pub const META_TARGETS : &[&str] = &["TARGETS", "TIMESTAMP", "MULTIPLE", "SAVE_TARGETS", "DELETE",
+                                     "INSERT_PROPERTY", "INSERT_SELECTION"];
+
+// Targets used to serve plain text, in order of preference
+pub const TEXT_TARGETS : &[&str] = &["UTF8_STRING", "text/plain;charset=utf-8", "text/plain", "STRING", "TEXT"];
+
+enum OwnerCommand {
+    SetContent {
+        entries: Vec<ClipboardEntry>,
+        generation: u64,
+        reply: Sender<bool>,
+    },
+}
+
+enum OwnerNotification {
+    Served { generation: u64 },
+    Lost { generation: u64 },
+}
+
+#[derive(Clone, Copy)]
+struct Atoms {
+    clipboard: xlib::Atom,
+    targets: xlib::Atom,
+    incr: xlib::Atom,
+    text: xlib::Atom,
+    utf8_string: xlib::Atom,
+    property: xlib::Atom,
+}
+
+impl Atoms {
+    fn new(display: *mut xlib::Display) -> Atoms {
+        Atoms {
+            clipboard: intern_atom(display, "CLIPBOARD"),
+            targets: intern_atom(display, "TARGETS"),
+            incr: intern_atom(display, "INCR"),
+            text: intern_atom(display, "TEXT"),
+            utf8_string: intern_atom(display, "UTF8_STRING"),
+            property: intern_atom(display, "ESPANSO_SELECTION"),
+        }
+    }
+}
+
+pub struct X11Clipboard {
+    // Connection used to read the selection, only used by the calling thread
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    atoms: Atoms,
+
+    owner_window: xlib::Window,
+    commands: Sender<OwnerCommand>,
+    notifications: Receiver<OwnerNotification>,
+    wake_fd: RawFd,  // Used to wake up the owner thread when a command is sent
+
+    generation: Cell<u64>,  // Incremented every time the content is changed
+    content: RefCell<Vec<ClipboardEntry>>,
+}
+
+impl X11Clipboard {
+    pub fn new() -> Result<X11Clipboard, String> {
+        let display = unsafe { xlib::XOpenDisplay(ptr::null()) };
+        if display.is_null() {
+            return Err("unable to connect to the X11 server".to_owned());
+        }
+
+        let window = unsafe { create_window(display) };
+        let atoms = Atoms::new(display);
+
+        let mut pipe_fds : [c_int; 2] = [0; 2];
+        if unsafe { libc::pipe(pipe_fds.as_mut_ptr()) } != 0 {
+            unsafe { xlib::XCloseDisplay(display); }
+            return Err("unable to create the clipboard wake pipe".to_owned());
+        }
+
+        let (command_sender, command_receiver) = channel();
+        let (notification_sender, notification_receiver) = channel();
+        let (startup_sender, startup_receiver) = channel();
+        let wake_read_fd = pipe_fds[0];
+
+        std::thread::Builder::new().name("clipboard_owner".to_string()).spawn(move || {
+            owner_main(command_receiver, notification_sender, wake_read_fd, startup_sender);
+        }).expect("Unable to spawn clipboard owner thread");
+
+        let owner_window = match startup_receiver.recv() {
+            Ok(Ok(owner_window)) => owner_window,
+            Ok(Err(e)) => {
+                unsafe {
+                    libc::close(pipe_fds[1]);
+                    xlib::XCloseDisplay(display);
+                }
+                return Err(e);
+            },
+            Err(_) => {
+                unsafe {
+                    libc::close(pipe_fds[1]);
+                    xlib::XCloseDisplay(display);
+                }
+                return Err("clipboard owner thread terminated unexpectedly".to_owned());
+            },
+        };
+
+        Ok(X11Clipboard {
+            display,
+            window,
+            atoms,
+            owner_window,
+            commands: command_sender,
+            notifications: notification_receiver,
+            wake_fd: pipe_fds[1],
+            generation: Cell::new(0),
+            content: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// Take ownership of the CLIPBOARD selection, serving the given entries.
+    /// The entry formats are the target names, such as "UTF8_STRING" or "image/png".
+    pub fn set_entries(&self, entries: Vec<ClipboardEntry>) -> bool {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+
+        // Discard the notifications related to the previous content
+        while self.notifications.try_recv().is_ok() {}
+
+        let (reply_sender, reply_receiver) = channel();
+        let command = OwnerCommand::SetContent {
+            entries: entries.clone(),
+            generation,
+            reply: reply_sender,
+        };
+
+        if self.commands.send(command).is_err() {
+            error!("Clipboard owner thread is not running");
+            return false;
+        }
+        self.wake_owner();
+
+        self.content.replace(entries);
+
+        match reply_receiver.recv_timeout(CONVERSION_TIMEOUT) {
+            Ok(owned) => {
+                if !owned {
+                    warn!("Unable to take ownership of the clipboard");
+                }
+                owned
+            },
+            Err(_) => {
+                error!("Clipboard owner thread did not answer");
+                false
+            },
+        }
+    }
+
+    /// Return the targets the current clipboard content is offered in
+    pub fn get_targets(&self) -> Option<Vec<String>> {
+        if self.is_owner() {
+            let content = self.content.borrow();
+            return Some(content.iter().map(|entry| entry.format.clone()).collect());
+        }
+
+        let property = self.convert_selection(self.atoms.targets)?;
+        if property.format != 32 {
+            return None;
+        }
+
+        let targets = property.data.chunks_exact(mem::size_of::<xlib::Atom>())
+            .map(|chunk| xlib::Atom::from_ne_bytes(chunk.try_into().unwrap()))
+            .filter_map(|atom| atom_name(self.display, atom))
+            .collect();
+
+        Some(targets)
+    }
+
+    /// Return the clipboard content for the given target
+    pub fn get_target(&self, target: &str) -> Option<Vec<u8>> {
+        if self.is_owner() {
+            let content = self.content.borrow();
+            return content.iter().find(|entry| entry.format == target)
+                .map(|entry| entry.data.clone());
+        }
+
+        let property = self.convert_selection(intern_atom(self.display, target))?;
+
+        // Only byte oriented content can be served back as it is
+        if property.format == 8 {
+            Some(property.data)
+        }else{
+            None
+        }
+    }
+
+    /// Wait until a client requests the content set by the last set_entries call.
+    /// Return false if the timeout expired or another client took the ownership of the clipboard.
+    /// The requests served before this call are ignored, as they usually come from the clipboard
+    /// managers, but they may still request the content later on.
+    pub fn wait_for_request(&self, timeout: Duration) -> bool {
+        let generation = self.generation.get();
+        let deadline = Instant::now() + timeout;
+
+        while let Ok(notification) = self.notifications.try_recv() {
+            if let OwnerNotification::Lost { generation: lost } = notification {
+                if lost == generation {
+                    return false;
+                }
+            }
+        }
+
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+
+            match self.notifications.recv_timeout(deadline - now) {
+                Ok(OwnerNotification::Served { generation: served }) => {
+                    if served == generation {
+                        return true;
+                    }
+                },
+                Ok(OwnerNotification::Lost { generation: lost }) => {
+                    if lost == generation {
+                        return false;
+                    }
+                },
+                Err(_) => {
+                    return false;
+                },
+            }
+        }
+    }
+
+    fn is_owner(&self) -> bool {
+        unsafe {
+            xlib::XGetSelectionOwner(self.display, self.atoms.clipboard) == self.owner_window
+        }
+    }
+
+    fn wake_owner(&self) {
+        let buffer : [u8; 1] = [1];
+        unsafe {
+            libc::write(self.wake_fd, buffer.as_ptr() as *const c_void, 1);
+        }
+    }
+
+    /// Ask the selection owner to convert the selection to the given target,
+    /// and return the content of the resulting property.
+    fn convert_selection(&self, target: xlib::Atom) -> Option<Property> {
+        unsafe {
+            // Discard the property events left by the previous conversions
+            let mut event : xlib::XEvent = mem::zeroed();
+            while xlib::XCheckTypedWindowEvent(self.display, self.window, xlib::PropertyNotify, &mut event) != 0 {}
+
+            xlib::XConvertSelection(self.display, self.atoms.clipboard, target, self.atoms.property,
+                                    self.window, xlib::CurrentTime);
+            xlib::XFlush(self.display);
+
+            let event = self.wait_for_event(xlib::SelectionNotify, |event| {
+                let event : xlib::XSelectionEvent = From::from(*event);
+                event.target == target
+            })?;
+            let event : xlib::XSelectionEvent = From::from(event);
+
+            // The owner refused the conversion
+            if event.property == 0 {
+                return None;
+            }
+
+            let property = get_property(self.display, self.window, event.property)?;
+            if property.property_type != self.atoms.incr {
+                return Some(property);
+            }
+
+            // Large contents are transferred incrementally, in multiple chunks.
+            // The transfer starts when the INCR property is deleted, so the
+            // notifications received so far only refer to the INCR property.
+            let mut stale_event : xlib::XEvent = mem::zeroed();
+            while xlib::XCheckTypedWindowEvent(self.display, self.window, xlib::PropertyNotify, &mut stale_event) != 0 {}
+            let mut data = Vec::new();
+            loop {
+                let property_atom = event.property;
+                self.wait_for_event(xlib::PropertyNotify, |event| {
+                    let event : xlib::XPropertyEvent = From::from(*event);
+                    event.atom == property_atom && event.state == xlib::PropertyNewValue
+                })?;
+
+                let chunk = get_property(self.display, self.window, property_atom)?;
+                if chunk.data.is_empty() {
+                    return Some(Property {
+                        data,
+                        property_type: chunk.property_type,
+                        format: chunk.format,
+                    });
+                }
+
+                data.extend(chunk.data);
+            }
+        }
+    }
+
+    /// Wait for an event of the given type matching the predicate, discarding the others
+    unsafe fn wait_for_event<F>(&self, event_type: c_int, predicate: F) -> Option<xlib::XEvent>
+        where F: Fn(&xlib::XEvent) -> bool {
+        let deadline = Instant::now() + CONVERSION_TIMEOUT;
+        let mut event : xlib::XEvent = mem::zeroed();
+
+        loop {
+            while xlib::XCheckTypedWindowEvent(self.display, self.window, event_type, &mut event) != 0 {
+                if predicate(&event) {
+                    return Some(event);
+                }
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                warn!("Timed out while waiting for the clipboard owner");
+                return None;
+            }
+
+            wait_for_fds(&[xlib::XConnectionNumber(self.display)], Some(deadline - now));
+        }
+    }
+}
+
+impl Drop for X11Clipboard {
+    fn drop(&mut self) {
+        unsafe {
+            // Closing the pipe terminates the owner thread
+            libc::close(self.wake_fd);
+            xlib::XDestroyWindow(self.display, self.window);
+            xlib::XCloseDisplay(self.display);
+        }
+    }
+}
+
+struct Property {
+    data: Vec<u8>,
+    property_type: xlib::Atom,
+    format: c_int,
+}
+
+fn owner_main(commands: Receiver<OwnerCommand>, notifications: Sender<OwnerNotification>,
+              wake_fd: RawFd, startup: Sender<Result<xlib::Window, String>>) {
+    let display = unsafe { xlib::XOpenDisplay(ptr::null()) };
+    if display.is_null() {
+        unsafe { libc::close(wake_fd); }
+        startup.send(Err("unable to connect to the X11 server".to_owned())).unwrap_or_default();
+        return;
+    }
+
+    let window = unsafe { create_window(display) };
+    startup.send(Ok(window)).unwrap_or_default();
+
+    let mut owner = SelectionOwner {
+        display,
+        window,
+        atoms: Atoms::new(display),
+        entries: Vec::new(),
+        generation: 0,
+        owned: false,
+        notifications,
+        transfers: Vec::new(),
+    };
+
+    owner.run(&commands, wake_fd);
+
+    unsafe {
+        libc::close(wake_fd);
+        xlib::XDestroyWindow(display, window);
+        xlib::XCloseDisplay(display);
+    }
+}
+
+struct SelectionOwner {
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    atoms: Atoms,
+    entries: Vec<(xlib::Atom, Vec<u8>)>,
+    generation: u64,
+    owned: bool,
+    notifications: Sender<OwnerNotification>,
+    transfers: Vec<IncrTransfer>,
+}
+
+/// Content served incrementally, a chunk is written every time the requestor
+/// deletes the property to signal it read the previous one
+struct IncrTransfer {
+    requestor: xlib::Window,
+    property: xlib::Atom,
+    property_type: xlib::Atom,
+    data: Vec<u8>,
+    offset: usize,
+    generation: u64,
+    started: Instant,
+}
+
+impl SelectionOwner {
+    fn run(&mut self, commands: &Receiver<OwnerCommand>, wake_fd: RawFd) {
+        let connection_fd = unsafe { xlib::XConnectionNumber(self.display) };
+        let mut event : xlib::XEvent = unsafe { mem::zeroed() };
+
+        loop {
+            loop {
+                match commands.try_recv() {
+                    Ok(command) => self.handle_command(command),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+
+            unsafe {
+                while xlib::XPending(self.display) > 0 {
+                    xlib::XNextEvent(self.display, &mut event);
+                    self.handle_event(&event);
+                }
+            }
+
+            wait_for_fds(&[connection_fd, wake_fd], None);
+
+            // Consume the wake up signals. An empty read means the pipe was closed.
+            if is_readable(wake_fd) {
+                let mut buffer : [u8; 64] = [0; 64];
+                let read = unsafe { libc::read(wake_fd, buffer.as_mut_ptr() as *mut c_void, buffer.len()) };
+                if read <= 0 {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn handle_command(&mut self, command: OwnerCommand) {
+        match command {
+            OwnerCommand::SetContent { entries, generation, reply } => {
+                self.entries = entries.into_iter().map(|entry| {
+                    (intern_atom(self.display, &entry.format), entry.data)
+                }).collect();
+                self.generation = generation;
+
+                unsafe {
+                    xlib::XSetSelectionOwner(self.display, self.atoms.clipboard, self.window, xlib::CurrentTime);
+                    self.owned = xlib::XGetSelectionOwner(self.display, self.atoms.clipboard) == self.window;
+                }
+
+                reply.send(self.owned).unwrap_or_default();
+            },
+        }
+    }
+
+    unsafe fn handle_event(&mut self, event: &xlib::XEvent) {
+        match event.get_type() {
+            xlib::SelectionRequest => {
+                let request : xlib::XSelectionRequestEvent = From::from(*event);
+                self.handle_request(&request);
+            },
+            xlib::PropertyNotify => {
+                let notify : xlib::XPropertyEvent = From::from(*event);
+                if notify.state == xlib::PropertyDelete {
+                    self.continue_transfer(notify.window, notify.atom);
+                }
+            },
+            xlib::SelectionClear => {
+                let clear : xlib::XSelectionClearEvent = From::from(*event);
+                if clear.selection == self.atoms.clipboard {
+                    self.owned = false;
+                    self.notifications.send(OwnerNotification::Lost {
+                        generation: self.generation,
+                    }).unwrap_or_default();
+                }
+            },
+            _ => {},
+        }
+    }
+
+    unsafe fn handle_request(&mut self, request: &xlib::XSelectionRequestEvent) {
+        // Obsolete clients don't specify the property, in that case the target is used
+        let property = if request.property == 0 {
+            request.target
+        }else{
+            request.property
+        };
+
+        let served = if request.selection != self.atoms.clipboard || !self.owned {
+            false
+        }else if request.target == self.atoms.targets {
+            let mut targets : Vec<xlib::Atom> = vec![self.atoms.targets];
+            targets.extend(self.entries.iter().map(|(target, _)| *target));
+
+            xlib::XChangeProperty(self.display, request.requestor, property, xlib::XA_ATOM, 32,
+                                  xlib::PropModeReplace, targets.as_ptr() as *const c_uchar,
+                                  targets.len() as c_int);
+            true
+        }else{
+            self.serve_content(request.requestor, property, request.target)
+        };
+
+        let reply = xlib::XSelectionEvent {
+            type_: xlib::SelectionNotify,
+            serial: 0,
+            send_event: xlib::True,
+            display: self.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: if served { property } else { 0 },
+            time: request.time,
+        };
+        let mut reply = xlib::XEvent::from(reply);
+
+        xlib::XSendEvent(self.display, request.requestor, xlib::False, xlib::NoEventMask, &mut reply);
+        xlib::XFlush(self.display);
+    }
+
+    unsafe fn serve_content(&mut self, requestor: xlib::Window, property: xlib::Atom, target: xlib::Atom) -> bool {
+        let data = match self.entries.iter().find(|(entry_target, _)| *entry_target == target) {
+            Some((_, data)) => data,
+            None => return false,
+        };
+
+        let property_type = if target == self.atoms.text {
+            self.atoms.utf8_string
+        }else{
+            target
+        };
+
+        if data.len() > INCR_CHUNK_SIZE {
+            self.start_transfer(requestor, property, property_type, data.clone());
+            return true;
+        }
+
+        xlib::XChangeProperty(self.display, requestor, property, property_type, 8,
+                              xlib::PropModeReplace, data.as_ptr(), data.len() as c_int);
+
+        self.notifications.send(OwnerNotification::Served {
+            generation: self.generation,
+        }).unwrap_or_default();
+
+        true
+    }
+
+    /// Announce an incremental transfer, the chunks are written once the requestor
+    /// deletes the INCR property. See: https://tronche.com/gui/x/icccm/sec-2.html#s-2.7.2
+    unsafe fn start_transfer(&mut self, requestor: xlib::Window, property: xlib::Atom,
+                             property_type: xlib::Atom, data: Vec<u8>) {
+        // Drop the transfers abandoned by their requestors
+        self.transfers.retain(|transfer| {
+            transfer.started.elapsed() < INCR_TIMEOUT &&
+                !(transfer.requestor == requestor && transfer.property == property)
+        });
+
+        xlib::XSelectInput(self.display, requestor, xlib::PropertyChangeMask);
+
+        let size : [c_long; 1] = [data.len() as c_long];
+        xlib::XChangeProperty(self.display, requestor, property, self.atoms.incr, 32,
+                              xlib::PropModeReplace, size.as_ptr() as *const c_uchar, 1);
+
+        self.transfers.push(IncrTransfer {
+            requestor,
+            property,
+            property_type,
+            data,
+            offset: 0,
+            generation: self.generation,
+            started: Instant::now(),
+        });
+    }
+
+    /// Write the next chunk of the transfer waiting on the given property, if any.
+    /// An empty chunk marks the end of the transfer.
+    unsafe fn continue_transfer(&mut self, requestor: xlib::Window, property: xlib::Atom) {
+        let index = match self.transfers.iter().position(|transfer| {
+            transfer.requestor == requestor && transfer.property == property
+        }) {
+            Some(index) => index,
+            None => return,
+        };
+
+        let transfer = &mut self.transfers[index];
+        let end = (transfer.offset + INCR_CHUNK_SIZE).min(transfer.data.len());
+        let chunk = &transfer.data[transfer.offset..end];
+        xlib::XChangeProperty(self.display, requestor, property, transfer.property_type, 8,
+                              xlib::PropModeReplace, chunk.as_ptr(), chunk.len() as c_int);
+        xlib::XFlush(self.display);
+
+        if chunk.is_empty() {
+            let transfer = self.transfers.remove(index);
+            xlib::XSelectInput(self.display, requestor, xlib::NoEventMask);
+
+            self.notifications.send(OwnerNotification::Served {
+                generation: transfer.generation,
+            }).unwrap_or_default();
+        }else{
+            transfer.offset = end;
+        }
+    }
+}
+
+unsafe fn create_window(display: *mut xlib::Display) -> xlib::Window {
+    let root = xlib::XDefaultRootWindow(display);
+    let window = xlib::XCreateSimpleWindow(display, root, 0, 0, 1, 1, 0, 0, 0);
+    xlib::XSelectInput(display, window, xlib::PropertyChangeMask);
+    window
+}
+
+unsafe fn get_property(display: *mut xlib::Display, window: xlib::Window, property: xlib::Atom) -> Option<Property> {
+    let mut property_type : xlib::Atom = 0;
+    let mut format : c_int = 0;
+    let mut items : c_ulong = 0;
+    let mut bytes_after : c_ulong = 0;
+    let mut data : *mut c_uchar = ptr::null_mut();
+
+    // The property is deleted after reading, as required by the protocol
+    let res = xlib::XGetWindowProperty(display, window, property, 0, c_long::from(i32::max_value()) / 4,
+                                       xlib::True, xlib::AnyPropertyType as xlib::Atom,
+                                       &mut property_type, &mut format, &mut items,
+                                       &mut bytes_after, &mut data);
+
+    if res != c_int::from(xlib::Success) || property_type == 0 {
+        if !data.is_null() {
+            xlib::XFree(data as *mut c_void);
+        }
+        return None;
+    }
+
+    // Xlib returns the 16 and 32 bit items as native shorts and longs
+    let item_size = match format {
+        16 => mem::size_of::<c_short>(),
+        32 => mem::size_of::<c_long>(),
+        _ => 1,
+    };
+
+    let content = if data.is_null() {
+        Vec::new()
+    }else{
+        let content = std::slice::from_raw_parts(data, items as usize * item_size).to_vec();
+        xlib::XFree(data as *mut c_void);
+        content
+    };
+
+    Some(Property {
+        data: content,
+        property_type,
+        format,
+    })
+}
+
+fn intern_atom(display: *mut xlib::Display, name: &str) -> xlib::Atom {
+    let name = CString::new(name).unwrap_or_default();
+    unsafe {
+        xlib::XInternAtom(display, name.as_ptr(), xlib::False)
+    }
+}
+
+fn atom_name(display: *mut xlib::Display, atom: xlib::Atom) -> Option<String> {
+    unsafe {
+        let name = xlib::XGetAtomName(display, atom);
+        if name.is_null() {
+            return None;
+        }
+
+        let result = CStr::from_ptr(name).to_string_lossy().into_owned();
+        xlib::XFree(name as *mut c_void);
+        Some(result)
+    }
+}
+
+/// Block until one of the given file descriptors is readable or the timeout expires
+fn wait_for_fds(fds: &[RawFd], timeout: Option<Duration>) {
+    let mut poll_fds : Vec<libc::pollfd> = fds.iter().map(|fd| libc::pollfd {
+        fd: *fd,
+        events: libc::POLLIN,
+        revents: 0,
+    }).collect();
+
+    let timeout = match timeout {
+        Some(timeout) => timeout.as_millis() as c_int + 1,
+        None => -1,
+    };
+
+    unsafe {
+        libc::poll(poll_fds.as_mut_ptr(), poll_fds.len() as libc::nfds_t, timeout);
+    }
+}
+
+fn is_readable(fd: RawFd) -> bool {
+    let mut poll_fd = libc::pollfd {
+        fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    unsafe {
+        libc::poll(&mut poll_fd, 1, 0) > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // These tests require an X11 server, run them with:
+    // xvfb-run cargo test -- --ignored
+
+    fn entry(format: &str, data: &str) -> ClipboardEntry {
+        ClipboardEntry {
+            format: format.to_owned(),
+            data: data.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn test_set_and_get_from_another_client() {
+        let owner = X11Clipboard::new().unwrap();
+        let reader = X11Clipboard::new().unwrap();
+
+        assert!(owner.set_entries(vec![entry("UTF8_STRING", "espanso")]));
+        assert_eq!(reader.get_target("UTF8_STRING").unwrap(), b"espanso".to_vec());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_multiple_targets() {
+        let owner = X11Clipboard::new().unwrap();
+        let reader = X11Clipboard::new().unwrap();
+
+        assert!(owner.set_entries(vec![entry("text/html", "<b>espanso</b>"), entry("UTF8_STRING", "espanso")]));
+
+        let targets = reader.get_targets().unwrap();
+        assert!(targets.contains(&"text/html".to_owned()));
+        assert!(targets.contains(&"UTF8_STRING".to_owned()));
+        assert_eq!(reader.get_target("text/html").unwrap(), b"<b>espanso</b>".to_vec());
+        assert!(reader.get_target("image/png").is_none());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_ownership_lost() {
+        let first = X11Clipboard::new().unwrap();
+        let second = X11Clipboard::new().unwrap();
+
+        assert!(first.set_entries(vec![entry("UTF8_STRING", "first")]));
+        assert!(second.set_entries(vec![entry("UTF8_STRING", "second")]));
+
+        assert!(!first.wait_for_request(Duration::from_millis(500)));
+        assert_eq!(first.get_target("UTF8_STRING").unwrap(), b"second".to_vec());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_wait_for_request() {
+        let owner = X11Clipboard::new().unwrap();
+        let reader = X11Clipboard::new().unwrap();
+
+        assert!(owner.set_entries(vec![entry("UTF8_STRING", "espanso")]));
+        assert!(!owner.wait_for_request(Duration::from_millis(100)));
+
+        // The reader connection can't be shared between threads
+        drop(reader);
+        let handle = std::thread::spawn(|| {
+            std::thread::sleep(Duration::from_millis(100));
+            let reader = X11Clipboard::new().unwrap();
+            reader.get_target("UTF8_STRING").unwrap();
+        });
+        assert!(owner.wait_for_request(Duration::from_millis(1000)));
+        handle.join().unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_wait_for_request_ignores_previous_requests() {
+        let owner = X11Clipboard::new().unwrap();
+        let reader = X11Clipboard::new().unwrap();
+
+        // For example, a clipboard manager reading the new content
+        assert!(owner.set_entries(vec![entry("UTF8_STRING", "espanso")]));
+        reader.get_target("UTF8_STRING").unwrap();
+
+        assert!(!owner.wait_for_request(Duration::from_millis(200)));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_incremental_transfer() {
+        let owner = X11Clipboard::new().unwrap();
+        let reader = X11Clipboard::new().unwrap();
+
+        let data : Vec<u8> = (0..INCR_CHUNK_SIZE * 5 + 123).map(|i| (i % 251) as u8).collect();
+        assert!(owner.set_entries(vec![ClipboardEntry {
+            format: "image/png".to_owned(),
+            data: data.clone(),
+        }]));
+
+        assert_eq!(reader.get_target("image/png").unwrap(), data);
+    }
+}
@@ -21,12 +21,13 @@ extern crate dirs;
 
 use std::path::{Path, PathBuf};
 use std::{fs};
-use crate::matcher::{Match, MatchVariable};
+use crate::matcher::{Match, MatchVariable, MatchContentType};
+use crate::matcher::gesture::{Gesture, GestureBinding};
 use std::fs::{File, create_dir_all};
 use std::io::Read;
 use serde::{Serialize, Deserialize};
-use crate::event::KeyModifier;
-use crate::keyboard::PasteShortcut;
+use crate::event::{KeyModifier, ActionType};
+use crate::keyboard::{PasteShortcut, InjectOptions};
 use std::collections::{HashSet, HashMap};
 use log::{error};
 use std::fmt;
@@ -38,7 +39,7 @@ pub(crate) mod runtime;
 const DEFAULT_CONFIG_FILE_CONTENT : &str = include_str!("../res/config.yml");
 
 pub const DEFAULT_CONFIG_FILE_NAME : &str = "default.yml";
-const USER_CONFIGS_FOLDER_NAME: &str = "user";
+pub const USER_CONFIGS_FOLDER_NAME: &str = "user";
 
 // Default values for primitives
 fn default_name() -> String{ "default".to_owned() }
@@ -46,6 +47,10 @@ fn default_parent() -> String{ "self".to_owned() }
 fn default_filter_title() -> String{ "".to_owned() }
 fn default_filter_class() -> String{ "".to_owned() }
 fn default_filter_exec() -> String{ "".to_owned() }
+fn default_filter_exclude_title() -> String{ "".to_owned() }
+fn default_filter_exclude_class() -> String{ "".to_owned() }
+fn default_filter_exclude_exec() -> String{ "".to_owned() }
+fn default_priority() -> i32 { 0 }
 fn default_log_level() -> i32 { 0 }
 fn default_conflict_check() -> bool{ true }
 fn default_ipc_server_port() -> i32 { 34982 }
@@ -54,6 +59,8 @@ fn default_config_caching_interval() -> i32 { 800 }
 fn default_word_separators() -> Vec<char> { vec![' ', ',', '.', '\r', '\n', 22u8 as char] }
 fn default_toggle_interval() -> u32 { 230 }
 fn default_toggle_key() -> KeyModifier { KeyModifier::ALT }
+fn default_enable_tab_stops() -> bool { false }
+fn default_tab_stop_key() -> KeyModifier { KeyModifier::CTRL }
 fn default_preserve_clipboard() -> bool {false}
 fn default_passive_match_regex() -> String{ "(?P<name>:\\p{L}+)(/(?P<args>.*)/)?".to_owned() }
 fn default_passive_arg_delimiter() -> char { '/' }
@@ -67,6 +74,23 @@ fn default_restore_clipboard_delay() -> i32 { 300 }
 fn default_exclude_default_entries() -> bool {false}
 fn default_matches() -> Vec<Match> { Vec::new() }
 fn default_global_vars() -> Vec<MatchVariable> { Vec::new() }
+fn default_profiles() -> HashMap<String, Vec<String>> { HashMap::new() }
+fn default_enable_history() -> bool { false }
+fn default_auto_backend_threshold() -> usize { 100 }
+fn default_inject_delay_ms() -> u64 { 0 }
+fn default_inject_chunk_size() -> usize { 0 }
+fn default_secure_input_filters() -> Vec<String> {
+    vec!["(?i)pinentry".to_owned(), "(?i)keepass".to_owned(), "(?i)1password".to_owned(),
+         "(?i)bitwarden".to_owned()]
+}
+fn default_secure_input_title_filters() -> Vec<String> { vec![] }
+fn default_secure_input_atspi() -> bool { false }
+fn default_log_triggers() -> bool { false }
+fn default_hotkey_interval() -> u32 { 800 }
+fn default_hold_interval() -> u32 { 1000 }
+fn default_gestures() -> Vec<GestureBinding> { Vec::new() }
+fn default_search_command() -> String { "".to_owned() }
+fn default_disable_in() -> Vec<String> { Vec::new() }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Configs {
@@ -85,6 +109,23 @@ pub struct Configs {
     #[serde(default = "default_filter_exec")]
     pub filter_exec: String,
 
+    #[serde(default = "default_filter_exclude_title")]
+    pub filter_exclude_title: String,
+
+    #[serde(default = "default_filter_exclude_class")]
+    pub filter_exclude_class: String,
+
+    #[serde(default = "default_filter_exclude_exec")]
+    pub filter_exclude_exec: String,
+
+    // How the filters are combined, by default the config is selected when any
+    // of them matches. With "all", every specified filter must match.
+    #[serde(default)]
+    pub filter_mode: FilterMode,
+
+    #[serde(default = "default_priority")]
+    pub priority: i32,
+
     #[serde(default = "default_log_level")]
     pub log_level: i32,
 
@@ -109,6 +150,15 @@ pub struct Configs {
     #[serde(default = "default_toggle_interval")]
     pub toggle_interval: u32,
 
+    // Parse the numbered tab stops ($1, $2, ..., $0) in the replacements.
+    // The $|$ cursor hint is always available.
+    #[serde(default = "default_enable_tab_stops")]
+    pub enable_tab_stops: bool,
+
+    // Pressed twice to jump to the next tab stop
+    #[serde(default = "default_tab_stop_key")]
+    pub tab_stop_key: KeyModifier,
+
     #[serde(default = "default_preserve_clipboard")]
     pub preserve_clipboard: bool,
 
@@ -145,6 +195,18 @@ pub struct Configs {
     #[serde(default)]
     pub backend: BackendType,
 
+    // Replacements longer than this number of chars are pasted when using the Auto backend
+    #[serde(default = "default_auto_backend_threshold")]
+    pub auto_backend_threshold: usize,
+
+    // Slow down the keystrokes injection, for applications dropping keys.
+    // With only the delay specified, it is applied after each char.
+    #[serde(default = "default_inject_delay_ms")]
+    pub inject_delay_ms: u64,
+
+    #[serde(default = "default_inject_chunk_size")]
+    pub inject_chunk_size: usize,
+
     #[serde(default = "default_exclude_default_entries")]
     pub exclude_default_entries: bool,
 
@@ -152,8 +214,64 @@ pub struct Configs {
     pub matches: Vec<Match>,
 
     #[serde(default = "default_global_vars")]
-    pub global_vars: Vec<MatchVariable>
-
+    pub global_vars: Vec<MatchVariable>,
+
+    // Named subsets of configs and packages that can be activated at runtime.
+    // Each entry can be either a config name or a package name.
+    #[serde(default = "default_profiles")]
+    pub profiles: HashMap<String, Vec<String>>,
+
+    // Record the expansions in the data dir, used by the stats subcommand.
+    // Only the triggers are stored, never the expanded content.
+    #[serde(default = "default_enable_history")]
+    pub enable_history: bool,
+
+    // Regexes matched against the window class and executable to detect password prompts,
+    // where the typed chars are not buffered and matching is suspended.
+    #[serde(default = "default_secure_input_filters")]
+    pub secure_input_filters: Vec<String>,
+
+    // Same as secure_input_filters, but matched against the window title. Empty by default,
+    // as common words such as "password" also appear in the titles of regular windows.
+    #[serde(default = "default_secure_input_title_filters")]
+    pub secure_input_title_filters: Vec<String>,
+
+    // Linux only, also suspend matching when a password field is focused, detected
+    // through AT-SPI. Requires espanso to be built with the "atspi" feature.
+    #[serde(default = "default_secure_input_atspi")]
+    pub secure_input_atspi: bool,
+
+    // Include the triggers in the logs. They are typed text, so they are redacted by default.
+    #[serde(default = "default_log_triggers")]
+    pub log_triggers: bool,
+
+    // Maximum time between the modifiers press and the key for a match hotkey to fire.
+    // Only used on the platforms that don't report the modifiers release.
+    #[serde(default = "default_hotkey_interval")]
+    pub hotkey_interval: u32,
+
+    // Minimum time a modifier has to be held for the "hold" gestures.
+    #[serde(default = "default_hold_interval")]
+    pub hold_interval: u32,
+
+    // Actions triggered by key gestures, in addition to the toggle_key and passive_key
+    // double presses. Earlier bindings take precedence.
+    #[serde(default = "default_gestures")]
+    pub gestures: Vec<GestureBinding>,
+
+    // Command used to search the matches, receiving one match per line through stdin
+    // and printing the chosen one. If empty, the first among wofi, rofi and dmenu is used.
+    #[serde(default = "default_search_command")]
+    pub search_command: String,
+
+    // Apps in which matching is suspended. Each entry is compared, case insensitively,
+    // with the class and the executable of the active window.
+    #[serde(default = "default_disable_in")]
+    pub disable_in: Vec<String>,
+
+    // Name of the package the config belongs to, if any. Calculated when loading the ConfigSet.
+    #[serde(skip)]
+    pub _package: Option<String>,
 }
 
 // Macro used to validate config fields
@@ -185,6 +303,7 @@ impl Configs {
         validate_field!(result, self.conflict_check, default_conflict_check());
         validate_field!(result, self.toggle_key, default_toggle_key());
         validate_field!(result, self.toggle_interval, default_toggle_interval());
+        validate_field!(result, self.tab_stop_key, default_tab_stop_key());
         validate_field!(result, self.backspace_limit, default_backspace_limit());
         validate_field!(result, self.ipc_server_port, default_ipc_server_port());
         validate_field!(result, self.use_system_agent, default_use_system_agent());
@@ -195,15 +314,118 @@ impl Configs {
         validate_field!(result, self.passive_key, default_passive_key());
         validate_field!(result, self.action_noop_interval, default_action_noop_interval());
         validate_field!(result, self.restore_clipboard_delay, default_restore_clipboard_delay());
+        validate_field!(result, self.profiles, default_profiles());
+        validate_field!(result, self.enable_history, default_enable_history());
+        validate_field!(result, self.secure_input_filters, default_secure_input_filters());
+        validate_field!(result, self.secure_input_title_filters, default_secure_input_title_filters());
+        validate_field!(result, self.secure_input_atspi, default_secure_input_atspi());
+        validate_field!(result, self.log_triggers, default_log_triggers());
+        validate_field!(result, self.hotkey_interval, default_hotkey_interval());
+        validate_field!(result, self.hold_interval, default_hold_interval());
+        validate_field!(result, self.gestures, default_gestures());
+        validate_field!(result, self.search_command, default_search_command());
 
         result
     }
+
+    /// Throttling options used when injecting keystrokes
+    pub fn inject_options(&self) -> InjectOptions {
+        InjectOptions {
+            chunk_size: self.inject_chunk_size,
+            delay_ms: self.inject_delay_ms,
+        }
+    }
+
+    /// The given trigger if log_triggers is enabled, a placeholder otherwise
+    pub fn loggable<'s>(&self, trigger: &'s str) -> &'s str {
+        if self.log_triggers {
+            trigger
+        }else{
+            "<redacted>"
+        }
+    }
+
+    /// The configured gestures, followed by the toggle and passive key ones
+    pub fn gesture_bindings(&self) -> Vec<GestureBinding> {
+        let mut gestures = self.gestures.clone();
+        if self.toggle_key != KeyModifier::OFF {
+            gestures.push(GestureBinding {
+                gesture: Gesture::Press { modifier: self.toggle_key.clone(), count: 2 },
+                action: ActionType::Toggle,
+            });
+        }
+        if self.passive_key != KeyModifier::OFF {
+            gestures.push(GestureBinding {
+                gesture: Gesture::Press { modifier: self.passive_key.clone(), count: 2 },
+                action: ActionType::Passive,
+            });
+        }
+        gestures
+    }
+
+    /// A triple press gesture, with the double press one of the same modifier. The double
+    /// press would fire on the way to the triple one, so they can't be bound together.
+    fn overlapping_gestures(&self) -> Option<(Gesture, Gesture)> {
+        let gestures: Vec<Gesture> = self.gesture_bindings().into_iter()
+            .map(|binding| binding.gesture)
+            .collect();
+
+        gestures.iter().find_map(|gesture| {
+            match gesture {
+                Gesture::Press { modifier, count } if *count > 2 => {
+                    gestures.iter().find(|other| {
+                        match other {
+                            Gesture::Press { modifier: other_modifier, count: other_count } => {
+                                other_modifier == modifier && other_count < count
+                            },
+                            _ => false,
+                        }
+                    }).map(|other| (gesture.clone(), other.clone()))
+                },
+                _ => None,
+            }
+        })
+    }
+
+    /// Port of the IPC server used on Windows. Instances with an overridden data dir
+    /// derive their own port from it, unless a custom port is configured.
+    #[cfg(target_os = "windows")]
+    pub fn ipc_port(&self) -> u16 {
+        let data_dir_override = crate::context::get_data_dir_override();
+        resolve_ipc_port(self.ipc_server_port, data_dir_override.as_ref().map(|dir| dir.as_path()))
+    }
+}
+
+#[cfg(any(target_os = "windows", test))]
+fn resolve_ipc_port(configured_port: i32, data_dir_override: Option<&Path>) -> u16 {
+    match data_dir_override {
+        Some(data_dir) if configured_port == default_ipc_server_port() => {
+            // Hashed by hand, as the daemon and the client must agree on the port
+            let hash = data_dir.to_string_lossy().bytes()
+                .fold(0u32, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte as u32));
+            (configured_port as u32 + 1 + hash % 1000) as u16
+        },
+        _ => configured_port as u16,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterMode {
+    Any,
+    All,
+}
+impl Default for FilterMode {
+    fn default() -> Self {
+        FilterMode::Any
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BackendType {
     Inject,
-    Clipboard
+    Clipboard,
+    Auto,   // Choose between Inject and Clipboard for each expansion
 }
 impl Default for BackendType {
     // The default backend varies based on the operating system.
@@ -224,6 +446,23 @@ impl Default for BackendType {
     }
 }
 
+impl BackendType {
+    /// Resolve the Auto backend for the given text: short single-line ASCII replacements
+    /// are injected, while long, multi-line or non-ASCII ones are pasted through the clipboard.
+    pub fn resolve(&self, text: &str, auto_threshold: usize) -> BackendType {
+        match self {
+            BackendType::Auto => {
+                if text.contains('\n') || !text.is_ascii() || text.chars().count() > auto_threshold {
+                    BackendType::Clipboard
+                }else{
+                    BackendType::Inject
+                }
+            },
+            backend => backend.clone(),
+        }
+    }
+}
+
 impl Configs {
     fn load_config(path: &Path) -> Result<Configs, ConfigLoadError> {
         let file_res = File::open(path);
@@ -235,28 +474,86 @@ impl Configs {
                 return Err(ConfigLoadError::UnableToReadFile)
             }
 
-            let config_res = serde_yaml::from_str(&contents);
-
-            match config_res {
-                Ok(config) => Ok(config),
-                Err(e) => {
-                    Err(ConfigLoadError::InvalidYAML(path.to_owned(), e.to_string()))
-                }
+            // Deserialize the file based on its format, defaulting to YAML
+            let config : Result<Configs, ConfigLoadError> = match ConfigFormat::from_path(path).unwrap_or(ConfigFormat::YAML) {
+                ConfigFormat::YAML => {
+                    serde_yaml::from_str(&contents).map_err(|e| {
+                        ConfigLoadError::InvalidYAML(path.to_owned(), e.to_string())
+                    })
+                },
+                ConfigFormat::JSON => {
+                    serde_json::from_str(&contents).map_err(|e| {
+                        ConfigLoadError::InvalidJSON(path.to_owned(), e.to_string())
+                    })
+                },
+                ConfigFormat::TOML => {
+                    toml::from_str(&contents).map_err(|e| {
+                        ConfigLoadError::InvalidTOML(path.to_owned(), e.to_string())
+                    })
+                },
+            };
+
+            let mut config = config?;
+            if let Some(base_dir) = path.parent() {
+                config.resolve_relative_paths(base_dir);
             }
+
+            Ok(config)
         }else{
             Err(ConfigLoadError::FileNotFound)
         }
     }
 
+    /// Make the relative image paths relative to the given config file directory
+    fn resolve_relative_paths(&mut self, base_dir: &Path) {
+        for m in self.matches.iter_mut() {
+            if let MatchContentType::Image(content) = &mut m.content {
+                if content.path.is_relative() {
+                    content.path = base_dir.join(&content.path);
+                }
+            }
+        }
+    }
+
+    /// Record the config and package each match was declared in, so that
+    /// they can still be identified after merging them into other configs.
+    fn set_origin(&mut self, package: Option<String>) {
+        for m in self.matches.iter_mut() {
+            m._config = self.name.clone();
+            m._package = package.clone();
+        }
+        self._package = package;
+    }
+
+    /// Check if the given profile entries include the config or the package.
+    pub fn is_origin_in_profile(entries: &[String], config_name: &str, package: &Option<String>) -> bool {
+        entries.iter().any(|entry| {
+            entry == config_name || Some(entry) == package.as_ref()
+        })
+    }
+
     fn merge_config(&mut self, new_config: Configs) {
         // Merge matches
         let mut merged_matches = new_config.matches;
+
+        // The matches disabled in the parent stay disabled even if redefined by the child,
+        // so that a single snippet of a package can be turned off from the user configs
+        let disabled_set: HashSet<String> = self.matches.iter()
+            .filter(|m| !m.enabled)
+            .map(|m| m.merge_key())
+            .collect();
+        merged_matches.iter_mut().for_each(|m| {
+            if disabled_set.contains(&m.merge_key()) {
+                m.enabled = false;
+            }
+        });
+
         let mut match_trigger_set = HashSet::new();
         merged_matches.iter().for_each(|m| {
-            match_trigger_set.insert(m.trigger.clone());
+            match_trigger_set.insert(m.merge_key());
         });
         let parent_matches : Vec<Match> = self.matches.iter().filter(|&m| {
-            !match_trigger_set.contains(&m.trigger)
+            !match_trigger_set.contains(&m.merge_key())
         }).cloned().collect();
 
         merged_matches.extend(parent_matches);
@@ -280,10 +577,10 @@ impl Configs {
         // Merge matches
         let mut match_trigger_set = HashSet::new();
         self.matches.iter().for_each(|m| {
-            match_trigger_set.insert(m.trigger.clone());
+            match_trigger_set.insert(m.merge_key());
         });
         let default_matches : Vec<Match> = default.matches.iter().filter(|&m| {
-            !match_trigger_set.contains(&m.trigger)
+            !match_trigger_set.contains(&m.merge_key())
         }).cloned().collect();
 
         self.matches.extend(default_matches);
@@ -302,6 +599,28 @@ impl Configs {
     }
 }
 
+/// Supported config file formats, all of them deserialized into the same `Configs` model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigFormat {
+    YAML,
+    JSON,
+    TOML,
+}
+
+impl ConfigFormat {
+    /// Detect the config format from the file extension, returning None
+    /// if the file is not a supported config file.
+    pub fn from_path(path: &Path) -> Option<ConfigFormat> {
+        let extension = path.extension().unwrap_or_default().to_str().unwrap_or_default();
+        match extension {
+            "yml" | "yaml" => Some(ConfigFormat::YAML),
+            "json" => Some(ConfigFormat::JSON),
+            "toml" => Some(ConfigFormat::TOML),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ConfigSet {
     pub default: Configs,
@@ -316,7 +635,12 @@ impl ConfigSet {
 
         // Load default configuration
         let default_file = config_dir.join(DEFAULT_CONFIG_FILE_NAME);
-        let default = Configs::load_config(default_file.as_path())?;
+        let mut default = Configs::load_config(default_file.as_path())?;
+        default.set_origin(None);
+
+        if let Some((gesture, shorter)) = default.overlapping_gestures() {
+            return Err(ConfigLoadError::OverlappingGestures(gesture.to_string(), shorter.to_string()));
+        }
 
         // Analyze which config files has to be loaded
 
@@ -344,8 +668,8 @@ impl ConfigSet {
             if let Ok(entry) = entry {
                 let path = entry.path();
 
-                // Skip non-yaml config files
-                if path.extension().unwrap_or_default().to_str().unwrap_or_default() != "yml" {
+                // Skip files that are not in a supported config format
+                if ConfigFormat::from_path(path).is_none() {
                     continue;
                 }
 
@@ -367,6 +691,12 @@ impl ConfigSet {
 
                 name_set.insert(config.name.clone());
 
+                // Packages are identified by the name of their folder in the package directory
+                let package = path.strip_prefix(package_dir).ok()
+                    .and_then(|relative| relative.components().next())
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned());
+                config.set_origin(package);
+
                 if config.parent == "self" {  // No parent, root config
                     root_configs.push(config);
                 }else{  // Children config
@@ -465,7 +795,10 @@ impl ConfigSet {
     }
 
     fn has_conflicts(default: &Configs, specific: &Vec<Configs>) -> bool {
-        let mut sorted_triggers : Vec<String> = default.matches.iter().map(|t| {
+        // Hotkey-only matches have no trigger that could conflict
+        let mut sorted_triggers : Vec<String> = default.matches.iter().filter(|t| {
+            !t.trigger.is_empty()
+        }).map(|t| {
             t.trigger.clone()
         }).collect();
         sorted_triggers.sort();
@@ -473,7 +806,9 @@ impl ConfigSet {
         let mut has_conflicts = Self::list_has_conflicts(&sorted_triggers);
 
         for s in specific.iter() {
-            let mut specific_triggers : Vec<String> = s.matches.iter().map(|t| {
+            let mut specific_triggers : Vec<String> = s.matches.iter().filter(|t| {
+                !t.trigger.is_empty()
+            }).map(|t| {
                 t.trigger.clone()
             }).collect();
             specific_triggers.sort();
@@ -506,6 +841,18 @@ pub trait ConfigManager<'a> {
     fn active_config(&'a self) -> &'a Configs;
     fn default_config(&'a self) -> &'a Configs;
     fn matches(&'a self) -> &'a Vec<Match>;
+
+    // Check whether the match is enabled, belongs to the active profile and its
+    // app filters (if any) are satisfied by the active window
+    fn is_match_active(&'a self, m: &Match) -> bool;
+
+    // Profiles, None means that all the configs are enabled
+    fn active_profile(&'a self) -> Option<String>;
+    fn set_active_profile(&'a self, profile: Option<String>) -> bool;
+
+    // Check if the typed chars should not be buffered, because a password
+    // field or an app disabled by the active config is focused
+    fn is_input_suspended(&'a self) -> bool;
 }
 
 // Error handling
@@ -514,10 +861,13 @@ pub enum ConfigLoadError {
     FileNotFound,
     UnableToReadFile,
     InvalidYAML(PathBuf, String),
+    InvalidJSON(PathBuf, String),
+    InvalidTOML(PathBuf, String),
     InvalidConfigDirectory,
     InvalidParameter(PathBuf),
     NameDuplicate(PathBuf),
     UnableToCreateDefaultConfig,
+    OverlappingGestures(String, String),
 }
 
 impl fmt::Display for ConfigLoadError {
@@ -526,10 +876,13 @@ impl fmt::Display for ConfigLoadError {
             ConfigLoadError::FileNotFound =>  write!(f, "File not found"),
             ConfigLoadError::UnableToReadFile =>  write!(f, "Unable to read config file"),
             ConfigLoadError::InvalidYAML(path, e) => write!(f, "Error parsing YAML file '{}', invalid syntax: {}", path.to_str().unwrap_or_default(), e),
+            ConfigLoadError::InvalidJSON(path, e) => write!(f, "Error parsing JSON file '{}', invalid syntax: {}", path.to_str().unwrap_or_default(), e),
+            ConfigLoadError::InvalidTOML(path, e) => write!(f, "Error parsing TOML file '{}', invalid syntax: {}", path.to_str().unwrap_or_default(), e),
             ConfigLoadError::InvalidConfigDirectory =>  write!(f, "Invalid config directory"),
             ConfigLoadError::InvalidParameter(path) =>  write!(f, "Invalid parameter in '{}', use of reserved parameters in used defined configs is not permitted", path.to_str().unwrap_or_default()),
             ConfigLoadError::NameDuplicate(path) =>  write!(f, "Found duplicate 'name' in '{}', please use different names", path.to_str().unwrap_or_default()),
             ConfigLoadError::UnableToCreateDefaultConfig =>  write!(f, "Could not generate default config file"),
+            ConfigLoadError::OverlappingGestures(gesture, shorter) =>  write!(f, "The '{}' gesture can't be used along with '{}', which would fire first. Check the gestures, toggle_key and passive_key options", gesture, shorter),
         }
     }
 }
@@ -540,10 +893,13 @@ impl Error for ConfigLoadError {
             ConfigLoadError::FileNotFound => "File not found",
             ConfigLoadError::UnableToReadFile => "Unable to read config file",
             ConfigLoadError::InvalidYAML(_, _) => "Error parsing YAML file, invalid syntax",
+            ConfigLoadError::InvalidJSON(_, _) => "Error parsing JSON file, invalid syntax",
+            ConfigLoadError::InvalidTOML(_, _) => "Error parsing TOML file, invalid syntax",
             ConfigLoadError::InvalidConfigDirectory => "Invalid config directory",
             ConfigLoadError::InvalidParameter(_) => "Invalid parameter, use of reserved parameters in user defined configs is not permitted",
             ConfigLoadError::NameDuplicate(_) => "Found duplicate 'name' in some configurations, please use different names",
             ConfigLoadError::UnableToCreateDefaultConfig => "Could not generate default config file",
+            ConfigLoadError::OverlappingGestures(_, _) => "Found a triple press gesture along with the double press of the same modifier",
         }
     }
 }
@@ -551,7 +907,7 @@ impl Error for ConfigLoadError {
 
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use std::io::Write;
     use tempfile::{NamedTempFile, TempDir};
@@ -658,6 +1014,20 @@ mod tests {
         assert_eq!(config.unwrap().validate_user_defined_config(), false);
     }
 
+    #[test]
+    fn test_user_defined_config_has_reserved_fields_gestures() {
+        let working_config_file = create_tmp_file(r###"
+
+        # This should not happen in an app-specific config
+        gestures:
+          - gesture: "double ctrl"
+            action: passive
+
+        "###);
+        let config = Configs::load_config(working_config_file.path());
+        assert_eq!(config.unwrap().validate_user_defined_config(), false);
+    }
+
     #[test]
     fn test_user_defined_config_has_reserved_fields_backspace_limit() {
         let working_config_file = create_tmp_file(r###"
@@ -809,6 +1179,46 @@ mod tests {
         assert!(variant_eq(&config_set.unwrap_err(), &ConfigLoadError::NameDuplicate(PathBuf::new())))
     }
 
+    #[test]
+    fn test_config_set_triple_press_overlapping_toggle_key() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        gestures:
+          - gesture: "triple alt"
+            action: search
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert_eq!(config_set.unwrap_err(),
+                   ConfigLoadError::OverlappingGestures("triple alt".to_owned(), "double alt".to_owned()));
+    }
+
+    #[test]
+    fn test_config_set_triple_press_overlapping_gesture() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        toggle_key: OFF
+        gestures:
+          - gesture: "double ctrl"
+            action: disable
+          - gesture: "triple ctrl"
+            action: enable
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(variant_eq(&config_set.unwrap_err(), &ConfigLoadError::OverlappingGestures(String::new(), String::new())));
+    }
+
+    #[test]
+    fn test_config_set_press_gestures_on_different_modifiers() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        gestures:
+          - gesture: "triple ctrl"
+            action: search
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+        assert_eq!(config_set.default.gesture_bindings().len(), 2);
+    }
+
     #[test]
     fn test_user_defined_config_set_merge_with_parent_matches() {
         let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
@@ -927,6 +1337,146 @@ mod tests {
         assert_eq!(config_set.specific.len(), 0);
     }
 
+    #[test]
+    fn test_yaml_extension_files_are_loaded_from_config() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let user_defined_path = create_user_config_file(data_dir.path(), "specific.yaml", r###"
+        name: specific1
+
+        matches:
+            - trigger: "hello"
+              replace: "newstring"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+        assert_eq!(config_set.specific.len(), 1);
+        assert!(config_set.specific[0].matches.iter().any(|m| m.trigger == "hello"));
+    }
+
+    #[test]
+    fn test_hotkey_only_matches_are_merged_by_hotkey() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        matches:
+            - hotkey: "ctrl+alt+e"
+              replace: "first"
+            - hotkey: "ctrl+alt+f"
+              replace: "second"
+        "###);
+
+        create_user_config_file(data_dir.path(), "specific.yml", r###"
+        name: specific1
+
+        matches:
+            - hotkey: "ctrl+alt+e"
+              replace: "overridden"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+        assert_eq!(config_set.default.matches.len(), 2);
+        assert_eq!(config_set.specific[0].matches.len(), 2);
+        assert!(config_set.specific[0].matches.iter().any(|m| {
+            if let MatchContentType::Text(content) = &m.content {
+                m.merge_key() == "CTRL+ALT+e" && content.replace == "overridden"
+            }else{
+                false
+            }
+        }));
+    }
+
+    #[test]
+    fn test_json_files_are_loaded_from_config() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let user_defined_path = create_user_config_file(data_dir.path(), "specific.json", r###"
+        {
+            "name": "specific1",
+            "matches": [
+                {
+                    "trigger": "hello",
+                    "replace": "first line\nsecond line"
+                },
+                {
+                    "trigger": ":date",
+                    "replace": "{{mydate}}",
+                    "vars": [
+                        {
+                            "name": "mydate",
+                            "type": "date",
+                            "params": {
+                                "format": "%m/%d/%Y"
+                            }
+                        }
+                    ]
+                }
+            ]
+        }
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+        assert_eq!(config_set.specific.len(), 1);
+        assert_eq!(config_set.specific[0].name, "specific1");
+        assert!(config_set.specific[0].matches.iter().any(|m| {
+            if let MatchContentType::Text(content) = &m.content {
+                m.trigger == "hello" && content.replace == "first line\nsecond line"
+            }else{
+                false
+            }
+        }));
+        assert!(config_set.specific[0].matches.iter().any(|m| {
+            if let MatchContentType::Text(content) = &m.content {
+                m.trigger == ":date" && content._has_vars && content.vars.len() == 1
+            }else{
+                false
+            }
+        }));
+    }
+
+    #[test]
+    fn test_toml_files_are_loaded_from_config() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let user_defined_path = create_user_config_file(data_dir.path(), "specific.toml", r###"
+        name = "specific1"
+
+        [[matches]]
+        trigger = "hello"
+        replace = "newstring"
+
+        [[matches]]
+        trigger = "world"
+        replace = "another"
+        word = true
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+        assert_eq!(config_set.specific.len(), 1);
+        assert_eq!(config_set.specific[0].name, "specific1");
+        assert!(config_set.specific[0].matches.iter().any(|m| m.trigger == "hello"));
+        assert!(config_set.specific[0].matches.iter().any(|m| m.trigger == "world" && m.word));
+    }
+
+    #[test]
+    fn test_config_set_invalid_json_syntax() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let user_defined_path = create_user_config_file(data_dir.path(), "specific.json", r###"
+        {
+            "name": "specific1",
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        match config_set {
+            Ok(_) => {assert!(false)},
+            Err(e) => {
+                match e {
+                    ConfigLoadError::InvalidJSON(p, _) => assert_eq!(p, user_defined_path),
+                    _ => assert!(false),
+                }
+            },
+        }
+    }
+
     #[test]
     fn test_config_set_no_parent_configs_works_correctly() {
         let (data_dir, package_dir) = create_temp_espanso_directories();
@@ -1072,6 +1622,36 @@ mod tests {
         assert!(config_set.default.matches.iter().any(|m| m.trigger == "harry"));
     }
 
+    #[test]
+    fn test_config_set_package_match_disabled_from_default() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        matches:
+            - trigger: "harry"
+              enabled: false
+        "###);
+
+        create_package_file(package_dir.path(), "package1", "package.yml", r###"
+        parent: default
+
+        matches:
+            - trigger: "harry"
+              replace: "potter"
+            - trigger: "ron"
+              replace: "weasley"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+        assert_eq!(config_set.default.matches.len(), 2);
+        assert!(config_set.default.matches.iter().any(|m| {
+            if let MatchContentType::Text(content) = &m.content {
+                m.trigger == "harry" && content.replace == "potter" && !m.enabled
+            }else{
+                false
+            }
+        }));
+        assert!(config_set.default.matches.iter().any(|m| m.trigger == "ron" && m.enabled));
+    }
+
     #[test]
     fn test_config_set_package_configs_without_merge() {
         let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
@@ -1125,6 +1705,77 @@ mod tests {
         assert!(config_set.specific[0].matches.iter().any(|m| m.trigger == "ron"));
     }
 
+    #[test]
+    fn test_config_set_matches_origin() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        matches:
+            - trigger: hasta
+              replace: Hasta la vista
+        "###);
+
+        let user_defined_path = create_user_config_file(data_dir.path(), "specific.yml", r###"
+        name: specific1
+
+        matches:
+            - trigger: "hello"
+              replace: "world"
+        "###);
+
+        let package_path = create_package_file(package_dir.path(), "package1", "package.yml", r###"
+        parent: default
+
+        matches:
+            - trigger: "harry"
+              replace: "potter"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+        let hasta = config_set.default.matches.iter().find(|m| m.trigger == "hasta").unwrap();
+        assert_eq!(hasta._config, "default");
+        assert_eq!(hasta._package, None);
+        let harry = config_set.default.matches.iter().find(|m| m.trigger == "harry").unwrap();
+        assert_eq!(harry._package, Some("package1".to_owned()));
+        let hello = config_set.specific[0].matches.iter().find(|m| m.trigger == "hello").unwrap();
+        assert_eq!(hello._config, "specific1");
+        assert_eq!(hello._package, None);
+        assert_eq!(config_set.specific[0]._package, None);
+    }
+
+    #[test]
+    fn test_is_origin_in_profile() {
+        let entries = vec!["default".to_owned(), "emoji".to_owned()];
+        assert!(Configs::is_origin_in_profile(&entries, "default", &None));
+        assert!(Configs::is_origin_in_profile(&entries, "/path/to/package.yml", &Some("emoji".to_owned())));
+        assert!(!Configs::is_origin_in_profile(&entries, "personal", &None));
+        assert!(!Configs::is_origin_in_profile(&entries, "/path/to/package.yml", &Some("other".to_owned())));
+    }
+
+    #[test]
+    fn test_user_defined_config_has_reserved_fields_profiles() {
+        let working_config_file = create_tmp_file(r###"
+
+        # This should not happen in an app-specific config
+        profiles:
+            work:
+                - default
+
+        "###);
+        let config = Configs::load_config(working_config_file.path());
+        assert_eq!(config.unwrap().validate_user_defined_config(), false);
+    }
+
+    #[test]
+    fn test_user_defined_config_has_reserved_fields_enable_history() {
+        let working_config_file = create_tmp_file(r###"
+
+        # This should not happen in an app-specific config
+        enable_history: true
+
+        "###);
+        let config = Configs::load_config(working_config_file.path());
+        assert_eq!(config.unwrap().validate_user_defined_config(), false);
+    }
+
     #[test]
     fn test_list_has_conflict_no_conflict() {
         assert_eq!(ConfigSet::list_has_conflicts(&vec!(":ab".to_owned(), ":bc".to_owned())), false);
@@ -1312,4 +1963,98 @@ mod tests {
         assert_eq!(config_set.specific[0].global_vars.len(), 1);
         assert!(config_set.specific[0].global_vars.iter().any(|m| m.name == "specificvar"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_triggers_redacted_in_logs_by_default() {
+        let config : Configs = serde_yaml::from_str("backend: Inject").unwrap();
+        assert_eq!(config.loggable(":pass"), "<redacted>");
+
+        let config : Configs = serde_yaml::from_str("log_triggers: true").unwrap();
+        assert_eq!(config.loggable(":pass"), ":pass");
+    }
+
+    #[test]
+    fn test_ipc_port_without_override() {
+        assert_eq!(resolve_ipc_port(default_ipc_server_port(), None), 34982);
+        assert_eq!(resolve_ipc_port(4000, None), 4000);
+    }
+
+    #[test]
+    fn test_ipc_port_derived_from_data_dir() {
+        let first = resolve_ipc_port(default_ipc_server_port(), Some(Path::new("/tmp/espanso-a")));
+        let second = resolve_ipc_port(default_ipc_server_port(), Some(Path::new("/tmp/espanso-b")));
+        assert_ne!(first, 34982);
+        assert_ne!(second, 34982);
+        assert_ne!(first, second);
+        assert_eq!(first, resolve_ipc_port(default_ipc_server_port(), Some(Path::new("/tmp/espanso-a"))));
+    }
+
+    #[test]
+    fn test_ipc_port_configured_wins_over_data_dir() {
+        assert_eq!(resolve_ipc_port(4000, Some(Path::new("/tmp/espanso-a"))), 4000);
+    }
+
+    #[test]
+    fn test_backend_resolve_auto() {
+        assert_eq!(BackendType::Auto.resolve("hello", 10), BackendType::Inject);
+        assert_eq!(BackendType::Auto.resolve("hello world!", 10), BackendType::Clipboard);
+        assert_eq!(BackendType::Auto.resolve("two\nlines", 100), BackendType::Clipboard);
+        assert_eq!(BackendType::Auto.resolve("café", 100), BackendType::Clipboard);
+        assert_eq!(BackendType::Auto.resolve("😀", 100), BackendType::Clipboard);
+    }
+
+    #[test]
+    fn test_backend_resolve_fixed() {
+        assert_eq!(BackendType::Inject.resolve("two\nlines", 1), BackendType::Inject);
+        assert_eq!(BackendType::Clipboard.resolve("short", 100), BackendType::Clipboard);
+    }
+
+    #[test]
+    fn test_config_auto_backend() {
+        let config_file = create_tmp_file(r###"
+        backend: Auto
+        auto_backend_threshold: 20
+        "###);
+        let config = Configs::load_config(config_file.path()).unwrap();
+        assert_eq!(config.backend, BackendType::Auto);
+        assert_eq!(config.auto_backend_threshold, 20);
+    }
+
+    #[test]
+    fn test_config_image_path_relative_to_config_file() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        matches:
+            - trigger: ":logo"
+              image_path: "images/logo.png"
+            - trigger: ":absolute"
+              image_path: "/path/to/logo.png"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path()).unwrap();
+
+        let path_of = |trigger: &str| {
+            let m = config_set.default.matches.iter().find(|m| m.trigger == trigger).unwrap();
+            match &m.content {
+                MatchContentType::Image(content) => content.path.clone(),
+                _ => panic!("expected an image match"),
+            }
+        };
+
+        assert_eq!(path_of(":logo"), data_dir.path().join("images/logo.png"));
+        assert_eq!(path_of(":absolute"), PathBuf::from("/path/to/logo.png"));
+    }
+
+    #[test]
+    fn test_config_inject_options() {
+        let config_file = create_tmp_file(r###"
+        inject_delay_ms: 15
+        inject_chunk_size: 8
+        "###);
+        let config = Configs::load_config(config_file.path()).unwrap();
+        assert_eq!(config.inject_options(), InjectOptions { chunk_size: 8, delay_ms: 15 });
+
+        let config_file = create_tmp_file("backend: Inject");
+        let config = Configs::load_config(config_file.path()).unwrap();
+        assert!(!config.inject_options().is_throttled());
+    }
+}
@@ -20,9 +20,9 @@
 use regex::Regex;
 use crate::system::SystemManager;
 use std::cell::RefCell;
-use std::time::SystemTime;
+use std::time::{SystemTime, UNIX_EPOCH};
 use log::{debug, warn};
-use super::{Configs, ConfigSet};
+use super::{Configs, ConfigSet, ConfigManager, FilterMode};
 use crate::matcher::Match;
 
 pub struct RuntimeConfigManager<'a, S: SystemManager> {
@@ -33,140 +33,276 @@ pub struct RuntimeConfigManager<'a, S: SystemManager> {
     class_regexps: Vec<Option<Regex>>,
     exec_regexps: Vec<Option<Regex>>,
 
+    // Exclude filter regexps
+    exclude_title_regexps: Vec<Option<Regex>>,
+    exclude_class_regexps: Vec<Option<Regex>>,
+    exclude_exec_regexps: Vec<Option<Regex>>,
+
+    // Password prompt detection regexps
+    secure_regexps: Vec<Regex>,
+    secure_title_regexps: Vec<Regex>,
+
     system_manager: S,
 
+    // Name of the active profile, None if all the configs are enabled
+    active_profile: RefCell<Option<String>>,
+
     // Cache
     last_config_update: RefCell<SystemTime>,
-    last_config: RefCell<Option<&'a Configs>>
+    last_config: RefCell<Option<&'a Configs>>,
+    last_suspended_update: RefCell<SystemTime>,
+    last_suspended: RefCell<bool>,
 }
 
 impl <'a, S: SystemManager> RuntimeConfigManager<'a, S> {
     pub fn new<'b>(set: ConfigSet, system_manager: S) -> RuntimeConfigManager<'b, S> {
         // Compile all the regexps
-        let title_regexps = set.specific.iter().map(
-            |config| {
-                if config.filter_title.is_empty() {
-                    None
-                }else{
-                    let res = Regex::new(&config.filter_title);
-                    if let Ok(regex) = res {
-                        Some(regex)
-                    }else{
-                        warn!("Invalid regex in 'filter_title' field of configuration {}, ignoring it...", config.name);
-                        None
-                    }
-                }
-            }
-        ).collect();
+        let title_regexps = Self::compile_filter_regexps(&set, "filter_title",
+                                                         |config| &config.filter_title);
+        let class_regexps = Self::compile_filter_regexps(&set, "filter_class",
+                                                         |config| &config.filter_class);
+        let exec_regexps = Self::compile_filter_regexps(&set, "filter_exec",
+                                                        |config| &config.filter_exec);
+        let exclude_title_regexps = Self::compile_filter_regexps(&set, "filter_exclude_title",
+                                                                 |config| &config.filter_exclude_title);
+        let exclude_class_regexps = Self::compile_filter_regexps(&set, "filter_exclude_class",
+                                                                 |config| &config.filter_exclude_class);
+        let exclude_exec_regexps = Self::compile_filter_regexps(&set, "filter_exclude_exec",
+                                                                |config| &config.filter_exclude_exec);
+
+        let secure_regexps = Self::compile_secure_regexps(&set.default.secure_input_filters,
+                                                          "secure_input_filters");
+        let secure_title_regexps = Self::compile_secure_regexps(&set.default.secure_input_title_filters,
+                                                                "secure_input_title_filters");
+
+        let last_config_update = RefCell::new(SystemTime::now());
+        let last_config = RefCell::new(None);
+
+        RuntimeConfigManager {
+            set,
+            title_regexps,
+            class_regexps,
+            exec_regexps,
+            exclude_title_regexps,
+            exclude_class_regexps,
+            exclude_exec_regexps,
+            secure_regexps,
+            secure_title_regexps,
+            system_manager,
+            active_profile: RefCell::new(None),
+            last_config_update,
+            last_config,
+            last_suspended_update: RefCell::new(UNIX_EPOCH),
+            last_suspended: RefCell::new(false),
+        }
+    }
 
-        let class_regexps = set.specific.iter().map(
+    fn compile_filter_regexps<F>(set: &ConfigSet, field_name: &str, get_filter: F) -> Vec<Option<Regex>>
+        where F: Fn(&Configs) -> &String {
+        set.specific.iter().map(
             |config| {
-                if config.filter_class.is_empty() {
+                let filter = get_filter(config);
+                if filter.is_empty() {
                     None
                 }else{
-                    let res = Regex::new(&config.filter_class);
+                    let res = Regex::new(filter);
                     if let Ok(regex) = res {
                         Some(regex)
                     }else{
-                        warn!("Invalid regex in 'filter_class' field of configuration {}, ignoring it...", config.name);
+                        warn!("Invalid regex in '{}' field of configuration {}, ignoring it...", field_name, config.name);
                         None
                     }
                 }
             }
-        ).collect();
+        ).collect()
+    }
 
-        let exec_regexps = set.specific.iter().map(
-            |config| {
-                if config.filter_exec.is_empty() {
-                    None
-                }else{
-                    let res = Regex::new(&config.filter_exec);
-                    if let Ok(regex) = res {
-                        Some(regex)
-                    }else{
-                        warn!("Invalid regex in 'filter_exec' field of configuration {}, ignoring it...", config.name);
-                        None
-                    }
-                }
+    fn compile_secure_regexps(filters: &[String], field_name: &str) -> Vec<Regex> {
+        filters.iter().filter_map(|filter| {
+            let res = Regex::new(filter);
+            if res.is_err() {
+                warn!("Invalid regex in '{}' field: {}, ignoring it...", field_name, filter);
             }
-        ).collect();
+            res.ok()
+        }).collect()
+    }
 
-        let last_config_update = RefCell::new(SystemTime::now());
-        let last_config = RefCell::new(None);
+    /// Check if the config at the given index defines at least one filter.
+    /// Configs without any filter can never be selected.
+    fn has_filters(&self, index: usize) -> bool {
+        self.title_regexps[index].is_some() || self.class_regexps[index].is_some() ||
+            self.exec_regexps[index].is_some() || self.exclude_title_regexps[index].is_some() ||
+            self.exclude_class_regexps[index].is_some() || self.exclude_exec_regexps[index].is_some()
+    }
 
-        RuntimeConfigManager {
-            set,
-            title_regexps,
-            class_regexps,
-            exec_regexps,
-            system_manager,
-            last_config_update,
-            last_config
+    /// Check if a config or match with the given origin is enabled by the active profile.
+    fn is_origin_enabled(&self, config_name: &str, package: &Option<String>) -> bool {
+        let active_profile = self.active_profile.borrow();
+        match active_profile.as_ref().and_then(|name| self.set.default.profiles.get(name)) {
+            Some(entries) => Configs::is_origin_in_profile(entries, config_name, package),
+            None => true,
         }
     }
 
+    /// Check if the config at the given index should be active for the given window.
+    /// Depending on the filter_mode, any or all the specified filters must match,
+    /// while none of the exclude filters must match.
+    fn is_config_matching(&self, index: usize, title: &Option<String>,
+                          exec: &Option<String>, class: &Option<String>) -> bool {
+        if !self.has_filters(index) {
+            return false;
+        }
+
+        let config = &self.set.specific[index];
+        if !self.is_origin_enabled(&config.name, &config._package) {
+            return false;
+        }
+
+        let includes = [
+            (&self.title_regexps[index], title),
+            (&self.exec_regexps[index], exec),
+            (&self.class_regexps[index], class),
+        ];
+        let is_included = match config.filter_mode {
+            FilterMode::All => {
+                includes.iter().all(|(regex, value)| include_filter_matches(regex, value))
+            },
+            FilterMode::Any => {
+                // Configs with only exclude filters match every other window
+                includes.iter().all(|(regex, _)| regex.is_none()) ||
+                    includes.iter().any(|(regex, value)| {
+                        regex.is_some() && include_filter_matches(regex, value)
+                    })
+            },
+        };
+
+        is_included &&
+            !exclude_filter_matches(&self.exclude_title_regexps[index], title) &&
+            !exclude_filter_matches(&self.exclude_exec_regexps[index], exec) &&
+            !exclude_filter_matches(&self.exclude_class_regexps[index], class)
+    }
+
     fn calculate_active_config(&'a self) -> &'a Configs {
         // TODO: optimize performance by avoiding some of these checks if no Configs use the filters
 
         debug!("Requested config for window:");
 
         let active_title = self.system_manager.get_current_window_title();
-
-        if let Some(title) = active_title {
+        if let Some(title) = &active_title {
             debug!("=> Title: '{}'", title);
-
-            for (i, regex) in self.title_regexps.iter().enumerate() {
-                if let Some(regex) = regex {
-                    if regex.is_match(&title) {
-                        debug!("Matched 'filter_title' for '{}' config, using custom settings.",
-                               self.set.specific[i].name);
-
-                        return &self.set.specific[i]
-                    }
-                }
-            }
         }
 
         let active_executable = self.system_manager.get_current_window_executable();
-
-        if let Some(executable) = active_executable {
+        if let Some(executable) = &active_executable {
             debug!("=> Executable: '{}'", executable);
-
-            for (i, regex) in self.exec_regexps.iter().enumerate() {
-                if let Some(regex) = regex {
-                    if regex.is_match(&executable) {
-                        debug!("Matched 'filter_exec' for '{}' config, using custom settings.",
-                               self.set.specific[i].name);
-
-                        return &self.set.specific[i]
-                    }
-                }
-            }
         }
 
         let active_class = self.system_manager.get_current_window_class();
-
-        if let Some(class) = active_class {
+        if let Some(class) = &active_class {
             debug!("=> Class: '{}'", class);
+        }
 
-            for (i, regex) in self.class_regexps.iter().enumerate() {
-                if let Some(regex) = regex {
-                    if regex.is_match(&class) {
-                        debug!("Matched 'filter_class' for '{}' config, using custom settings.",
-                               self.set.specific[i].name);
+        // Select the matching config with the highest priority. When multiple
+        // configs share the same priority, the name is used as tie-breaker so that
+        // the result doesn't depend on the order in which files were loaded.
+        let mut selected: Option<&'a Configs> = None;
 
-                        return &self.set.specific[i]
-                    }
-                }
+        for (i, config) in self.set.specific.iter().enumerate() {
+            if !self.is_config_matching(i, &active_title, &active_executable, &active_class) {
+                continue;
+            }
+
+            let is_better = match selected {
+                Some(current) => {
+                    config.priority > current.priority ||
+                        (config.priority == current.priority && config.name < current.name)
+                },
+                None => true,
+            };
+
+            if is_better {
+                selected = Some(config);
             }
         }
 
+        if let Some(config) = selected {
+            debug!("Matched filters for '{}' config, using custom settings.", config.name);
+            return config;
+        }
+
         // No matches, return the default mapping
         debug!("No matches for custom configs, using default settings.");
         &self.set.default
     }
 }
 
+impl <'a, S: SystemManager> RuntimeConfigManager<'a, S> {
+    fn calculate_input_suspended(&'a self) -> bool {
+        let title = self.system_manager.get_current_window_title();
+        let class = self.system_manager.get_current_window_class();
+        let exec = self.system_manager.get_current_window_executable();
+
+        if is_app_disabled(&self.active_config().disable_in, &class, &exec) {
+            debug!("Matching disabled for the active app");
+            return true;
+        }
+
+        let matches = |value: &Option<String>, regex: &Regex| {
+            value.as_ref().map_or(false, |value| regex.is_match(value))
+        };
+        let is_secure_window = self.secure_regexps.iter().any(|regex| {
+            matches(&class, regex) || matches(&exec, regex)
+        }) || self.secure_title_regexps.iter().any(|regex| matches(&title, regex));
+        if is_secure_window {
+            debug!("Password prompt detected, matching suspended");
+            return true;
+        }
+
+        if self.set.default.secure_input_atspi && self.system_manager.is_secure_input_focused() {
+            debug!("Password field focused, matching suspended");
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Check if the window class or executable contains, case insensitively, one of the disabled apps
+fn is_app_disabled(disable_in: &[String], class: &Option<String>, exec: &Option<String>) -> bool {
+    let contains = |value: &Option<String>, app: &str| -> bool {
+        match value {
+            Some(value) => value.to_lowercase().contains(&app.to_lowercase()),
+            None => false,
+        }
+    };
+
+    disable_in.iter()
+        .filter(|app| !app.is_empty())
+        .any(|app| contains(class, app) || contains(exec, app))
+}
+
+/// An include filter is satisfied if not specified, or if the window property
+/// is available and matches the regex.
+fn include_filter_matches(regex: &Option<Regex>, value: &Option<String>) -> bool {
+    match regex {
+        Some(regex) => {
+            match value {
+                Some(value) => regex.is_match(value),
+                None => false,
+            }
+        },
+        None => true,
+    }
+}
+
+/// An exclude filter is triggered only if specified and the window property matches the regex.
+fn exclude_filter_matches(regex: &Option<Regex>, value: &Option<String>) -> bool {
+    match (regex, value) {
+        (Some(regex), Some(value)) => regex.is_match(value),
+        _ => false,
+    }
+}
+
 impl <'a, S: SystemManager> super::ConfigManager<'a> for RuntimeConfigManager<'a, S> {
     fn active_config(&'a self) -> &'a Configs {
         let mut last_config_update = self.last_config_update.borrow_mut();
@@ -197,6 +333,78 @@ impl <'a, S: SystemManager> super::ConfigManager<'a> for RuntimeConfigManager<'a
     fn matches(&'a self) -> &'a Vec<Match> {
         &self.active_config().matches
     }
+
+    fn is_match_active(&'a self, m: &Match) -> bool {
+        if !m.enabled {
+            return false;
+        }
+
+        if !self.is_origin_enabled(&m._config, &m._package) {
+            debug!("Match '{}' disabled by the active profile", self.set.default.loggable(&m.trigger));
+            return false;
+        }
+
+        let filter = &m._filter;
+
+        // Avoid querying the window properties when not needed
+        if filter.is_empty() {
+            return true;
+        }
+
+        let title = self.system_manager.get_current_window_title();
+        let exec = self.system_manager.get_current_window_executable();
+        let class = self.system_manager.get_current_window_class();
+
+        let result = include_filter_matches(&filter.title, &title) &&
+            include_filter_matches(&filter.exec, &exec) &&
+            include_filter_matches(&filter.class, &class) &&
+            !exclude_filter_matches(&filter.exclude_title, &title) &&
+            !exclude_filter_matches(&filter.exclude_exec, &exec) &&
+            !exclude_filter_matches(&filter.exclude_class, &class);
+
+        if !result {
+            debug!("Match '{}' filtered out for the current window", self.set.default.loggable(&m.trigger));
+        }
+
+        result
+    }
+
+    fn active_profile(&'a self) -> Option<String> {
+        self.active_profile.borrow().clone()
+    }
+
+    fn set_active_profile(&'a self, profile: Option<String>) -> bool {
+        if let Some(name) = &profile {
+            if !self.set.default.profiles.contains_key(name) {
+                warn!("Profile '{}' does not exist", name);
+                return false;
+            }
+        }
+
+        *self.active_profile.borrow_mut() = profile;
+
+        // Invalidate the cached config, as it could be disabled by the new profile
+        *self.last_config.borrow_mut() = None;
+
+        true
+    }
+
+    fn is_input_suspended(&'a self) -> bool {
+        // The cache expires after a fixed interval, even while typing, so that
+        // a newly focused password field is detected in time
+        let mut last_suspended_update = self.last_suspended_update.borrow_mut();
+        if let Ok(elapsed) = (*last_suspended_update).elapsed() {
+            if elapsed.as_millis() < self.set.default.config_caching_interval as u128 {
+                return *self.last_suspended.borrow();
+            }
+        }
+        *last_suspended_update = SystemTime::now();
+
+        let suspended = self.calculate_input_suspended();
+        *self.last_suspended.borrow_mut() = suspended;
+
+        suspended
+    }
 }
 
 // TESTS
@@ -209,7 +417,8 @@ mod tests {
     use std::fs;
     use std::path::PathBuf;
     use crate::config::ConfigManager;
-    use crate::config::tests::{create_temp_espanso_directories, create_temp_file_in_dir, create_user_config_file};
+    use crate::config::tests::{create_temp_espanso_directories, create_temp_espanso_directories_with_default_content,
+                               create_temp_file_in_dir, create_user_config_file, create_package_file};
 
     struct DummySystemManager {
         title: RefCell<String>,
@@ -404,6 +613,7 @@ mod tests {
         assert_eq!(config_manager.calculate_active_config().name, "chrome");
     }
 
+    #[test]
     fn test_runtime_calculate_active_config_specific_multi_filter_match() {
         let (data_dir, package_dir) = create_temp_espanso_directories();
 
@@ -423,6 +633,158 @@ mod tests {
         assert_eq!(config_manager.calculate_active_config().name, "chrome");
     }
 
+    #[test]
+    fn test_runtime_calculate_active_config_specific_multi_filter_all_must_match() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: jira
+        filter_class: firefox
+        filter_title: "Jira"
+        filter_mode: all
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Search", "firefox", "/usr/bin/firefox");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert_eq!(config_manager.calculate_active_config().name, "default");
+
+        config_manager.system_manager.change("PROJ-123 - Jira", "firefox", "/usr/bin/firefox");
+        assert_eq!(config_manager.calculate_active_config().name, "jira");
+
+        config_manager.system_manager.change("PROJ-123 - Jira", "chromium", "/usr/bin/chromium");
+        assert_eq!(config_manager.calculate_active_config().name, "default");
+    }
+
+    #[test]
+    fn test_runtime_calculate_active_config_specific_multi_filter_any_by_default() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: jira
+        filter_class: firefox
+        filter_title: "Jira"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Search", "firefox", "/usr/bin/firefox");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert_eq!(config_manager.calculate_active_config().name, "jira");
+
+        config_manager.system_manager.change("PROJ-123 - Jira", "chromium", "/usr/bin/chromium");
+        assert_eq!(config_manager.calculate_active_config().name, "jira");
+
+        config_manager.system_manager.change("Google Search", "chromium", "/usr/bin/chromium");
+        assert_eq!(config_manager.calculate_active_config().name, "default");
+    }
+
+    #[test]
+    fn test_runtime_calculate_active_config_exclude_filter() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: firefox
+        filter_class: firefox
+        filter_exclude_title: "Jira"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Search", "firefox", "/usr/bin/firefox");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert_eq!(config_manager.calculate_active_config().name, "firefox");
+
+        config_manager.system_manager.change("PROJ-123 - Jira", "firefox", "/usr/bin/firefox");
+        assert_eq!(config_manager.calculate_active_config().name, "default");
+    }
+
+    #[test]
+    fn test_runtime_calculate_active_config_only_exclude_filter() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: noterminal
+        filter_exclude_class: "Terminal"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Chrome", "Chrome", "C:\\Path\\chrome.exe");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert_eq!(config_manager.calculate_active_config().name, "noterminal");
+
+        config_manager.system_manager.change("bash", "Terminal", "/usr/bin/terminal");
+        assert_eq!(config_manager.calculate_active_config().name, "default");
+    }
+
+    #[test]
+    fn test_runtime_calculate_active_config_priority() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: abrowser
+        filter_class: firefox
+        "###);
+
+        let specific_path2 = create_user_config_file(&data_dir.path(), "specific2.yml", r###"
+        name: jira
+        filter_class: firefox
+        filter_title: "Jira"
+        filter_mode: all
+        priority: 10
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("PROJ-123 - Jira", "firefox", "/usr/bin/firefox");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert_eq!(config_manager.calculate_active_config().name, "jira");
+
+        config_manager.system_manager.change("Google Search", "firefox", "/usr/bin/firefox");
+        assert_eq!(config_manager.calculate_active_config().name, "abrowser");
+    }
+
+    #[test]
+    fn test_runtime_calculate_active_config_same_priority_uses_name() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: zbrowser
+        filter_class: firefox
+        "###);
+
+        let specific_path2 = create_user_config_file(&data_dir.path(), "specific2.yml", r###"
+        name: abrowser
+        filter_class: firefox
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Search", "firefox", "/usr/bin/firefox");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert_eq!(config_manager.calculate_active_config().name, "abrowser");
+    }
+
     #[test]
     fn test_runtime_calculate_active_config_no_match() {
         let (data_dir, package_dir) = create_temp_espanso_directories();
@@ -442,6 +804,133 @@ mod tests {
         assert_eq!(config_manager.calculate_active_config().name, "default");
     }
 
+    #[test]
+    fn test_runtime_is_match_active_without_filters() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Chrome", "Chrome", "C:\\Path\\chrome.exe");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        let m : Match = serde_yaml::from_str(r###"
+        trigger: ":hello"
+        replace: "world"
+        "###).unwrap();
+
+        assert!(config_manager.is_match_active(&m));
+    }
+
+    #[test]
+    fn test_runtime_is_match_active_disabled() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Chrome", "Chrome", "C:\\Path\\chrome.exe");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        let m : Match = serde_yaml::from_str(r###"
+        trigger: ":hello"
+        replace: "world"
+        enabled: false
+        "###).unwrap();
+
+        assert!(!config_manager.is_match_active(&m));
+    }
+
+    #[test]
+    fn test_runtime_is_match_active_with_filters() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("general - Slack", "Slack", "/usr/bin/slack");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        let m : Match = serde_yaml::from_str(r###"
+        trigger: ":hello"
+        replace: "world"
+        filter_class: "Slack"
+        filter_exclude_title: "random"
+        "###).unwrap();
+
+        assert!(config_manager.is_match_active(&m));
+
+        config_manager.system_manager.change("random - Slack", "Slack", "/usr/bin/slack");
+        assert!(!config_manager.is_match_active(&m));
+
+        config_manager.system_manager.change("Google Chrome", "Chrome", "/usr/bin/chrome");
+        assert!(!config_manager.is_match_active(&m));
+    }
+
+    #[test]
+    fn test_runtime_profiles() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        profiles:
+            work:
+                - default
+                - company
+            presentation:
+                - default
+
+        matches:
+            - trigger: ":hello"
+              replace: "world"
+        "###);
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: chrome
+        filter_class: "Chrome"
+        "###);
+
+        let package_path = create_package_file(package_dir.path(), "company", "package.yml", r###"
+        parent: default
+
+        matches:
+            - trigger: ":company"
+              replace: "ACME"
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        assert!(config_set.is_ok());
+
+        let dummy_system_manager = DummySystemManager::new_custom("Google Chrome", "Chrome", "C:\\Path\\chrome.exe");
+
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        let hello = config_manager.default_config().matches.iter().find(|m| m.trigger == ":hello").unwrap().clone();
+        let company = config_manager.default_config().matches.iter().find(|m| m.trigger == ":company").unwrap().clone();
+
+        assert_eq!(config_manager.active_profile(), None);
+        assert_eq!(config_manager.calculate_active_config().name, "chrome");
+        assert!(config_manager.is_match_active(&hello));
+        assert!(config_manager.is_match_active(&company));
+
+        assert!(config_manager.set_active_profile(Some("work".to_owned())));
+        assert_eq!(config_manager.active_profile(), Some("work".to_owned()));
+        assert_eq!(config_manager.calculate_active_config().name, "default");
+        assert!(config_manager.is_match_active(&hello));
+        assert!(config_manager.is_match_active(&company));
+
+        assert!(config_manager.set_active_profile(Some("presentation".to_owned())));
+        assert!(config_manager.is_match_active(&hello));
+        assert!(!config_manager.is_match_active(&company));
+
+        assert!(!config_manager.set_active_profile(Some("invalid".to_owned())));
+        assert_eq!(config_manager.active_profile(), Some("presentation".to_owned()));
+
+        assert!(config_manager.set_active_profile(None));
+        assert_eq!(config_manager.calculate_active_config().name, "chrome");
+        assert!(config_manager.is_match_active(&company));
+    }
+
     #[test]
     fn test_runtime_active_config_cache() {
         let (data_dir, package_dir) = create_temp_espanso_directories();
@@ -467,4 +956,97 @@ mod tests {
         assert_eq!(config_manager.calculate_active_config().name, "firefox");
         assert_eq!(config_manager.active_config().name, "default");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_runtime_input_suspended_default_filters() {
+        let (data_dir, package_dir) = create_temp_espanso_directories();
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        let dummy_system_manager = DummySystemManager::new_custom("Google Chrome", "Chrome", "chrome");
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert!(!config_manager.calculate_input_suspended());
+
+        // The titles are not considered by default
+        config_manager.system_manager.change("How to choose a password", "Firefox", "firefox");
+        assert!(!config_manager.calculate_input_suspended());
+
+        config_manager.system_manager.change("Unlock", "KeePassXC", "keepassxc");
+        assert!(config_manager.calculate_input_suspended());
+
+        config_manager.system_manager.change("Passphrase", "Gcr-prompter", "/usr/bin/pinentry-gnome3");
+        assert!(config_manager.calculate_input_suspended());
+    }
+
+    #[test]
+    fn test_runtime_input_suspended_title_filters() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        secure_input_title_filters: ["(?i)password"]
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        let dummy_system_manager = DummySystemManager::new_custom("Editor", "Code", "code");
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert!(!config_manager.calculate_input_suspended());
+
+        config_manager.system_manager.change("Enter Password", "Dialog", "polkit-agent");
+        assert!(config_manager.calculate_input_suspended());
+    }
+
+    #[test]
+    fn test_runtime_input_suspended_disable_in() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        disable_in: ["Gnome-terminal"]
+        "###);
+
+        let specific_path = create_user_config_file(&data_dir.path(), "specific.yml", r###"
+        name: chrome
+        filter_class: "Chrome"
+        disable_in: ["chrome"]
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        let dummy_system_manager = DummySystemManager::new_custom("Shell", "gnome-terminal", "/usr/bin/gnome-terminal");
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        assert!(config_manager.calculate_input_suspended());
+
+        config_manager.system_manager.change("Google Chrome", "Chrome", "chrome");
+        *config_manager.last_config.borrow_mut() = None;
+        assert!(config_manager.calculate_input_suspended());
+
+        config_manager.system_manager.change("Editor", "Code", "code");
+        *config_manager.last_config.borrow_mut() = None;
+        assert!(!config_manager.calculate_input_suspended());
+    }
+
+    #[test]
+    fn test_runtime_input_suspended_custom_filters() {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        secure_input_filters: ["^vault"]
+        "###);
+
+        let config_set = ConfigSet::load(data_dir.path(), package_dir.path());
+        let dummy_system_manager = DummySystemManager::new_custom("Vault", "Dialog", "keepassxc");
+        let config_manager = RuntimeConfigManager::new(config_set.unwrap(), dummy_system_manager);
+
+        // The custom filters replace the default ones
+        assert!(!config_manager.calculate_input_suspended());
+
+        config_manager.system_manager.change("Unlock", "Dialog", "vault-cli");
+        assert!(config_manager.calculate_input_suspended());
+
+        config_manager.system_manager.change("Unlock", "vault-gui", "/usr/bin/gui");
+        assert!(config_manager.calculate_input_suspended());
+    }
+
+    #[test]
+    fn test_is_app_disabled() {
+        let disable_in = vec!["KeePass".to_owned(), "".to_owned()];
+        assert!(is_app_disabled(&disable_in, &Some("keepassxc".to_owned()), &None));
+        assert!(is_app_disabled(&disable_in, &None, &Some("C:\\KeePass\\KeePass.exe".to_owned())));
+        assert!(!is_app_disabled(&disable_in, &Some("Firefox".to_owned()), &Some("firefox".to_owned())));
+        assert!(!is_app_disabled(&[], &Some("keepassxc".to_owned()), &None));
+    }
+}
@@ -26,10 +26,12 @@ use std::process::exit;
 use log::{error, info};
 use std::ffi::CStr;
 use std::{thread, time};
+use super::ModifierHoldTracker;
 
 #[repr(C)]
 pub struct LinuxContext {
-    pub send_channel: Sender<Event>
+    pub send_channel: Sender<Event>,
+    hold_tracker: ModifierHoldTracker,
 }
 
 impl LinuxContext {
@@ -46,6 +48,7 @@ impl LinuxContext {
 
         let context = Box::new(LinuxContext {
             send_channel,
+            hold_tracker: ModifierHoldTracker::default(),
         });
 
         unsafe {
@@ -81,11 +84,13 @@ impl Drop for LinuxContext {
 // Native bridge code
 
 extern fn keypress_callback(_self: *mut c_void, raw_buffer: *const u8, len: i32,
-                            is_modifier: i32, key_code: i32) {
+                            is_modifier: i32, key_code: i32, is_key_down: i32) {
     unsafe {
         let _self = _self as *mut LinuxContext;
 
         if is_modifier == 0 {  // Char event
+            (*_self).hold_tracker.on_char();
+
             // Convert the received buffer to a string
             let c_str = CStr::from_ptr(raw_buffer as (*const c_char));
             let char_str = c_str.to_str();
@@ -111,8 +116,22 @@ extern fn keypress_callback(_self: *mut c_void, raw_buffer: *const u8, len: i32,
             };
 
             if let Some(modifier) = modifier {
-                let event = Event::Key(KeyEvent::Modifier(modifier));
-                (*_self).send_channel.send(event).unwrap();
+                if is_key_down != 0 {
+                    (*_self).hold_tracker.on_press(modifier.clone());
+
+                    let event = Event::Key(KeyEvent::Modifier(modifier));
+                    (*_self).send_channel.send(event).unwrap();
+                }else{
+                    let hold_duration = (*_self).hold_tracker.on_release(&modifier);
+
+                    let event = Event::Key(KeyEvent::ModifierRelease(modifier.clone()));
+                    (*_self).send_channel.send(event).unwrap();
+
+                    if let Some(duration) = hold_duration {
+                        let event = Event::Key(KeyEvent::ModifierHold(modifier, duration));
+                        (*_self).send_channel.send(event).unwrap();
+                    }
+                }
             }
         }
     }
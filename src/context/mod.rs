@@ -28,9 +28,17 @@ pub(crate) mod macos;
 
 use std::sync::mpsc::Sender;
 use crate::event::Event;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs::create_dir_all;
 use std::sync::Once;
+#[cfg(not(target_os = "macos"))]
+use crate::event::KeyModifier;
+#[cfg(not(target_os = "macos"))]
+use std::cell::RefCell;
+#[cfg(not(target_os = "macos"))]
+use std::collections::HashMap;
+#[cfg(not(target_os = "macos"))]
+use std::time::Instant;
 
 pub trait Context {
     fn eventloop(&self);
@@ -54,11 +62,109 @@ pub fn new(send_channel: Sender<Event>) -> Box<dyn Context> {
     windows::WindowsContext::new(send_channel)
 }
 
+/// Measures how long a modifier is held alone, used by the contexts that
+/// report both the press and the release of the modifiers.
+#[cfg(not(target_os = "macos"))]
+#[derive(Default)]
+struct ModifierHoldTracker {
+    press_times: RefCell<HashMap<KeyModifier, Instant>>,
+
+    // Set when other keys are pressed together with the held modifiers
+    interrupted: RefCell<bool>,
+}
+
+#[cfg(not(target_os = "macos"))]
+impl ModifierHoldTracker {
+    fn on_press(&self, modifier: KeyModifier) {
+        let mut press_times = self.press_times.borrow_mut();
+        if !press_times.is_empty() && !press_times.contains_key(&modifier) {
+            *self.interrupted.borrow_mut() = true;
+        }
+
+        // The auto repeat generates multiple presses, only the first one counts
+        press_times.entry(modifier).or_insert_with(Instant::now);
+    }
+
+    fn on_char(&self) {
+        if !self.press_times.borrow().is_empty() {
+            *self.interrupted.borrow_mut() = true;
+        }
+    }
+
+    /// Return the milliseconds the modifier was held for, if it was held alone
+    fn on_release(&self, modifier: &KeyModifier) -> Option<u64> {
+        let mut press_times = self.press_times.borrow_mut();
+        let press_time = press_times.remove(modifier)?;
+
+        if !press_times.is_empty() {
+            *self.interrupted.borrow_mut() = true;
+            return None;
+        }
+
+        let interrupted = self.interrupted.replace(false);
+        if interrupted {
+            None
+        }else{
+            Some(press_time.elapsed().as_millis() as u64)
+        }
+    }
+}
+
 // espanso directories
 
 static WARING_INIT : Once = Once::new();
 
+// Used to run multiple espanso instances side by side. These are environment
+// variables so that the overrides are inherited by the spawned daemon process.
+const CONFIG_DIR_ENV_VAR : &str = "ESPANSO_CONFIG_DIR";
+const DATA_DIR_ENV_VAR : &str = "ESPANSO_DATA_DIR";
+
+pub fn set_config_dir_override(path: &Path) {
+    set_dir_override(CONFIG_DIR_ENV_VAR, path);
+}
+
+pub fn set_data_dir_override(path: &Path) {
+    set_dir_override(DATA_DIR_ENV_VAR, path);
+}
+
+/// Return true if either the config or data directory have been overridden.
+pub fn has_dir_overrides() -> bool {
+    get_dir_override(CONFIG_DIR_ENV_VAR).is_some() || get_dir_override(DATA_DIR_ENV_VAR).is_some()
+}
+
+/// Return the data directory override, if any.
+#[cfg(target_os = "windows")]
+pub fn get_data_dir_override() -> Option<PathBuf> {
+    get_dir_override(DATA_DIR_ENV_VAR)
+}
+
+fn set_dir_override(var_name: &str, path: &Path) {
+    // Make the path absolute, as the daemon could be started from another directory
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    }else{
+        std::env::current_dir().expect("Can't obtain the current directory").join(path)
+    };
+
+    std::env::set_var(var_name, path);
+}
+
+fn get_dir_override(var_name: &str) -> Option<PathBuf> {
+    match std::env::var_os(var_name) {
+        Some(path) if !path.is_empty() => {
+            let path = PathBuf::from(path);
+            create_dir_all(&path).expect("Error creating espanso override directory");
+            Some(path)
+        },
+        _ => None,
+    }
+}
+
 pub fn get_data_dir() -> PathBuf {
+    if let Some(data_dir) = get_dir_override(DATA_DIR_ENV_VAR) {
+        return data_dir;
+    }
+
     let data_dir = dirs::data_local_dir().expect("Can't obtain data_local_dir(), terminating.");
     let espanso_dir = data_dir.join("espanso");
     create_dir_all(&espanso_dir).expect("Error creating espanso data directory");
@@ -66,6 +172,10 @@ pub fn get_data_dir() -> PathBuf {
 }
 
 pub fn get_config_dir() -> PathBuf {
+    if let Some(config_dir) = get_dir_override(CONFIG_DIR_ENV_VAR) {
+        return config_dir;
+    }
+
     // Portable mode check
     // Get the espanso executable path
     let espanso_exe_path = std::env::current_exe().expect("Could not get espanso executable path");
@@ -123,4 +233,42 @@ pub fn get_package_dir() -> PathBuf {
     let package_dir = data_dir.join(PACKAGES_FOLDER_NAME);
     create_dir_all(&package_dir).expect("Error creating espanso packages directory");
     package_dir
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // Each test uses its own variable, as the environment is shared between test threads
+
+    #[test]
+    fn test_dir_override_missing() {
+        assert_eq!(get_dir_override("ESPANSO_TEST_MISSING_DIR"), None);
+    }
+
+    #[test]
+    fn test_dir_override_empty_is_ignored() {
+        std::env::set_var("ESPANSO_TEST_EMPTY_DIR", "");
+        assert_eq!(get_dir_override("ESPANSO_TEST_EMPTY_DIR"), None);
+    }
+
+    #[test]
+    fn test_dir_override_creates_directory() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("instance").join("data");
+        set_dir_override("ESPANSO_TEST_CREATED_DIR", &dir);
+
+        assert_eq!(get_dir_override("ESPANSO_TEST_CREATED_DIR"), Some(dir.clone()));
+        assert!(dir.is_dir());
+    }
+
+    #[test]
+    fn test_dir_override_relative_path_is_made_absolute() {
+        set_dir_override("ESPANSO_TEST_RELATIVE_DIR", Path::new("relative"));
+
+        let path = PathBuf::from(std::env::var_os("ESPANSO_TEST_RELATIVE_DIR").unwrap());
+        assert!(path.is_absolute());
+        assert_eq!(path, std::env::current_dir().unwrap().join("relative"));
+    }
+}
@@ -25,12 +25,14 @@ use std::ffi::c_void;
 use std::{fs};
 use widestring::{U16CString, U16CStr};
 use log::{info, error};
+use super::ModifierHoldTracker;
 
 const BMP_BINARY : &[u8] = include_bytes!("../res/win/espanso.bmp");
 const ICO_BINARY : &[u8] = include_bytes!("../res/win/espanso.ico");
 
 pub struct WindowsContext {
     send_channel: Sender<Event>,
+    hold_tracker: ModifierHoldTracker,
 }
 
 impl WindowsContext {
@@ -68,6 +70,7 @@ impl WindowsContext {
 
         let context = Box::new(WindowsContext{
             send_channel,
+            hold_tracker: ModifierHoldTracker::default(),
         });
 
         unsafe {
@@ -108,6 +111,8 @@ extern fn keypress_callback(_self: *mut c_void, raw_buffer: *const u16, len: i32
         let _self = _self as *mut WindowsContext;
         if is_key_down != 0 {  // KEY DOWN EVENT
             if is_modifier == 0 {  // Char event
+                (*_self).hold_tracker.on_char();
+
                 // Convert the received buffer to a string
                 let buffer = std::slice::from_raw_parts(raw_buffer, len as usize);
                 let c_string = U16CStr::from_slice_with_nul(buffer);
@@ -128,27 +133,39 @@ extern fn keypress_callback(_self: *mut c_void, raw_buffer: *const u16, len: i32
                 }else{
                     error!("unable to decode widechar");
                 }
+            }else if let Some(modifier) = key_code_to_modifier(key_code) {
+                // Modifiers are sent on release, the press is only used to measure holds
+                (*_self).hold_tracker.on_press(modifier);
             }
         }else{  // KEY UP event
             if is_modifier != 0 {  // Modifier event
-                let modifier: Option<KeyModifier> = match key_code {
-                    0x5B | 0x5C => Some(META),
-                    0x10 => Some(SHIFT),
-                    0x12 => Some(ALT),
-                    0x11 => Some(CTRL),
-                    0x08  => Some(BACKSPACE),
-                    _ => None,
-                };
-
-                if let Some(modifier) = modifier {
-                    let event = Event::Key(KeyEvent::Modifier(modifier));
+                if let Some(modifier) = key_code_to_modifier(key_code) {
+                    let hold_duration = (*_self).hold_tracker.on_release(&modifier);
+
+                    let event = Event::Key(KeyEvent::Modifier(modifier.clone()));
                     (*_self).send_channel.send(event).unwrap();
+
+                    if let Some(duration) = hold_duration {
+                        let event = Event::Key(KeyEvent::ModifierHold(modifier, duration));
+                        (*_self).send_channel.send(event).unwrap();
+                    }
                 }
             }
         }
     }
 }
 
+fn key_code_to_modifier(key_code: i32) -> Option<KeyModifier> {
+    match key_code {
+        0x5B | 0x5C => Some(META),
+        0x10 => Some(SHIFT),
+        0x12 => Some(ALT),
+        0x11 => Some(CTRL),
+        0x08  => Some(BACKSPACE),
+        _ => None,
+    }
+}
+
 extern fn icon_click_callback(_self: *mut c_void) {
     unsafe {
         let _self = _self as *mut WindowsContext;
@@ -0,0 +1,301 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Record the events received from the platform context to a trace file, one JSON object
+// per line, and replay them through the matcher using the mock backends. Used to reproduce
+// the unexpected expansions reported by the users.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::{Serialize, Deserialize};
+use log::warn;
+use crate::config::ConfigSet;
+use crate::context;
+use crate::extension;
+use crate::event::{Event, KeyEvent, KeyModifier, ActionType};
+use crate::system::{self, SystemManager};
+use crate::testing::run_with_mocks;
+use crate::testing::mock::{Recorder, MockClipboardManager, MockSystemManager, MockUIManager,
+                           MockHistoryManager, MockWindow};
+
+const REDACTED_CHAR : char = '*';
+const REDACTED_TITLE : &str = "<redacted>";
+
+/// Window active when an event was recorded. The contexts don't report the window,
+/// so it's queried when the event is written, shortly after it was received: when
+/// switching windows quickly, the first events can be attributed to the new window.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TraceWindow {
+    pub title: Option<String>,
+    pub class: Option<String>,
+    pub exec: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TraceEventKind {
+    Char { chars: String },
+    Modifier { modifier: KeyModifier },
+    ModifierHold { modifier: KeyModifier, duration: u64 },
+    ModifierRelease { modifier: KeyModifier },
+    Action { action: i32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceEvent {
+    pub elapsed: u64,  // Milliseconds since the start of the recording
+    pub window: TraceWindow,
+    #[serde(flatten)]
+    pub kind: TraceEventKind,
+}
+
+impl TraceEventKind {
+    /// Convert the event, the system ones are not recorded as they don't come from the context
+    pub fn from_event(event: &Event) -> Option<TraceEventKind> {
+        match event {
+            Event::Key(KeyEvent::Char(chars)) => Some(TraceEventKind::Char { chars: chars.clone() }),
+            Event::Key(KeyEvent::Modifier(modifier)) => Some(TraceEventKind::Modifier { modifier: modifier.clone() }),
+            Event::Key(KeyEvent::ModifierHold(modifier, duration)) => {
+                Some(TraceEventKind::ModifierHold { modifier: modifier.clone(), duration: *duration })
+            },
+            Event::Key(KeyEvent::ModifierRelease(modifier)) => {
+                Some(TraceEventKind::ModifierRelease { modifier: modifier.clone() })
+            },
+            Event::Action(action) => Some(TraceEventKind::Action { action: action.clone() as i32 }),
+            Event::System(_) => None,
+        }
+    }
+
+    pub fn to_event(&self) -> Event {
+        match self {
+            TraceEventKind::Char { chars } => Event::Key(KeyEvent::Char(chars.clone())),
+            TraceEventKind::Modifier { modifier } => Event::Key(KeyEvent::Modifier(modifier.clone())),
+            TraceEventKind::ModifierHold { modifier, duration } => {
+                Event::Key(KeyEvent::ModifierHold(modifier.clone(), *duration))
+            },
+            TraceEventKind::ModifierRelease { modifier } => Event::Key(KeyEvent::ModifierRelease(modifier.clone())),
+            TraceEventKind::Action { action } => Event::Action(ActionType::from(*action)),
+        }
+    }
+
+    /// Hide the typed chars, keeping the word separators to preserve the word boundaries
+    pub fn redact(&self, word_separators: &[char]) -> TraceEventKind {
+        match self {
+            TraceEventKind::Char { chars } => {
+                let chars = chars.chars().map(|c| {
+                    if word_separators.contains(&c) { c } else { REDACTED_CHAR }
+                }).collect();
+                TraceEventKind::Char { chars }
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+impl TraceWindow {
+    fn from_system<S: SystemManager>(system_manager: &S, redact: bool) -> TraceWindow {
+        let title = system_manager.get_current_window_title();
+        TraceWindow {
+            title: if redact { title.map(|_| REDACTED_TITLE.to_owned()) } else { title },
+            class: system_manager.get_current_window_class(),
+            exec: system_manager.get_current_window_executable(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!("title: '{}', class: '{}', exec: '{}'",
+                self.title.as_ref().map(|s| s.as_str()).unwrap_or_default(),
+                self.class.as_ref().map(|s| s.as_str()).unwrap_or_default(),
+                self.exec.as_ref().map(|s| s.as_str()).unwrap_or_default())
+    }
+}
+
+pub fn load_trace(path: &Path) -> Result<Vec<TraceEvent>, String> {
+    let file = File::open(path).map_err(|e| format!("Unable to open trace file: {}", e))?;
+
+    let mut events = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Unable to read trace file: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let event = serde_json::from_str(&line)
+            .map_err(|e| format!("Invalid trace event at line {}: {}", i + 1, e))?;
+        events.push(event);
+    }
+
+    Ok(events)
+}
+
+/// Record the events received from the context until the process is terminated.
+/// The file is written line by line, so the trace is valid even if interrupted.
+pub fn record(config_set: ConfigSet, path: &Path, redact: bool) {
+    let file = File::create(path).unwrap_or_else(|e| {
+        eprintln!("Unable to create trace file: {}", e);
+        std::process::exit(1);
+    });
+
+    let (send_channel, receive_channel) = mpsc::channel();
+    let context = context::new(send_channel);
+
+    let word_separators = config_set.default.word_separators.clone();
+    thread::Builder::new().name("debug_record".to_string()).spawn(move || {
+        let system_manager = system::get_manager();
+        let mut writer = LineWriter::new(file);
+        let start = Instant::now();
+
+        for event in receive_channel.iter() {
+            let kind = match TraceEventKind::from_event(&event) {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            let trace_event = TraceEvent {
+                elapsed: start.elapsed().as_millis() as u64,
+                window: TraceWindow::from_system(&system_manager, redact),
+                kind: if redact { kind.redact(&word_separators) } else { kind },
+            };
+
+            let res = serde_json::to_string(&trace_event).map_err(|e| e.to_string())
+                .and_then(|json| writeln!(writer, "{}", json).map_err(|e| e.to_string()));
+            if let Err(e) = res {
+                warn!("Unable to write trace event: {}", e);
+            }
+        }
+    }).expect("Unable to spawn debug record thread");
+
+    println!("Recording events to {:?}, terminate with CTRL+C", path);
+    if redact {
+        println!("Typed text and window titles are redacted.");
+    }
+
+    context.eventloop();
+}
+
+/// Feed the recorded events through the matcher and the engine, printing the actions
+/// espanso would have performed. If realtime, the original timing is preserved, which is
+/// required to reproduce the behaviors depending on it, such as the toggle key.
+pub fn replay(config_set: ConfigSet, path: &Path, realtime: bool) {
+    let trace = load_trace(path).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    let recorder = Recorder::new();
+    let clipboard = MockClipboardManager::new(recorder.clone());
+    let system = MockSystemManager::new();
+    let ui_manager = MockUIManager::new(recorder.clone());
+    let history_manager = MockHistoryManager::new();
+
+    let mut expansions = 0;
+    // The shell and script variables are not evaluated, as the commands could have side effects
+    run_with_mocks(config_set, &system, &recorder, &clipboard, &ui_manager, &history_manager,
+                   extension::get_inert_extensions(), |event_manager, _| {
+        let start = Instant::now();
+
+        for event in trace.iter() {
+            if realtime {
+                let target = Duration::from_millis(event.elapsed);
+                let elapsed = start.elapsed();
+                if target > elapsed {
+                    thread::sleep(target - elapsed);
+                }
+            }
+
+            system.set_active_window(MockWindow {
+                title: event.window.title.clone(),
+                class: event.window.class.clone(),
+                exec: event.window.exec.clone(),
+            });
+
+            recorder.clear();
+            event_manager.dispatch(event.kind.to_event());
+
+            let actions = recorder.actions();
+            if !actions.is_empty() {
+                expansions += 1;
+                println!("[{} ms] {}", event.elapsed, event.window.describe());
+                for action in actions {
+                    println!("    {:?}", action);
+                }
+            }
+        }
+    });
+
+    println!("Replayed {} events, {} of them caused actions.", trace.len(), expansions);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_trace_event_serialization() {
+        let event = TraceEvent {
+            elapsed: 120,
+            window: TraceWindow {
+                title: Some("Title".to_owned()),
+                class: None,
+                exec: Some("exec".to_owned()),
+            },
+            kind: TraceEventKind::Char { chars: "a".to_owned() },
+        };
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"type\":\"char\""));
+        assert_eq!(serde_json::from_str::<TraceEvent>(&json).unwrap(), event);
+    }
+
+    #[test]
+    fn test_trace_event_kind_roundtrip() {
+        let kind = TraceEventKind::from_event(&Event::Key(KeyEvent::Modifier(KeyModifier::BACKSPACE))).unwrap();
+        assert_eq!(kind, TraceEventKind::Modifier { modifier: KeyModifier::BACKSPACE });
+
+        let kind = TraceEventKind::from_event(&Event::Action(ActionType::Toggle)).unwrap();
+        match kind.to_event() {
+            Event::Action(ActionType::Toggle) => {},
+            _ => panic!("wrong event"),
+        }
+    }
+
+    #[test]
+    fn test_redact_keeps_separators() {
+        let kind = TraceEventKind::Char { chars: "ab c.".to_owned() };
+        assert_eq!(kind.redact(&[' ', '.']), TraceEventKind::Char { chars: "** *.".to_owned() });
+
+        let kind = TraceEventKind::Modifier { modifier: KeyModifier::CTRL };
+        assert_eq!(kind.redact(&[' ']), kind);
+    }
+
+    #[test]
+    fn test_load_trace_invalid_line() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{{\"elapsed\":0,\"window\":{{}},\"type\":\"char\",\"chars\":\"a\"}}").unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let result = load_trace(file.path());
+        assert!(result.unwrap_err().contains("line 2"));
+    }
+}
@@ -20,53 +20,87 @@
 use crate::matcher::{Match, MatchReceiver, MatchContentType};
 use crate::keyboard::KeyboardManager;
 use crate::config::ConfigManager;
-use crate::config::BackendType;
-use crate::clipboard::ClipboardManager;
+use crate::config::{BackendType, Configs};
+use crate::clipboard::{ClipboardManager, ClipboardSnapshot};
 use log::{info, warn, error};
 use crate::ui::{UIManager, MenuItem, MenuItemType};
-use crate::event::{ActionEventReceiver, ActionType};
+use crate::event::{ActionEventReceiver, ActionType, SystemEventReceiver, SystemEvent,
+                   KeyEventReceiver, KeyEvent, KeyModifier};
+use crate::tabstop::{self, TabStopSession, Position};
 use crate::extension::Extension;
-use crate::render::{Renderer, RenderResult};
+use crate::render::{Renderer, RenderResult, RenderFragment};
+use crate::protocol::{IPCEvent, IPCEventKind};
+use crate::history::{HistoryManager, HistoryEntry, ExpansionSource};
+use crate::search::{self, SearchEntry};
 use std::cell::RefCell;
 use std::process::exit;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use regex::{Regex, Captures};
-use std::time::SystemTime;
+use std::time::{SystemTime, Duration, Instant};
+use std::sync::mpsc::Sender;
+
+// Maximum time to wait for the target application to paste the expansion
+const PASTE_CONFIRMATION_TIMEOUT: u64 = 2000;
+
+// Time needed by the previous window to get the focus back after the search picker is closed
+const SEARCH_PICKER_FOCUS_DELAY: u64 = 150;
+
+/// Clipboard state shared by the fragments of an injection
+#[derive(Default)]
+struct PasteState {
+    previous_clipboard_content: Option<Option<ClipboardSnapshot>>,  // Saved before the first paste
+    is_pending: bool,  // A paste was triggered, but not yet confirmed
+}
 
 pub struct Engine<'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>,
-                  U: UIManager, R: Renderer> {
+                  U: UIManager, R: Renderer, H: HistoryManager> {
     keyboard_manager: &'a S,
     clipboard_manager: &'a C,
     config_manager: &'a M,
     ui_manager: &'a U,
     renderer: &'a R,
+    history_manager: &'a H,
+    ipc_event_sender: Sender<IPCEvent>,
 
     enabled: RefCell<bool>,
+    tab_stop_session: RefCell<Option<TabStopSession>>,  // Tab stops of the last expansion, if any
     last_action_time: RefCell<SystemTime>,  // Used to block espanso from re-interpreting it's own inputs
     action_noop_interval: u128,
+    default_search_command: RefCell<Option<Option<String>>>,  // Looked up on first use, as it spawns a shell
 }
 
-impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIManager, R: Renderer>
-    Engine<'a, S, C, M, U, R> {
+impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIManager, R: Renderer, H: HistoryManager>
+    Engine<'a, S, C, M, U, R, H> {
     pub fn new(keyboard_manager: &'a S, clipboard_manager: &'a C,
                config_manager: &'a M, ui_manager: &'a U,
-               renderer: &'a R) -> Engine<'a, S, C, M, U, R> {
+               renderer: &'a R, history_manager: &'a H, ipc_event_sender: Sender<IPCEvent>) -> Engine<'a, S, C, M, U, R, H> {
         let enabled = RefCell::new(true);
+        let tab_stop_session = RefCell::new(None);
         let last_action_time = RefCell::new(SystemTime::now());
         let action_noop_interval = config_manager.default_config().action_noop_interval;
+        let default_search_command = RefCell::new(None);
 
         Engine{keyboard_manager,
             clipboard_manager,
             config_manager,
             ui_manager,
             renderer,
+            history_manager,
+            ipc_event_sender,
             enabled,
+            tab_stop_session,
             last_action_time,
             action_noop_interval,
+            default_search_command,
         }
     }
 
+    /// Restore the status of a previous engine, without notifying the user.
+    pub fn restore_enabled(&self, enabled: bool) {
+        *self.enabled.borrow_mut() = enabled;
+    }
+
     fn build_menu(&self) -> Vec<MenuItem> {
         let mut menu = Vec::new();
 
@@ -82,6 +116,15 @@ impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIMa
             item_id: ActionType::Toggle as i32,
         });
 
+        // The picker shows the labels and descriptions of the matches
+        if self.search_command().is_some() {
+            menu.push(MenuItem{
+                item_type: MenuItemType::Button,
+                item_name: "Search snippets".to_owned(),
+                item_id: ActionType::Search as i32,
+            });
+        }
+
         menu.push(MenuItem{
             item_type: MenuItemType::Separator,
             item_name: "".to_owned(),
@@ -97,64 +140,77 @@ impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIMa
         menu
     }
 
-    fn return_content_if_preserve_clipboard_is_enabled(&self) -> Option<String> {
+    fn return_snapshot_if_preserve_clipboard_is_enabled(&self) -> Option<ClipboardSnapshot> {
         // If the preserve_clipboard option is enabled, first save the current
-        // clipboard content in order to restore it later.
+        // clipboard content, in all its formats, in order to restore it later.
         if self.config_manager.default_config().preserve_clipboard {
-            match self.clipboard_manager.get_clipboard() {
-                Some(clipboard) => {Some(clipboard)},
-                None => {None},
-            }
+            self.clipboard_manager.get_snapshot()
         }else {
             None
         }
     }
 
-    /// Used to check if the last action has been executed within a specified interval.
-    /// If so, return true (blocking the action), otherwise false.
-    fn check_last_action_and_set(&self, interval: u128) -> bool {
-        let mut last_action_time = self.last_action_time.borrow_mut();
-        if let Ok(elapsed) = last_action_time.elapsed() {
-            if elapsed.as_millis() < interval {
-                return true;
+    /// Inject the rendered content into the active application, using the given backend.
+    /// The name is only used to report errors. Without extract_tab_stops, the cursor hint
+    /// and the tab stops are injected as they are, as for the raw text sent through IPC.
+    /// Return the number of injected chars and the backend actually used,
+    /// or None if the content could not be rendered.
+    fn inject_rendered(&self, config: &Configs, backend: BackendType, name: &str, rendered: RenderResult,
+                       trailing_separator: Option<char>, extract_tab_stops: bool) -> Option<(usize, BackendType)> {
+        let mut paste_state = PasteState::default();
+
+        let result = self.inject_content(config, backend, name, rendered, trailing_separator,
+                                         extract_tab_stops, &mut paste_state);
+
+        // Restore previous clipboard content
+        if let Some(Some(previous_clipboard_content)) = paste_state.previous_clipboard_content {
+            if paste_state.is_pending {
+                self.wait_for_paste(config);
             }
+            self.clipboard_manager.restore_snapshot(&previous_clipboard_content);
         }
 
-        (*last_action_time) = SystemTime::now();
-        return false;
+        result
     }
-}
-
-lazy_static! {
-    static ref VAR_REGEX: Regex = Regex::new("\\{\\{\\s*(?P<name>\\w+)\\s*\\}\\}").unwrap();
-}
-
-impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIManager, R: Renderer>
-    MatchReceiver for Engine<'a, S, C, M, U, R>{
-
-    fn on_match(&self, m: &Match, trailing_separator: Option<char>) {
-        let config = self.config_manager.active_config();
 
-        if !config.enable_active {
-            return;
+    /// Called before changing the clipboard content to paste it. The previous paste must be
+    /// completed first, while the original content is saved only once, so that the sequences
+    /// restore it at the end.
+    fn prepare_paste(&self, config: &Configs, paste_state: &mut PasteState) {
+        if paste_state.is_pending {
+            self.wait_for_paste(config);
         }
 
-        // avoid espanso reinterpreting its own actions
-        if self.check_last_action_and_set(self.action_noop_interval) {
-            return;
+        if paste_state.previous_clipboard_content.is_none() {
+            // If the preserve_clipboard option is enabled, save the current
+            // clipboard content to restore it later.
+            paste_state.previous_clipboard_content = Some(self.return_snapshot_if_preserve_clipboard_is_enabled());
         }
 
-        let char_count = if trailing_separator.is_none() {
-            m.trigger.chars().count() as i32
-        }else{
-            m.trigger.chars().count() as i32 + 1 // Count also the separator
-        };
+        paste_state.is_pending = true;
+    }
 
-        self.keyboard_manager.delete_string(char_count);
+    /// Wait for the target application to paste the clipboard content. The confirmation
+    /// can also come from other clients, such as the clipboard managers, so the
+    /// restore_clipboard_delay is kept as a minimum.
+    fn wait_for_paste(&self, config: &Configs) {
+        let paste_start = Instant::now();
+        self.clipboard_manager.wait_for_paste(Duration::from_millis(PASTE_CONFIRMATION_TIMEOUT));
+
+        let restore_delay = Duration::from_millis(config.restore_clipboard_delay as u64);
+        let elapsed = paste_start.elapsed();
+        if elapsed < restore_delay {
+            std::thread::sleep(restore_delay - elapsed);
+        }
+    }
 
-        let mut previous_clipboard_content : Option<String> = None;
+    fn inject_content(&self, config: &Configs, backend: BackendType, name: &str, rendered: RenderResult,
+                      trailing_separator: Option<char>, extract_tab_stops: bool,
+                      paste_state: &mut PasteState) -> Option<(usize, BackendType)> {
+        let mut result = None;
 
-        let rendered = self.renderer.render_match(m, config, vec![]);
+        // The injected content invalidates the positions of the previous tab stops
+        self.tab_stop_session.replace(None);
 
         match rendered {
             RenderResult::Text(mut target_string) => {
@@ -170,32 +226,41 @@ impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIMa
                 // Convert Windows style newlines into unix styles
                 target_string = target_string.replace("\r\n", "\n");
 
-                // Calculate cursor rewind moves if a Cursor Hint is present
-                let index = target_string.find("$|$");
-                let cursor_rewind = if let Some(index) = index {
-                    // Convert the byte index to a char index
-                    let char_str = &target_string[0..index];
-                    let char_index = char_str.chars().count();
-                    let total_size = target_string.chars().count();
-
-                    // Remove the $|$ placeholder
-                    target_string = target_string.replace("$|$", "");
-
-                    // Calculate the amount of rewind moves needed (LEFT ARROW).
-                    // Subtract also 3, equal to the number of chars of the placeholder "$|$"
-                    let moves = (total_size - char_index - 3) as i32;
-                    Some(moves)
+                // Remove the cursor hint and the tab stops, if present
+                let tab_stops = if extract_tab_stops {
+                    let (clean_string, tab_stops) = tabstop::extract_tab_stops(&target_string,
+                                                                               config.enable_tab_stops);
+                    target_string = clean_string;
+                    tab_stops
                 }else{
-                    None
+                    Vec::new()
                 };
 
-                match config.backend {
-                    BackendType::Inject => {
+                // The trailing separator, always the last char, shouldn't affect the Auto backend choice
+                let content = if trailing_separator.is_some() {
+                    let mut chars = target_string.chars();
+                    chars.next_back();
+                    chars.as_str()
+                }else{
+                    &target_string
+                };
+                let backend = backend.resolve(content, config.auto_backend_threshold);
+
+                match backend {
+                    BackendType::Inject | BackendType::Auto => {
+                        // The keys typed before the end of a previous paste would come first
+                        if paste_state.is_pending {
+                            self.wait_for_paste(config);
+                            paste_state.is_pending = false;
+                        }
+
                         // Send the expected string. On linux, newlines are managed automatically
                         // while on windows and macos, we need to emulate a Enter key press.
 
+                        let inject_options = config.inject_options();
+
                         if cfg!(target_os = "linux") {
-                            self.keyboard_manager.send_string(&target_string);
+                            self.keyboard_manager.send_string_throttled(&target_string, &inject_options);
                         }else{
                             // To handle newlines, substitute each "\n" char with an Enter key press.
                             let splits = target_string.split('\n');
@@ -205,45 +270,303 @@ impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIMa
                                     self.keyboard_manager.send_enter();
                                 }
 
-                                self.keyboard_manager.send_string(split);
+                                self.keyboard_manager.send_string_throttled(split, &inject_options);
                             }
                         }
                     },
                     BackendType::Clipboard => {
-                        // If the preserve_clipboard option is enabled, save the current
-                        // clipboard content to restore it later.
-                        previous_clipboard_content = self.return_content_if_preserve_clipboard_is_enabled();
+                        self.prepare_paste(config, paste_state);
 
                         self.clipboard_manager.set_clipboard(&target_string);
                         self.keyboard_manager.trigger_paste(&config.paste_shortcut);
                     },
                 }
 
-                if let Some(moves) = cursor_rewind {
-                    // Simulate left arrow key presses to bring the cursor into the desired position
-                    self.keyboard_manager.move_cursor_left(moves);
+                if !tab_stops.is_empty() {
+                    // Simulate arrow key presses to bring the cursor into the desired position
+                    self.start_tab_stop_session(&target_string, tab_stops);
                 }
+
+                // The trailing separator was typed by the user
+                let separator_length = if trailing_separator.is_some() { 1 } else { 0 };
+                result = Some((target_string.chars().count() - separator_length, backend));
             },
             RenderResult::Image(image_path) => {
-                // If the preserve_clipboard option is enabled, save the current
-                // clipboard content to restore it later.
-                previous_clipboard_content = self.return_content_if_preserve_clipboard_is_enabled();
+                self.prepare_paste(config, paste_state);
 
                 self.clipboard_manager.set_clipboard_image(&image_path);
                 self.keyboard_manager.trigger_paste(&config.paste_shortcut);
+
+                result = Some((0, BackendType::Clipboard));
+            },
+            RenderResult::Html { html, text } => {
+                // Rich text can only be pasted, regardless of the backend
+                self.prepare_paste(config, paste_state);
+
+                self.clipboard_manager.set_clipboard_html(&html, &text);
+                self.keyboard_manager.trigger_paste(&config.paste_shortcut);
+
+                result = Some((text.chars().count(), BackendType::Clipboard));
+            },
+            RenderResult::Sequence(fragments) => {
+                // Inject each fragment in order, using the clipboard for the images
+                let mut length = 0;
+                let mut last_was_image = false;
+                let mut text_backends: Vec<BackendType> = Vec::new();
+                let fragment_count = fragments.len();
+                for (i, fragment) in fragments.into_iter().enumerate() {
+                    // The trailing separator is added back only after the last fragment
+                    let separator = if i == fragment_count - 1 { trailing_separator } else { None };
+
+                    let fragment_result = match fragment {
+                        RenderFragment::Text(text) => {
+                            last_was_image = false;
+                            let fragment_result = self.inject_content(config, backend.clone(), name,
+                                                                      RenderResult::Text(text), separator,
+                                                                      extract_tab_stops, paste_state);
+                            if let Some((_, fragment_backend)) = &fragment_result {
+                                if !text_backends.contains(fragment_backend) {
+                                    text_backends.push(fragment_backend.clone());
+                                }
+                            }
+                            fragment_result
+                        },
+                        RenderFragment::Image(image_path) => {
+                            last_was_image = true;
+                            self.inject_content(config, backend.clone(), name, RenderResult::Image(image_path),
+                                                None, extract_tab_stops, paste_state)
+                        },
+                    };
+
+                    if let Some((fragment_length, _)) = fragment_result {
+                        length += fragment_length;
+                    }
+                }
+
+                if last_was_image && trailing_separator.is_some() {
+                    self.inject_content(config, backend, name, RenderResult::Text(String::new()),
+                                        trailing_separator, extract_tab_stops, paste_state);
+                }
+
+                // The backend of the text fragments, Auto if they were injected differently
+                let sequence_backend = match text_backends.as_slice() {
+                    [] => BackendType::Clipboard,
+                    [text_backend] => text_backend.clone(),
+                    _ => BackendType::Auto,
+                };
+                result = Some((length, sequence_backend));
             },
             RenderResult::Error => {
-                error!("Could not render match: {}", m.trigger);
+                error!("Could not render match: {}", name);
+
+                self.publish(IPCEventKind::Error {
+                    message: format!("Could not render match: {}", name),
+                });
             },
         }
 
-        // Restore previous clipboard content
-        if let Some(previous_clipboard_content) = previous_clipboard_content {
-            // Sometimes an expansion gets overwritten before pasting by the previous content
-            // A delay is needed to mitigate the problem
-            std::thread::sleep(std::time::Duration::from_millis(config.restore_clipboard_delay as u64));
+        result
+    }
+
+    /// Backend used to expand the given match, the match can override the config one
+    fn get_match_backend(m: &Match, config: &Configs) -> BackendType {
+        m.backend.clone().unwrap_or_else(|| config.backend.clone())
+    }
+
+    /// Notify the IPC subscribers and record the expansion in the history
+    fn on_expanded(&self, m: &Match, config: &Configs, length: usize, backend: BackendType,
+                   source: ExpansionSource) {
+        self.publish(IPCEventKind::Expansion {
+            trigger: m.trigger.clone(),
+            config: config.name.clone(),
+        });
+
+        self.history_manager.record(HistoryEntry::new(&m.display_name(), &config.name,
+                                                      length, backend).with_source(source));
+    }
 
-            self.clipboard_manager.set_clipboard(&previous_clipboard_content);
+    /// Check if the text requested through IPC can be injected, as it happens
+    /// for the typed triggers. Return the reason otherwise.
+    fn check_ipc_injection_allowed(&self) -> Result<(), &'static str> {
+        if !*self.enabled.borrow() {
+            return Err("espanso is disabled");
+        }
+
+        if self.config_manager.is_input_suspended() {
+            return Err("matching is suspended in the active window");
+        }
+
+        Ok(())
+    }
+
+    /// Let the user choose one of the active matches through the search picker,
+    /// then expand it without deleting any trigger.
+    fn search_and_expand(&self) {
+        let config = self.config_manager.active_config();
+
+        // The picker takes the focus, so the active matches are collected before opening it
+        let entries: Vec<SearchEntry> = config.matches.iter()
+            .filter(|m| self.config_manager.is_match_active(m))
+            .map(SearchEntry::new)
+            .collect();
+
+        let command = if let Some(command) = self.search_command() {
+            command
+        }else{
+            warn!("No search picker available, install rofi or set the search_command option");
+            self.ui_manager.notify("No search picker available");
+            return;
+        };
+
+        let lines: Vec<String> = entries.iter().map(|entry| entry.line.clone()).collect();
+        let output = self.ui_manager.pick(&command, &lines);
+
+        // Avoid reinterpreting the keys typed in the picker
+        self.set_last_action();
+
+        let entry = output.and_then(|output| search::resolve(&entries, &output));
+        if let Some(entry) = entry {
+            let m = entry.m;
+            info!("Expanding match '{}' chosen through the search",
+                  self.config_manager.default_config().loggable(&m.display_name()));
+
+            std::thread::sleep(Duration::from_millis(SEARCH_PICKER_FOCUS_DELAY));
+
+            let rendered = self.renderer.render_match(m, config, vec![]);
+            let backend = Self::get_match_backend(m, config);
+            if let Some((length, backend)) = self.inject_rendered(config, backend, &m.trigger,
+                                                                  rendered, None, true) {
+                self.on_expanded(m, config, length, backend, ExpansionSource::Search);
+            }
+        }
+    }
+
+    /// The configured search picker, or the first one available on the system
+    fn search_command(&self) -> Option<String> {
+        let command = &self.config_manager.default_config().search_command;
+        if !command.is_empty() {
+            return Some(command.clone());
+        }
+
+        self.default_search_command.borrow_mut()
+            .get_or_insert_with(search::picker::default_command)
+            .clone()
+    }
+
+    /// Notify the IPC subscribers. Errors are ignored, as the IPC server
+    /// may not be running.
+    fn publish(&self, kind: IPCEventKind) {
+        self.ipc_event_sender.send(IPCEvent::new(kind)).unwrap_or_default();
+    }
+
+    /// Move the cursor to the first tab stop, keeping track of the remaining ones
+    fn start_tab_stop_session(&self, text: &str, tab_stops: Vec<Position>) {
+        let mut session = TabStopSession::new(text, tab_stops);
+        self.move_to_next_tab_stop(&mut session);
+
+        if session.has_next() {
+            self.tab_stop_session.replace(Some(session));
+        }
+    }
+
+    fn move_to_next_tab_stop(&self, session: &mut TabStopSession) {
+        if let Some(movements) = session.next() {
+            for (key, count) in movements {
+                self.keyboard_manager.move_cursor(key, count);
+            }
+        }
+
+        // Avoid counting the injected keys as typed by the user
+        self.set_last_action();
+    }
+
+    /// Mark the current time as the last action, so that espanso doesn't
+    /// re-interpret the keystrokes it injects.
+    fn set_last_action(&self) {
+        let mut last_action_time = self.last_action_time.borrow_mut();
+        (*last_action_time) = SystemTime::now();
+    }
+
+    /// Check if the last action happened within the noop interval, without updating it
+    fn is_last_action_recent(&self) -> bool {
+        let last_action_time = self.last_action_time.borrow();
+        if let Ok(elapsed) = last_action_time.elapsed() {
+            return elapsed.as_millis() < self.action_noop_interval;
+        }
+
+        false
+    }
+
+    /// Used to check if the last action has been executed within a specified interval.
+    /// If so, return true (blocking the action), otherwise false.
+    fn check_last_action_and_set(&self, interval: u128) -> bool {
+        let mut last_action_time = self.last_action_time.borrow_mut();
+        if let Ok(elapsed) = last_action_time.elapsed() {
+            if elapsed.as_millis() < interval {
+                return true;
+            }
+        }
+
+        (*last_action_time) = SystemTime::now();
+        return false;
+    }
+}
+
+lazy_static! {
+    static ref VAR_REGEX: Regex = Regex::new("\\{\\{\\s*(?P<name>\\w+)\\s*\\}\\}").unwrap();
+}
+
+impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIManager, R: Renderer, H: HistoryManager>
+    MatchReceiver for Engine<'a, S, C, M, U, R, H>{
+
+    fn on_match(&self, m: &Match, trailing_separator: Option<char>) {
+        let config = self.config_manager.active_config();
+
+        if !config.enable_active {
+            return;
+        }
+
+        // avoid espanso reinterpreting its own actions
+        if self.check_last_action_and_set(self.action_noop_interval) {
+            return;
+        }
+
+        let char_count = if trailing_separator.is_none() {
+            m.trigger.chars().count() as i32
+        }else{
+            m.trigger.chars().count() as i32 + 1 // Count also the separator
+        };
+
+        self.keyboard_manager.delete_string_throttled(char_count, &config.inject_options());
+
+        let rendered = self.renderer.render_match(m, config, vec![]);
+
+        let backend = Self::get_match_backend(m, config);
+        if let Some((length, backend)) = self.inject_rendered(config, backend, &m.trigger,
+                                                              rendered, trailing_separator, true) {
+            self.on_expanded(m, config, length, backend, ExpansionSource::Typed);
+        }
+    }
+
+    fn on_hotkey_match(&self, m: &Match) {
+        let config = self.config_manager.active_config();
+
+        if !config.enable_active {
+            return;
+        }
+
+        // avoid espanso reinterpreting its own actions
+        if self.check_last_action_and_set(self.action_noop_interval) {
+            return;
+        }
+
+        // Nothing was typed, so there is no trigger to delete
+        let rendered = self.renderer.render_match(m, config, vec![]);
+
+        let backend = Self::get_match_backend(m, config);
+        if let Some((length, backend)) = self.inject_rendered(config, backend, &m.trigger,
+                                                              rendered, None, true) {
+            self.on_expanded(m, config, length, backend, ExpansionSource::Hotkey);
         }
     }
 
@@ -265,6 +588,19 @@ impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIMa
         *enabled_ref = status;
 
         self.ui_manager.notify(message);
+
+        self.publish(IPCEventKind::StatusChanged { enabled: status });
+    }
+
+    fn on_next_tab_stop(&self) {
+        let mut session_ref = self.tab_stop_session.borrow_mut();
+        if let Some(session) = session_ref.as_mut() {
+            self.move_to_next_tab_stop(session);
+
+            if !session.has_next() {
+                *session_ref = None;
+            }
+        }
     }
 
     fn on_passive(&self) {
@@ -311,7 +647,36 @@ impl <'a, S: KeyboardManager, C: ClipboardManager, M: ConfigManager<'a>, U: UIMa
 }
 
 impl <'a, S: KeyboardManager, C: ClipboardManager,
-    M: ConfigManager<'a>, U: UIManager, R: Renderer> ActionEventReceiver for Engine<'a, S, C, M, U, R>{
+    M: ConfigManager<'a>, U: UIManager, R: Renderer, H: HistoryManager> KeyEventReceiver for Engine<'a, S, C, M, U, R, H>{
+
+    // Keep track of the chars typed at the current tab stop, which shift the following ones
+    fn on_key_event(&self, e: KeyEvent) {
+        let mut session_ref = self.tab_stop_session.borrow_mut();
+        if session_ref.is_none() || self.is_last_action_recent() {
+            return;
+        }
+
+        match e {
+            KeyEvent::Char(c) => {
+                if c.contains('\n') || c.contains('\r') {
+                    // New lines change the layout, the positions are not valid anymore
+                    *session_ref = None;
+                }else if let Some(session) = session_ref.as_mut() {
+                    session.on_typed(c.chars().count() as i32);
+                }
+            },
+            KeyEvent::Modifier(KeyModifier::BACKSPACE) => {
+                if let Some(session) = session_ref.as_mut() {
+                    session.on_typed(-1);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+impl <'a, S: KeyboardManager, C: ClipboardManager,
+    M: ConfigManager<'a>, U: UIManager, R: Renderer, H: HistoryManager> ActionEventReceiver for Engine<'a, S, C, M, U, R, H>{
 
     fn on_action_event(&self, e: ActionType) {
         match e {
@@ -323,6 +688,95 @@ impl <'a, S: KeyboardManager, C: ClipboardManager,
                 self.ui_manager.cleanup();
                 exit(0);
             },
+            ActionType::Passive => {
+                self.on_passive();
+            },
+            ActionType::Search => {
+                self.search_and_expand();
+            },
+            ActionType::ReloadConfig => {
+                // The event manager returns, so that the daemon can rebuild
+                // the engine with the new configs
+                info!("Reloading the configuration");
+            },
+            _ => {}
+        }
+    }
+}
+
+impl <'a, S: KeyboardManager, C: ClipboardManager,
+    M: ConfigManager<'a>, U: UIManager, R: Renderer, H: HistoryManager> SystemEventReceiver for Engine<'a, S, C, M, U, R, H>{
+
+    fn on_system_event(&self, e: SystemEvent) {
+        match e {
+            SystemEvent::SwitchProfile(name) => {
+                if name.is_empty() {
+                    self.config_manager.set_active_profile(None);
+
+                    info!("Profile reset, all configs are enabled");
+                    self.ui_manager.notify("All configs enabled");
+
+                    self.publish(IPCEventKind::ProfileSwitched { profile: None });
+                }else if self.config_manager.set_active_profile(Some(name.clone())) {
+                    info!("Switched to profile: {}", name);
+                    self.ui_manager.notify(&format!("Switched to profile: {}", name));
+
+                    self.publish(IPCEventKind::ProfileSwitched { profile: Some(name) });
+                }else{
+                    warn!("Unable to switch to profile '{}', not found", name);
+                }
+            },
+            SystemEvent::ExpandMatch(trigger, args) => {
+                // Hotkey-only matches have no trigger, so they can't be requested
+                if trigger.is_empty() {
+                    warn!("Unable to expand match, no trigger specified");
+                    return;
+                }
+
+                if let Err(reason) = self.check_ipc_injection_allowed() {
+                    warn!("Unable to expand match '{}', {}", trigger, reason);
+                    return;
+                }
+
+                let config = self.config_manager.active_config();
+
+                // Consider only the matches enabled for the current window and profile,
+                // as if the trigger was typed
+                let m = config.matches.iter().find(|m| {
+                    m.trigger == trigger && self.config_manager.is_match_active(m)
+                });
+                if let Some(m) = m {
+                    info!("Expanding match '{}' requested through IPC",
+                          self.config_manager.default_config().loggable(&trigger));
+
+                    self.set_last_action();
+
+                    let rendered = self.renderer.render_match(m, config, args);
+                    let backend = Self::get_match_backend(m, config);
+                    if let Some((length, backend)) = self.inject_rendered(config, backend, &m.trigger,
+                                                                          rendered, None, true) {
+                        self.on_expanded(m, config, length, backend, ExpansionSource::Ipc);
+                    }
+                }else{
+                    warn!("Unable to expand match '{}', not found in the active config", trigger);
+                }
+            },
+            SystemEvent::InjectText(text) => {
+                if let Err(reason) = self.check_ipc_injection_allowed() {
+                    warn!("Unable to inject text, {}", reason);
+                    return;
+                }
+
+                let config = self.config_manager.active_config();
+
+                self.set_last_action();
+
+                if let Some((length, backend)) = self.inject_rendered(config, config.backend.clone(), "injected text",
+                                                                      RenderResult::Text(text), None, false) {
+                    self.history_manager.record(HistoryEntry::new("", &config.name, length, backend)
+                        .with_source(ExpansionSource::Inject));
+                }
+            },
             _ => {}
         }
     }
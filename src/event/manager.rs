@@ -17,28 +17,72 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::event::{KeyEventReceiver, ActionEventReceiver, Event};
+use crate::event::{KeyEventReceiver, ActionEventReceiver, SystemEventReceiver, Event, ActionType, KeyEvent};
 use std::sync::mpsc::Receiver;
 
 pub trait EventManager {
+    // Dispatch the incoming events, until a configuration reload is requested
     fn eventloop(&self);
+
+    // Deliver the event to the receivers, in order, followed by the actions of the
+    // gestures it fired. Returns true if a configuration reload was requested.
+    fn dispatch(&self, event: Event) -> bool;
 }
 
 pub struct DefaultEventManager<'a> {
-    receive_channel: Receiver<Event>,
+    receive_channel: &'a Receiver<Event>,
     key_receivers: Vec<&'a dyn KeyEventReceiver>,
     action_receivers: Vec<&'a dyn ActionEventReceiver>,
+    system_receivers: Vec<&'a dyn SystemEventReceiver>,
 }
 
 impl<'a> DefaultEventManager<'a> {
-    pub fn new(receive_channel: Receiver<Event>, key_receivers: Vec<&'a dyn KeyEventReceiver>,
-               action_receivers: Vec<&'a dyn ActionEventReceiver>) -> DefaultEventManager<'a> {
+    pub fn new(receive_channel: &'a Receiver<Event>, key_receivers: Vec<&'a dyn KeyEventReceiver>,
+               action_receivers: Vec<&'a dyn ActionEventReceiver>,
+               system_receivers: Vec<&'a dyn SystemEventReceiver>) -> DefaultEventManager<'a> {
         DefaultEventManager {
             receive_channel,
             key_receivers,
             action_receivers,
+            system_receivers,
+        }
+    }
+
+    /// Deliver the event to the receivers, returning the actions it triggered
+    fn deliver(&self, event: Event) -> Vec<ActionType> {
+        match event {
+            Event::Key(key_event) => {
+                self.key_receivers.iter().for_each(move |&receiver| receiver.on_key_event(key_event.clone()));
+                self.key_receivers.iter().flat_map(|&receiver| receiver.take_actions()).collect()
+            },
+            Event::Action(action_event) => {
+                self.action_receivers.iter().for_each(|&receiver| receiver.on_action_event(action_event.clone()));
+                Vec::new()
+            },
+            Event::System(system_event) => {
+                self.system_receivers.iter().for_each(|&receiver| receiver.on_system_event(system_event.clone()));
+                Vec::new()
+            }
         }
     }
+
+    /// The search picker blocks the dispatch, the keys typed in it are queued in
+    /// the meantime and must not trigger any match. The releases are kept to track
+    /// the held modifiers. Returns true if a reload was requested in the meantime.
+    fn discard_picker_keys(&self) -> bool {
+        while let Ok(pending) = self.receive_channel.try_recv() {
+            match pending {
+                Event::Key(KeyEvent::ModifierRelease(_)) => {},
+                Event::Key(_) => continue,
+                _ => {},
+            }
+            if self.dispatch(pending) {
+                return true;
+            }
+        }
+
+        false
+    }
 }
 
 impl <'a> EventManager for DefaultEventManager<'a> {
@@ -46,17 +90,104 @@ impl <'a> EventManager for DefaultEventManager<'a> {
         loop {
             match self.receive_channel.recv() {
                 Ok(event) => {
-                    match event {
-                        Event::Key(key_event) => {
-                            self.key_receivers.iter().for_each(move |&receiver| receiver.on_key_event(key_event.clone()));
-                        },
-                        Event::Action(action_event) => {
-                            self.action_receivers.iter().for_each(|&receiver| receiver.on_action_event(action_event.clone()));
-                        }
+                    // The receivers borrow the current configs, so they must be
+                    // rebuilt by the caller to pick up the new ones
+                    if self.dispatch(event) {
+                        return;
                     }
                 },
                 Err(e) => panic!("Broken event channel {}", e),
             }
         }
     }
-}
\ No newline at end of file
+
+    fn dispatch(&self, event: Event) -> bool {
+        let mut is_reload = false;
+        let mut pending = vec![event];
+
+        while !pending.is_empty() {
+            let event = pending.remove(0);
+            let (reload, search) = match &event {
+                Event::Action(ActionType::ReloadConfig) => (true, false),
+                Event::Action(ActionType::Search) => (false, true),
+                _ => (false, false),
+            };
+
+            let actions = self.deliver(event);
+            pending.extend(actions.into_iter().map(Event::Action));
+
+            if reload || (search && self.discard_picker_keys()) {
+                is_reload = true;
+            }
+        }
+
+        is_reload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::KeyModifier;
+    use std::cell::RefCell;
+    use std::sync::mpsc;
+
+    struct ActionRecorder {
+        actions: RefCell<Vec<ActionType>>,
+    }
+
+    impl ActionEventReceiver for ActionRecorder {
+        fn on_action_event(&self, e: ActionType) {
+            self.actions.borrow_mut().push(e);
+        }
+    }
+
+    #[test]
+    fn test_eventloop_returns_after_reload_config() {
+        let recorder = ActionRecorder { actions: RefCell::new(Vec::new()) };
+        let (send_channel, receive_channel) = mpsc::channel();
+        send_channel.send(Event::Action(ActionType::Toggle)).unwrap();
+        send_channel.send(Event::Action(ActionType::ReloadConfig)).unwrap();
+        send_channel.send(Event::Action(ActionType::Exit)).unwrap();
+
+        let event_manager = DefaultEventManager::new(&receive_channel, vec!(), vec!(&recorder), vec!());
+        event_manager.eventloop();
+
+        assert_eq!(*recorder.actions.borrow(), vec!(ActionType::Toggle, ActionType::ReloadConfig));
+
+        // The events queued after the reload are left for the next event manager
+        match receive_channel.try_recv() {
+            Ok(Event::Action(ActionType::Exit)) => {},
+            _ => panic!("expected the exit action to be still queued"),
+        }
+    }
+
+    struct KeyRecorder {
+        keys: RefCell<Vec<String>>,
+    }
+
+    impl KeyEventReceiver for KeyRecorder {
+        fn on_key_event(&self, e: KeyEvent) {
+            self.keys.borrow_mut().push(format!("{:?}", e));
+        }
+    }
+
+    #[test]
+    fn test_keys_typed_in_search_picker_are_discarded() {
+        let keys = KeyRecorder { keys: RefCell::new(Vec::new()) };
+        let actions = ActionRecorder { actions: RefCell::new(Vec::new()) };
+        let (send_channel, receive_channel) = mpsc::channel();
+        send_channel.send(Event::Action(ActionType::Search)).unwrap();
+        send_channel.send(Event::Key(KeyEvent::Char("a".to_owned()))).unwrap();
+        send_channel.send(Event::Key(KeyEvent::Modifier(KeyModifier::CTRL))).unwrap();
+        send_channel.send(Event::Key(KeyEvent::ModifierRelease(KeyModifier::CTRL))).unwrap();
+        send_channel.send(Event::Action(ActionType::Toggle)).unwrap();
+        send_channel.send(Event::Action(ActionType::ReloadConfig)).unwrap();
+
+        let event_manager = DefaultEventManager::new(&receive_channel, vec!(&keys), vec!(&actions), vec!());
+        event_manager.eventloop();
+
+        assert_eq!(*keys.keys.borrow(), vec!("ModifierRelease(CTRL)".to_owned()));
+        assert_eq!(*actions.actions.borrow(), vec!(ActionType::Search, ActionType::Toggle, ActionType::ReloadConfig));
+    }
+}
@@ -20,14 +20,18 @@
 pub(crate) mod manager;
 
 use serde::{Serialize, Deserialize};
+use std::sync::mpsc::Sender;
+use crate::protocol::DaemonStatus;
 
 #[derive(Debug, Clone)]
 pub enum Event {
     Action(ActionType),
-    Key(KeyEvent)
+    Key(KeyEvent),
+    System(SystemEvent),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ActionType {
     Noop = 0,
     Toggle = 1,
@@ -35,6 +39,9 @@ pub enum ActionType {
     IconClick = 3,
     Enable = 4,
     Disable = 5,
+    Passive = 6,
+    ReloadConfig = 7,
+    Search = 8,
 }
 
 impl From<i32> for ActionType {
@@ -45,6 +52,9 @@ impl From<i32> for ActionType {
             3 => ActionType::IconClick,
             4 => ActionType::Enable,
             5 => ActionType::Disable,
+            6 => ActionType::Passive,
+            7 => ActionType::ReloadConfig,
+            8 => ActionType::Search,
             _ => ActionType::Noop,
         }
     }
@@ -53,10 +63,18 @@ impl From<i32> for ActionType {
 #[derive(Debug, Clone)]
 pub enum KeyEvent {
     Char(String),
-    Modifier(KeyModifier)
+    Modifier(KeyModifier),
+
+    // Modifier released after being held alone for the given milliseconds.
+    // Only sent by the contexts that report both the press and the release.
+    ModifierHold(KeyModifier, u64),
+
+    // Modifier released, sent before the ModifierHold event, if any.
+    // Only sent by the contexts that report both the press and the release.
+    ModifierRelease(KeyModifier),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum KeyModifier {
     CTRL,
     SHIFT,
@@ -66,12 +84,38 @@ pub enum KeyModifier {
     OFF,
 }
 
+// Events that carry a payload, usually generated through IPC commands
+#[derive(Debug, Clone)]
+pub enum SystemEvent {
+    // Switch to the given profile, an empty name enables all the configs again
+    SwitchProfile(String),
+
+    // Request the current daemon status, sent back through the given channel
+    StatusRequest(Sender<DaemonStatus>),
+
+    // Expand the match with the given trigger and arguments, as if it was typed
+    // but without deleting the trigger
+    ExpandMatch(String, Vec<String>),
+
+    // Inject the given text through the configured backend
+    InjectText(String),
+}
+
 // Receivers
 
 pub trait KeyEventReceiver {
     fn on_key_event(&self, e: KeyEvent);
+
+    // Actions triggered by the last key events, dispatched right after them
+    fn take_actions(&self) -> Vec<ActionType> {
+        Vec::new()
+    }
 }
 
 pub trait ActionEventReceiver {
     fn on_action_event(&self, e: ActionType);
+}
+
+pub trait SystemEventReceiver {
+    fn on_system_event(&self, e: SystemEvent);
 }
\ No newline at end of file
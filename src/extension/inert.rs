@@ -0,0 +1,72 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde_yaml::Mapping;
+
+/// Stands in for the extensions running external commands when the expansions are
+/// only simulated, as in the debug replay, so that the commands have no side effects.
+pub struct InertExtension {
+    name: String,
+}
+
+impl InertExtension {
+    pub fn new(name: &str) -> InertExtension {
+        InertExtension{name: name.to_owned()}
+    }
+}
+
+impl super::Extension for InertExtension {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn calculate(&self, _: &Mapping, _: &Vec<String>) -> Option<String> {
+        Some(format!("<{} output>", self.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_yaml::Value;
+    use crate::extension::get_inert_extensions;
+
+    #[test]
+    fn test_inert_shell_does_not_run_the_command() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let marker = dir.path().join("marker");
+
+        let mut params = Mapping::new();
+        params.insert(Value::from("cmd"), Value::from(format!("touch '{}'", marker.to_string_lossy())));
+
+        let extensions = get_inert_extensions();
+        let shell = extensions.iter().find(|extension| extension.name() == "shell").unwrap();
+        assert_eq!(shell.calculate(&params, &vec![]), Some("<shell output>".to_owned()));
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_inert_extensions_cover_the_default_ones() {
+        let mut names: Vec<String> = get_inert_extensions().iter().map(|extension| extension.name()).collect();
+        let mut default_names: Vec<String> = crate::extension::get_extensions().iter().map(|extension| extension.name()).collect();
+        names.sort();
+        default_names.sort();
+        assert_eq!(names, default_names);
+    }
+}
@@ -24,6 +24,7 @@ mod shell;
 mod script;
 mod random;
 mod dummy;
+mod inert;
 
 pub trait Extension {
     fn name(&self) -> String;
@@ -38,4 +39,15 @@ pub fn get_extensions() -> Vec<Box<dyn Extension>> {
         Box::new(random::RandomExtension::new()),
         Box::new(dummy::DummyExtension::new()),
     ]
+}
+
+/// Extensions without side effects, the shell and script ones don't run any command
+pub fn get_inert_extensions() -> Vec<Box<dyn Extension>> {
+    vec![
+        Box::new(date::DateExtension::new()),
+        Box::new(inert::InertExtension::new("shell")),
+        Box::new(inert::InertExtension::new("script")),
+        Box::new(random::RandomExtension::new()),
+        Box::new(dummy::DummyExtension::new()),
+    ]
 }
\ No newline at end of file
@@ -0,0 +1,109 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::path::PathBuf;
+use std::fs::OpenOptions;
+use std::io::Write;
+use log::warn;
+use crate::history::{HistoryManager, HistoryEntry};
+
+pub const HISTORY_FILE_NAME : &str = "history.jsonl";
+
+/// Append the expansions to a file, one JSON entry per line.
+pub struct DefaultHistoryManager {
+    history_file: PathBuf,
+    enabled: bool,
+}
+
+impl DefaultHistoryManager {
+    pub fn new(history_file: PathBuf, enabled: bool) -> DefaultHistoryManager {
+        DefaultHistoryManager {
+            history_file,
+            enabled,
+        }
+    }
+
+    pub fn new_default(enabled: bool) -> DefaultHistoryManager {
+        DefaultHistoryManager::new(Self::default_history_file(), enabled)
+    }
+
+    pub fn default_history_file() -> PathBuf {
+        crate::context::get_data_dir().join(HISTORY_FILE_NAME)
+    }
+}
+
+impl HistoryManager for DefaultHistoryManager {
+    fn record(&self, entry: HistoryEntry) {
+        if !self.enabled {
+            return;
+        }
+
+        let json_str = match serde_json::to_string(&entry) {
+            Ok(json_str) => json_str,
+            Err(e) => {
+                warn!("Unable to serialize history entry: {}", e);
+                return;
+            },
+        };
+
+        let res = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history_file)
+            .and_then(|mut file| writeln!(file, "{}", json_str));
+
+        if let Err(e) = res {
+            warn!("Unable to record expansion in history: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BackendType;
+    use crate::history::load_history;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_record_appends_entries() {
+        let dir = TempDir::new().unwrap();
+        let history_file = dir.path().join(HISTORY_FILE_NAME);
+        let manager = DefaultHistoryManager::new(history_file.clone(), true);
+
+        manager.record(HistoryEntry::new(":hi", "default", 10, BackendType::Inject));
+        manager.record(HistoryEntry::new(":bye", "default", 3, BackendType::Clipboard));
+
+        let entries = load_history(&history_file);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].trigger, ":hi");
+        assert_eq!(entries[1].backend, BackendType::Clipboard);
+    }
+
+    #[test]
+    fn test_record_disabled() {
+        let dir = TempDir::new().unwrap();
+        let history_file = dir.path().join(HISTORY_FILE_NAME);
+        let manager = DefaultHistoryManager::new(history_file.clone(), false);
+
+        manager.record(HistoryEntry::new(":hi", "default", 10, BackendType::Inject));
+
+        assert!(!history_file.exists());
+    }
+}
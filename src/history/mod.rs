@@ -0,0 +1,247 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+pub(crate) mod default;
+
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufRead};
+use std::path::Path;
+use log::warn;
+use crate::config::BackendType;
+
+pub trait HistoryManager {
+    fn record(&self, entry: HistoryEntry);
+}
+
+/// How an expansion was requested
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpansionSource {
+    Typed,
+    Hotkey,
+    Search,
+    Ipc,     // Match expanded with the "expand" IPC command
+    Inject,  // Text injected with the "inject" IPC command, without any trigger
+}
+impl Default for ExpansionSource {
+    fn default() -> Self {
+        ExpansionSource::Typed
+    }
+}
+
+/// A single expansion. The expanded content is not stored, only its length.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct HistoryEntry {
+    pub trigger: String,
+    pub config: String,
+    pub length: usize,   // Number of chars injected
+    pub timestamp: i64,  // Seconds since the unix epoch
+    pub backend: BackendType,
+
+    #[serde(default)]
+    pub source: ExpansionSource,
+}
+
+impl HistoryEntry {
+    pub fn new(trigger: &str, config: &str, length: usize, backend: BackendType) -> HistoryEntry {
+        HistoryEntry {
+            trigger: trigger.to_owned(),
+            config: config.to_owned(),
+            length,
+            timestamp: chrono::Utc::now().timestamp(),
+            backend,
+            source: ExpansionSource::Typed,
+        }
+    }
+
+    pub fn with_source(mut self, source: ExpansionSource) -> HistoryEntry {
+        self.source = source;
+        self
+    }
+
+    /// Number of chars the user didn't have to type
+    pub fn chars_saved(&self) -> usize {
+        // Only the typed triggers cost some keystrokes
+        if self.source == ExpansionSource::Typed {
+            self.length.saturating_sub(self.trigger.chars().count())
+        }else{
+            self.length
+        }
+    }
+}
+
+/// Load the entries recorded in the given history file, skipping the malformed ones.
+pub fn load_history(history_file: &Path) -> Vec<HistoryEntry> {
+    let file = match File::open(history_file) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),  // Nothing recorded yet
+    };
+
+    BufReader::new(file).lines().filter_map(|line| {
+        let line = line.ok()?;
+        match serde_json::from_str(&line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("Skipping invalid history entry: {}", e);
+                None
+            },
+        }
+    }).collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HistoryStats {
+    pub expansions: usize,
+    pub chars_saved: usize,
+    pub usage: Vec<(String, usize)>,  // Trigger and number of expansions, most used first
+}
+
+pub fn calculate_stats(entries: &[HistoryEntry]) -> HistoryStats {
+    // Injected text has no trigger, so it's only counted in the totals
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for entry in entries.iter().filter(|entry| !entry.trigger.is_empty()) {
+        *counts.entry(&entry.trigger).or_insert(0) += 1;
+    }
+
+    let mut usage: Vec<(String, usize)> = counts.into_iter()
+        .map(|(trigger, count)| (trigger.to_owned(), count))
+        .collect();
+
+    // Sort by count, then by trigger to have a stable output
+    usage.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    HistoryStats {
+        expansions: entries.len(),
+        chars_saved: entries.iter().map(|e| e.chars_saved()).sum(),
+        usage,
+    }
+}
+
+/// Return the triggers that have not been expanded since the given timestamp,
+/// preserving the order in which they are given.
+pub fn find_unused_triggers<'a>(entries: &[HistoryEntry], triggers: &[&'a str], since: i64) -> Vec<&'a str> {
+    let mut last_used: HashMap<&str, i64> = HashMap::new();
+    for entry in entries.iter() {
+        let timestamp = last_used.entry(&entry.trigger).or_insert(entry.timestamp);
+        if entry.timestamp > *timestamp {
+            *timestamp = entry.timestamp;
+        }
+    }
+
+    triggers.iter().filter(|trigger| {
+        match last_used.get(*trigger) {
+            Some(timestamp) => *timestamp < since,
+            None => true,
+        }
+    }).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn entry(trigger: &str, length: usize, timestamp: i64) -> HistoryEntry {
+        HistoryEntry {
+            trigger: trigger.to_owned(),
+            config: "default".to_owned(),
+            length,
+            timestamp,
+            backend: BackendType::Inject,
+            source: ExpansionSource::Typed,
+        }
+    }
+
+    #[test]
+    fn test_chars_saved() {
+        assert_eq!(entry(":hi", 10, 0).chars_saved(), 7);
+        assert_eq!(entry(":long", 2, 0).chars_saved(), 0);
+        assert_eq!(entry("CTRL+ALT+e", 4, 0).with_source(ExpansionSource::Hotkey).chars_saved(), 4);
+    }
+
+    #[test]
+    fn test_load_history_skips_invalid_lines() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "{}", serde_json::to_string(&entry(":hi", 10, 5)).unwrap()).unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(file, "{}", serde_json::to_string(&entry(":bye", 3, 6)).unwrap()).unwrap();
+
+        let entries = load_history(file.path());
+        assert_eq!(entries, vec![entry(":hi", 10, 5), entry(":bye", 3, 6)]);
+    }
+
+    #[test]
+    fn test_load_history_missing_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert!(load_history(&dir.path().join("history.jsonl")).is_empty());
+    }
+
+    #[test]
+    fn test_calculate_stats() {
+        let entries = vec![
+            entry(":a", 5, 1),
+            entry(":b", 10, 2),
+            entry(":b", 10, 3),
+            entry(":c", 4, 4),
+        ];
+
+        let stats = calculate_stats(&entries);
+        assert_eq!(stats.expansions, 4);
+        assert_eq!(stats.chars_saved, 3 + 8 + 8 + 2);
+        assert_eq!(stats.usage, vec![
+            (":b".to_owned(), 2),
+            (":a".to_owned(), 1),
+            (":c".to_owned(), 1),
+        ]);
+    }
+
+    #[test]
+    fn test_calculate_stats_skips_injected_text_usage() {
+        let entries = vec![
+            entry(":a", 5, 1),
+            entry("", 4, 2).with_source(ExpansionSource::Inject),
+        ];
+
+        let stats = calculate_stats(&entries);
+        assert_eq!(stats.expansions, 2);
+        assert_eq!(stats.usage, vec![(":a".to_owned(), 1)]);
+    }
+
+    #[test]
+    fn test_legacy_entry_defaults_to_typed() {
+        let entry : HistoryEntry = serde_json::from_str(
+            r#"{"trigger":":hi","config":"default","length":5,"timestamp":1,"backend":"Inject"}"#).unwrap();
+        assert_eq!(entry.source, ExpansionSource::Typed);
+    }
+
+    #[test]
+    fn test_find_unused_triggers() {
+        let entries = vec![
+            entry(":old", 5, 10),
+            entry(":recent", 5, 10),
+            entry(":recent", 5, 100),
+        ];
+
+        let unused = find_unused_triggers(&entries, &[":old", ":recent", ":never"], 50);
+        assert_eq!(unused, vec![":old", ":never"]);
+    }
+}
@@ -19,7 +19,7 @@
 
 use std::ffi::CString;
 use crate::bridge::linux::*;
-use super::PasteShortcut;
+use super::{PasteShortcut, CursorKey};
 use log::error;
 
 pub struct LinuxKeyboardManager {
@@ -76,9 +76,14 @@ impl super::KeyboardManager for LinuxKeyboardManager {
         unsafe {delete_string(count)}
     }
 
-    fn move_cursor_left(&self, count: i32) {
+    fn move_cursor(&self, key: CursorKey, count: i32) {
         unsafe {
-            left_arrow(count);
+            match key {
+                CursorKey::Left => left_arrow(count),
+                CursorKey::Right => right_arrow(count),
+                CursorKey::Up => up_arrow(count),
+                CursorKey::Down => down_arrow(count),
+            }
         }
     }
 
@@ -19,7 +19,7 @@
 
 use std::ffi::CString;
 use crate::bridge::macos::*;
-use super::PasteShortcut;
+use super::{PasteShortcut, CursorKey};
 use log::error;
 
 pub struct MacKeyboardManager {
@@ -66,10 +66,16 @@ impl super::KeyboardManager for MacKeyboardManager {
         unsafe {delete_string(count)}
     }
 
-    fn move_cursor_left(&self, count: i32) {
+    fn move_cursor(&self, key: CursorKey, count: i32) {
+        let vk = match key {
+            CursorKey::Left => 0x7B,
+            CursorKey::Right => 0x7C,
+            CursorKey::Down => 0x7D,
+            CursorKey::Up => 0x7E,
+        };
+
         unsafe {
-            // Simulate the Left arrow count times
-            send_multi_vkey(0x7B, count);
+            send_multi_vkey(vk, count);
         }
     }
 }
\ No newline at end of file
@@ -33,11 +33,95 @@ pub trait KeyboardManager {
     fn send_enter(&self);
     fn trigger_paste(&self, shortcut: &PasteShortcut);
     fn delete_string(&self, count: i32);
-    fn move_cursor_left(&self, count: i32);
+    fn move_cursor(&self, key: CursorKey, count: i32);
     fn trigger_copy(&self);
+
+    // Send the string in chunks, waiting between each one
+    fn send_string_throttled(&self, s: &str, options: &InjectOptions) {
+        if !options.is_throttled() {
+            self.send_string(s);
+            return;
+        }
+
+        for chunk in split_chunks(s, options.effective_chunk_size()) {
+            self.send_string(chunk);
+            options.wait();
+        }
+    }
+
+    // Send the backspaces in chunks, waiting between each one
+    fn delete_string_throttled(&self, count: i32, options: &InjectOptions) {
+        if !options.is_throttled() {
+            self.delete_string(count);
+            return;
+        }
+
+        let chunk_size = options.effective_chunk_size() as i32;
+        let mut remaining = count;
+        while remaining > 0 {
+            let chunk = remaining.min(chunk_size);
+            self.delete_string(chunk);
+            options.wait();
+            remaining -= chunk;
+        }
+    }
+}
+
+/// Used to slow down the injection for the applications, such as remote desktops,
+/// that drop the keystrokes sent too quickly.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InjectOptions {
+    pub chunk_size: usize,  // Chars sent at once, 0 to disable chunking
+    pub delay_ms: u64,      // Wait after each chunk
+}
+
+impl InjectOptions {
+    pub fn is_throttled(&self) -> bool {
+        self.chunk_size > 0 || self.delay_ms > 0
+    }
+
+    // If only the delay is specified, it is applied after each char
+    fn effective_chunk_size(&self) -> usize {
+        if self.chunk_size > 0 {
+            self.chunk_size
+        }else{
+            1
+        }
+    }
+
+    fn wait(&self) {
+        if self.delay_ms > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(self.delay_ms));
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Split the string in chunks with the given number of chars
+fn split_chunks(s: &str, chunk_size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for (count, (index, _)) in s.char_indices().enumerate() {
+        if count > 0 && count % chunk_size == 0 {
+            chunks.push(&s[start..index]);
+            start = index;
+        }
+    }
+    if start < s.len() {
+        chunks.push(&s[start..]);
+    }
+
+    chunks
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CursorKey {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum PasteShortcut {
     Default,        // Default one for the current system
     CtrlV,          // Classic Ctrl+V shortcut
@@ -68,4 +152,25 @@ pub fn get_manager() -> impl KeyboardManager {
 #[cfg(target_os = "macos")]
 pub fn get_manager() -> impl KeyboardManager {
     macos::MacKeyboardManager{}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_chunks() {
+        assert_eq!(split_chunks("abcdefg", 3), vec!["abc", "def", "g"]);
+        assert_eq!(split_chunks("abc", 3), vec!["abc"]);
+        assert_eq!(split_chunks("àèìòù", 2), vec!["àè", "ìò", "ù"]);
+        assert!(split_chunks("", 2).is_empty());
+    }
+
+    #[test]
+    fn test_inject_options_throttling() {
+        assert!(!InjectOptions::default().is_throttled());
+        assert!(InjectOptions { chunk_size: 0, delay_ms: 10 }.is_throttled());
+        assert_eq!(InjectOptions { chunk_size: 0, delay_ms: 10 }.effective_chunk_size(), 1);
+        assert_eq!(InjectOptions { chunk_size: 5, delay_ms: 0 }.effective_chunk_size(), 5);
+    }
+}
@@ -19,7 +19,7 @@
 
 use widestring::{U16CString};
 use crate::bridge::windows::*;
-use super::PasteShortcut;
+use super::{PasteShortcut, CursorKey};
 use log::error;
 
 pub struct WindowsKeyboardManager {
@@ -67,10 +67,16 @@ impl super::KeyboardManager for WindowsKeyboardManager {
         }
     }
 
-    fn move_cursor_left(&self, count: i32) {
+    fn move_cursor(&self, key: CursorKey, count: i32) {
+        let vk = match key {
+            CursorKey::Left => 0x25,
+            CursorKey::Up => 0x26,
+            CursorKey::Right => 0x27,
+            CursorKey::Down => 0x28,
+        };
+
         unsafe {
-            // Send the left arrow key multiple times
-            send_multi_vkey(0x25, count)
+            send_multi_vkey(vk, count)
         }
     }
 
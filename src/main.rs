@@ -24,12 +24,13 @@ use std::thread;
 use std::fs::{File, OpenOptions};
 use std::process::exit;
 use std::sync::mpsc;
-use std::sync::mpsc::Receiver;
+use std::sync::mpsc::{Receiver, Sender};
 use std::time::Duration;
+use std::path::Path;
 
 use clap::{App, Arg, SubCommand, ArgMatches};
 use fs2::FileExt;
-use log::{info, warn, LevelFilter};
+use log::{info, warn, error, LevelFilter};
 use simplelog::{CombinedLogger, SharedLogger, TerminalMode, TermLogger, WriteLogger};
 
 use crate::config::{ConfigSet, ConfigManager};
@@ -38,12 +39,14 @@ use crate::engine::Engine;
 use crate::event::*;
 use crate::event::manager::{DefaultEventManager, EventManager};
 use crate::matcher::scrolling::ScrollingMatcher;
-use crate::system::SystemManager;
+use crate::system::{SystemManager, StaticSystemManager};
+use crate::render::{RenderResult, RenderFragment};
 use crate::ui::UIManager;
 use crate::protocol::*;
 use std::io::{BufReader, BufRead};
 use crate::package::default::DefaultPackageManager;
 use crate::package::{PackageManager, InstallResult, UpdateResult, RemoveResult};
+use crate::history::default::DefaultHistoryManager;
 
 mod ui;
 mod event;
@@ -62,6 +65,11 @@ mod protocol;
 mod clipboard;
 mod extension;
 mod sysdaemon;
+mod history;
+mod tabstop;
+mod testing;
+mod debug;
+mod search;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const LOG_FILE: &str = "espanso.log";
@@ -85,6 +93,18 @@ fn main() {
             .short("v")
             .multiple(true)
             .help("Sets the level of verbosity"))
+        .arg(Arg::with_name("config-dir")
+            .long("config-dir")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("Use the given config directory, instead of the default one"))
+        .arg(Arg::with_name("data-dir")
+            .long("data-dir")
+            .takes_value(true)
+            .value_name("PATH")
+            .help("Use the given data directory, instead of the default one. \
+                   The IPC socket and lock file are placed here, so instances with different \
+                   data directories can run at the same time."))
         .subcommand(SubCommand::with_name("cmd")
             .about("Send a command to the espanso daemon.")
             .subcommand(SubCommand::with_name("exit")
@@ -95,9 +115,65 @@ fn main() {
                 .about("Disable the espanso replacement engine."))
             .subcommand(SubCommand::with_name("toggle")
                 .about("Toggle the status of the espanso replacement engine."))
+            .subcommand(SubCommand::with_name("search")
+                .about("Open the picker to search the matches and expand the chosen one."))
+            .subcommand(SubCommand::with_name("expand")
+                .about("Expand the match with the given trigger in the focused application.")
+                .arg(Arg::with_name("trigger")
+                    .help("Trigger of the match")
+                    .required(true))
+                .arg(Arg::with_name("arg")
+                    .help("Argument passed to the match, can be repeated")
+                    .long("arg")
+                    .takes_value(true)
+                    .multiple(true)
+                    .number_of_values(1)))
+            .subcommand(SubCommand::with_name("inject")
+                .about("Inject the given text in the focused application.")
+                .arg(Arg::with_name("text")
+                    .help("Text to inject")
+                    .required(true)))
         )
+        .subcommand(SubCommand::with_name("profile")
+            .about("Manage the espanso profiles, named subsets of configs and packages.")
+            .subcommand(SubCommand::with_name("switch")
+                .about("Activate the given profile in the running daemon.")
+                .arg(Arg::with_name("profile_name")
+                    .help("Profile name")
+                    .required(true)))
+            .subcommand(SubCommand::with_name("reset")
+                .about("Disable the active profile, enabling all configs again."))
+            .subcommand(SubCommand::with_name("list")
+                .about("List the profiles defined in the default configuration."))
+        )
+        .subcommand(SubCommand::with_name("render")
+            .about("Render a match without the daemon, printing the result. Useful to debug variables.")
+            .arg(Arg::with_name("trigger")
+                .help("Trigger of the match")
+                .required(true))
+            .arg(Arg::with_name("arg")
+                .help("Argument passed to the match, can be repeated")
+                .long("arg")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1))
+            .arg(Arg::with_name("app-title")
+                .help("Simulate an application with the given window title")
+                .long("app-title")
+                .takes_value(true))
+            .arg(Arg::with_name("app-class")
+                .help("Simulate an application with the given window class")
+                .long("app-class")
+                .takes_value(true))
+            .arg(Arg::with_name("app-exec")
+                .help("Simulate an application with the given executable")
+                .long("app-exec")
+                .takes_value(true)))
         .subcommand(SubCommand::with_name("dump")
-            .about("Prints all current configuration options."))
+            .about("Prints all current configuration options.")
+            .arg(Arg::with_name("matches")
+                .long("matches")
+                .help("Only list the matches of each config, with their labels, descriptions and tags")))
         .subcommand(SubCommand::with_name("detect")
             .about("Tool to detect current window properties, to simplify filters creation."))
         .subcommand(SubCommand::with_name("daemon")
@@ -115,7 +191,40 @@ fn main() {
         .subcommand(SubCommand::with_name("restart")
             .about("Restart the espanso daemon."))
         .subcommand(SubCommand::with_name("status")
-            .about("Check if the espanso daemon is running or not."))
+            .about("Check if the espanso daemon is running and print its status."))
+        .subcommand(SubCommand::with_name("stats")
+            .about("Print statistics about the recorded expansions.")
+            .arg(Arg::with_name("top")
+                .long("top")
+                .takes_value(true)
+                .default_value("10")
+                .help("Number of most used snippets to show"))
+            .arg(Arg::with_name("unused-days")
+                .long("unused-days")
+                .takes_value(true)
+                .default_value("90")
+                .help("List the snippets not used in the given number of days")))
+        .subcommand(SubCommand::with_name("debug")
+            .about("Tools to reproduce unexpected expansions.")
+            .subcommand(SubCommand::with_name("record")
+                .about("Record the keyboard events to a trace file, along with the active window.")
+                .arg(Arg::with_name("file")
+                    .help("Path of the trace file")
+                    .required(true))
+                .arg(Arg::with_name("redact")
+                    .long("redact")
+                    .help("Hide the typed text, except the word separators, and the window titles")))
+            .subcommand(SubCommand::with_name("replay")
+                .about("Replay a trace file, printing the actions espanso would perform.")
+                .arg(Arg::with_name("file")
+                    .help("Path of the trace file")
+                    .required(true))
+                .arg(Arg::with_name("fast")
+                    .long("fast")
+                    .help("Ignore the original timing, the behaviors depending on it may differ")))
+        )
+        .subcommand(SubCommand::with_name("subscribe")
+            .about("Print the events of the running daemon as JSON lines, until it terminates."))
         .subcommand(SubCommand::with_name("path")
             .about("Prints all the current espanso directory paths, to easily locate configuration and data paths.")
             .subcommand(SubCommand::with_name("config")
@@ -148,6 +257,14 @@ fn main() {
 
     let log_level = matches.occurrences_of("v") as i32;
 
+    // Directory overrides must be set before loading the configuration
+    if let Some(config_dir) = matches.value_of("config-dir") {
+        context::set_config_dir_override(Path::new(config_dir));
+    }
+    if let Some(data_dir) = matches.value_of("data-dir") {
+        context::set_data_dir_override(Path::new(data_dir));
+    }
+
     // Load the configuration
     let mut config_set = ConfigSet::load_default().unwrap_or_else(|e| {
         println!("{}", e);
@@ -163,8 +280,22 @@ fn main() {
         return;
     }
 
-    if matches.subcommand_matches("dump").is_some() {
-        println!("{:#?}", config_set);
+    if let Some(matches) = matches.subcommand_matches("profile") {
+        profile_main(config_set, matches);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("render") {
+        render_main(config_set, matches);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("dump") {
+        if matches.is_present("matches") {
+            dump_matches_main(config_set);
+        }else{
+            println!("{:#?}", config_set);
+        }
         return;
     }
 
@@ -199,7 +330,22 @@ fn main() {
     }
 
     if matches.subcommand_matches("status").is_some() {
-        status_main();
+        status_main(config_set);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("stats") {
+        stats_main(config_set, matches);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("debug") {
+        debug_main(config_set, matches);
+        return;
+    }
+
+    if matches.subcommand_matches("subscribe").is_some() {
+        subscribe_main(config_set);
         return;
     }
 
@@ -306,24 +452,25 @@ fn daemon_main(config_set: ConfigSet) {
 
     let (send_channel, receive_channel) = mpsc::channel();
 
+    // Used to forward the daemon events to the IPC subscribers
+    let (ipc_event_sender, ipc_event_receiver) = mpsc::channel();
+
     let context = context::new(send_channel.clone());
 
     let config_set_copy = config_set.clone();
     thread::Builder::new().name("daemon_background".to_string()).spawn(move || {
-        daemon_background(receive_channel, config_set_copy);
+        daemon_background(receive_channel, config_set_copy, ipc_event_sender);
     }).expect("Unable to spawn daemon background thread");
 
-    let ipc_server = protocol::get_ipc_server(config_set, send_channel.clone());
+    let ipc_server = protocol::get_ipc_server(config_set, send_channel.clone(), ipc_event_receiver);
     ipc_server.start();
 
     context.eventloop();
 }
 
 /// Background thread worker for the daemon
-fn daemon_background(receive_channel: Receiver<Event>, config_set: ConfigSet) {
-    let system_manager = system::get_manager();
-    let config_manager = RuntimeConfigManager::new(config_set, system_manager);
-
+fn daemon_background(receive_channel: Receiver<Event>, mut config_set: ConfigSet,
+                     ipc_event_sender: Sender<IPCEvent>) {
     let ui_manager = ui::get_uimanager();
     ui_manager.notify("espanso is running!");
 
@@ -331,29 +478,69 @@ fn daemon_background(receive_channel: Receiver<Event>, config_set: ConfigSet) {
 
     let keyboard_manager = keyboard::get_manager();
 
-    let extensions = extension::get_extensions();
+    info!("espanso is running!");
 
-    let renderer = render::default::DefaultRenderer::new(extensions,
-                                                          config_manager.default_config().clone());
+    // Preserved across the configuration reloads
+    let mut enabled = true;
+    let mut active_profile = None;
 
-    let engine = Engine::new(&keyboard_manager,
-                             &clipboard_manager,
-                             &config_manager,
-                             &ui_manager,
-                             &renderer,
-    );
+    loop {
+        let system_manager = system::get_manager();
+        let config_manager = RuntimeConfigManager::new(config_set.clone(), system_manager);
+        if active_profile.is_some() {
+            config_manager.set_active_profile(active_profile.clone());
+        }
 
-    let matcher = ScrollingMatcher::new(&config_manager, &engine);
+        let extensions = extension::get_extensions();
 
-    let event_manager = DefaultEventManager::new(
-        receive_channel,
-        vec!(&matcher),
-        vec!(&engine, &matcher),
-    );
+        let renderer = render::default::DefaultRenderer::new(extensions,
+                                                              config_manager.default_config().clone());
 
-    info!("espanso is running!");
+        let history_manager = DefaultHistoryManager::new_default(config_manager.default_config().enable_history);
+
+        let engine = Engine::new(&keyboard_manager,
+                                 &clipboard_manager,
+                                 &config_manager,
+                                 &ui_manager,
+                                 &renderer,
+                                 &history_manager,
+                                 ipc_event_sender.clone(),
+        );
+        engine.restore_enabled(enabled);
+
+        let matcher = ScrollingMatcher::new(&config_manager, &engine);
+        matcher.restore_enabled(enabled);
+
+        let event_manager = DefaultEventManager::new(
+            &receive_channel,
+            vec!(&matcher, &engine),
+            vec!(&engine, &matcher),
+            vec!(&engine, &matcher),
+        );
 
-    event_manager.eventloop();
+        let match_count = config_manager.default_config().matches.len();
+        ipc_event_sender.send(IPCEvent::new(IPCEventKind::ConfigReloaded { match_count }))
+            .unwrap_or_default();
+
+        // Returns when a configuration reload is requested
+        event_manager.eventloop();
+
+        enabled = matcher.is_enabled();
+        active_profile = config_manager.active_profile();
+
+        match ConfigSet::load_default() {
+            Ok(mut new_config_set) => {
+                // Keep the verbosity requested from the command line
+                new_config_set.default.log_level = config_set.default.log_level;
+                config_set = new_config_set;
+            },
+            Err(e) => {
+                error!("Unable to reload the configuration, keeping the previous one: {}", e);
+                ipc_event_sender.send(IPCEvent::new(IPCEventKind::Error { message: e.to_string() }))
+                    .unwrap_or_default();
+            },
+        }
+    }
 }
 
 /// start subcommand, spawn a background espanso process.
@@ -383,7 +570,8 @@ fn start_daemon(_: ConfigSet) {
 
 #[cfg(target_os = "macos")]
 fn start_daemon(config_set: ConfigSet) {
-    if config_set.default.use_system_agent {
+    // The launchd agent is not aware of the directory overrides
+    if config_set.default.use_system_agent && !context::has_dir_overrides() {
         use std::process::Command;
 
         let res = Command::new("launchctl")
@@ -423,7 +611,8 @@ fn start_daemon(config_set: ConfigSet) {
         false
     };
 
-    if config_set.default.use_system_agent && !force_unmanaged {
+    // The systemd service is not aware of the directory overrides
+    if config_set.default.use_system_agent && !force_unmanaged && !context::has_dir_overrides() {
         // Make sure espanso is currently registered in systemd
         let res = Command::new("systemctl")
             .args(&["--user", "is-enabled", "espanso.service"])
@@ -504,14 +693,121 @@ fn fork_daemon(config_set: ConfigSet) {
 }
 
 /// status subcommand, print the current espanso status
-fn status_main() {
+fn status_main(config_set: ConfigSet) {
     let lock_file = acquire_lock();
     if let Some(lock_file) = lock_file {
         println!("espanso is not running");
 
         release_lock(lock_file);
-    }else{
-        println!("espanso is running");
+        return;
+    }
+
+    println!("espanso is running");
+
+    let ipc_client = protocol::get_ipc_client(config_set);
+    let res = ipc_client.send_command(IPCCommand {
+        id: "status".to_owned(),
+        payload: "".to_owned(),
+        ..Default::default()
+    });
+
+    match res {
+        Ok(response) => {
+            if response.daemon_version != VERSION {
+                println!("Warning: the daemon is running espanso v{}, while this client is v{}",
+                         response.daemon_version, VERSION);
+                println!("Restart espanso to use the new version:  espanso restart");
+            }
+
+            if let Some(status) = response.status {
+                println!("Version: {}", response.daemon_version);
+                println!("Status: {}", if status.enabled { "enabled" } else { "disabled" });
+                println!("Active config: {}", status.active_config);
+                println!("Active profile: {}", status.active_profile.unwrap_or_else(|| "none".to_owned()));
+                println!("Loaded matches: {}", status.match_count);
+                println!("Uptime: {}s", status.uptime);
+            }
+        },
+        Err(e) => {
+            println!("Unable to query the daemon status: {}", e);
+        },
+    }
+}
+
+/// stats subcommand, print the most used snippets and the ones that are never used
+fn stats_main(config_set: ConfigSet, matches: &ArgMatches) {
+    let top: usize = matches.value_of("top").unwrap_or_default().parse().unwrap_or_else(|_| {
+        eprintln!("Invalid --top value, expected a number");
+        exit(1);
+    });
+    let unused_days: i64 = matches.value_of("unused-days").unwrap_or_default().parse().unwrap_or_else(|_| {
+        eprintln!("Invalid --unused-days value, expected a number");
+        exit(1);
+    });
+
+    if !config_set.default.enable_history {
+        println!("Warning: the expansion history is disabled, add 'enable_history: true' to the default config to record it");
+        println!();
+    }
+
+    let entries = history::load_history(&DefaultHistoryManager::default_history_file());
+    let stats = history::calculate_stats(&entries);
+
+    println!("Expansions: {}", stats.expansions);
+    println!("Characters saved: {}", stats.chars_saved);
+    println!();
+
+    println!("Most used snippets:");
+    for (trigger, count) in stats.usage.iter().take(top) {
+        println!("{:>8}  {}", count, trigger);
+    }
+    println!();
+
+    // Consider all the loaded matches, deduplicating triggers defined in multiple configs
+    let mut triggers: Vec<&str> = Vec::new();
+    let configs = std::iter::once(&config_set.default).chain(config_set.specific.iter());
+    for config in configs {
+        for m in config.matches.iter() {
+            // Hotkey-only and disabled matches can't be typed
+            if m.trigger.is_empty() || !m.enabled {
+                continue;
+            }
+
+            if !triggers.contains(&m.trigger.as_str()) {
+                triggers.push(&m.trigger);
+            }
+        }
+    }
+
+    let since = chrono::Utc::now().timestamp() - unused_days * 24 * 60 * 60;
+    let unused = history::find_unused_triggers(&entries, &triggers, since);
+
+    println!("Snippets not used in the last {} days: {}", unused_days, unused.len());
+    for trigger in unused.iter() {
+        println!("  {}", trigger);
+    }
+}
+
+/// subscribe subcommand, print the daemon events as they happen
+fn subscribe_main(config_set: ConfigSet) {
+    let lock_file = acquire_lock();
+    if let Some(lock_file) = lock_file {
+        println!("espanso is not running");
+
+        release_lock(lock_file);
+        exit(1);
+    }
+
+    let ipc_client = protocol::get_ipc_client(config_set);
+    let res = ipc_client.subscribe(&|event| {
+        if let Ok(json) = serde_json::to_string(&event) {
+            println!("{}", json);
+        }
+    });
+
+    if let Err(e) = res {
+        println!("Unable to subscribe to the daemon events: {}", e);
+        exit(1);
     }
 }
 
@@ -529,6 +825,7 @@ fn stop_main(config_set: ConfigSet) {
     let res = send_command(config_set, IPCCommand{
         id: "exit".to_owned(),
         payload: "".to_owned(),
+        ..Default::default()
     });
 
     if let Err(e) = res {
@@ -539,6 +836,39 @@ fn stop_main(config_set: ConfigSet) {
     }
 }
 
+/// List the matches of each config, with their metadata
+fn dump_matches_main(config_set: ConfigSet) {
+    let configs = std::iter::once(&config_set.default).chain(config_set.specific.iter());
+    for config in configs {
+        println!("{}:", config.name);
+
+        for m in config.matches.iter() {
+            let mut line = match &m.hotkey {
+                Some(hotkey) if m.trigger.is_empty() => format!("  {}", hotkey),
+                Some(hotkey) => format!("  {} ({})", m.trigger, hotkey),
+                None => format!("  {}", m.trigger),
+            };
+            if let Some(label) = &m.label {
+                line.push_str(&format!("  {}", label));
+            }
+            for tag in m.tags.iter() {
+                line.push_str(&format!("  #{}", tag));
+            }
+            if !m.enabled {
+                line.push_str("  (disabled)");
+            }
+            println!("{}", line);
+
+            if let Some(description) = &m.description {
+                for description_line in description.lines() {
+                    println!("      {}", description_line);
+                }
+            }
+        }
+        println!();
+    }
+}
+
 /// Kill the daemon if running and start it again
 fn restart_main(config_set: ConfigSet) {
     // Kill the daemon if running
@@ -548,6 +878,7 @@ fn restart_main(config_set: ConfigSet) {
         send_command(config_set.clone(), IPCCommand{
             id: "exit".to_owned(),
             payload: "".to_owned(),
+            ..Default::default()
         }).unwrap_or_else(|e| warn!("Unable to send IPC command to daemon: {}", e));
     }else{
         release_lock(lock_file.unwrap());
@@ -640,27 +971,99 @@ fn detect_main() {
     }
 }
 
+/// Render the given match offline, selecting the config as the daemon would
+/// for an application with the specified window properties.
+fn render_main(config_set: ConfigSet, matches: &ArgMatches) {
+    let trigger = matches.value_of("trigger").unwrap_or_default();
+    let args : Vec<String> = matches.values_of("arg")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+
+    let window = StaticSystemManager::new(
+        matches.value_of("app-title").map(String::from),
+        matches.value_of("app-class").map(String::from),
+        matches.value_of("app-exec").map(String::from),
+    );
+
+    let (_, result) = render::preview::render_trigger(config_set, trigger, args, window)
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            exit(2);
+        });
+
+    match result {
+        RenderResult::Text(text) => {
+            println!("{}", text);
+        },
+        RenderResult::Image(image_path) => {
+            println!("Image: {}", image_path.to_string_lossy());
+        },
+        RenderResult::Html { html, .. } => {
+            println!("{}", html);
+        },
+        RenderResult::Sequence(fragments) => {
+            for fragment in fragments {
+                match fragment {
+                    RenderFragment::Text(text) => print!("{}", text),
+                    RenderFragment::Image(image_path) => print!("[Image: {}]", image_path.to_string_lossy()),
+                }
+            }
+            println!();
+        },
+        RenderResult::Error => {
+            eprintln!("Could not render match: {}", trigger);
+            exit(1);
+        },
+    }
+}
+
 /// Send the given command to the espanso daemon
 fn cmd_main(config_set: ConfigSet, matches: &ArgMatches) {
     let command = if matches.subcommand_matches("exit").is_some() {
         Some(IPCCommand {
             id: String::from("exit"),
             payload: String::from(""),
+            ..Default::default()
         })
     }else if matches.subcommand_matches("toggle").is_some() {
         Some(IPCCommand {
             id: String::from("toggle"),
             payload: String::from(""),
+            ..Default::default()
         })
     }else if matches.subcommand_matches("enable").is_some() {
         Some(IPCCommand {
             id: String::from("enable"),
             payload: String::from(""),
+            ..Default::default()
         })
     }else if matches.subcommand_matches("disable").is_some() {
         Some(IPCCommand {
             id: String::from("disable"),
             payload: String::from(""),
+            ..Default::default()
+        })
+    }else if matches.subcommand_matches("search").is_some() {
+        Some(IPCCommand {
+            id: String::from("search"),
+            payload: String::from(""),
+            ..Default::default()
+        })
+    }else if let Some(matches) = matches.subcommand_matches("expand") {
+        let args = matches.values_of("arg")
+            .map(|values| values.map(String::from).collect())
+            .unwrap_or_default();
+
+        Some(IPCCommand {
+            id: String::from("expand"),
+            payload: matches.value_of("trigger").unwrap_or_default().to_owned(),
+            args,
+        })
+    }else if let Some(matches) = matches.subcommand_matches("inject") {
+        Some(IPCCommand {
+            id: String::from("inject"),
+            payload: matches.value_of("text").unwrap_or_default().to_owned(),
+            ..Default::default()
         })
     }else{
         None
@@ -679,9 +1082,50 @@ fn cmd_main(config_set: ConfigSet, matches: &ArgMatches) {
     exit(1);
 }
 
+/// Manage the profiles, switching them in the running daemon
+fn profile_main(config_set: ConfigSet, matches: &ArgMatches) {
+    if matches.subcommand_matches("list").is_some() {
+        let mut names: Vec<&String> = config_set.default.profiles.keys().collect();
+        names.sort();
+
+        for name in names {
+            println!("{} - {}", name, config_set.default.profiles[name].join(", "));
+        }
+
+        exit(0);
+    }
+
+    let profile_name = if let Some(matches) = matches.subcommand_matches("switch") {
+        let profile_name = matches.value_of("profile_name").unwrap_or_default();
+        if !config_set.default.profiles.contains_key(profile_name) {
+            eprintln!("Profile '{}' not found, define it in the 'profiles' section of the default config.", profile_name);
+            exit(2);
+        }
+        profile_name.to_owned()
+    }else if matches.subcommand_matches("reset").is_some() {
+        "".to_owned()
+    }else{
+        eprintln!("Missing profile subcommand, use 'switch', 'reset' or 'list'.");
+        exit(1);
+    };
+
+    let res = send_command(config_set, IPCCommand {
+        id: String::from("profile"),
+        payload: profile_name,
+        ..Default::default()
+    });
+
+    if let Err(e) = res {
+        println!("{}", e);
+        exit(1);
+    }
+
+    exit(0);
+}
+
 fn send_command(config_set: ConfigSet, command: IPCCommand) -> Result<(), String> {
     let ipc_client = protocol::get_ipc_client(config_set);
-    ipc_client.send_command(command)
+    ipc_client.send_command(command).map(|_| ())
 }
 
 fn log_main() {
@@ -852,6 +1296,19 @@ fn list_package_main(_config_set: ConfigSet, matches: &ArgMatches) {
     }
 }
 
+fn debug_main(config_set: ConfigSet, matches: &ArgMatches) {
+    if let Some(matches) = matches.subcommand_matches("record") {
+        let path = Path::new(matches.value_of("file").unwrap());
+        debug::record(config_set, path, matches.is_present("redact"));
+    }else if let Some(matches) = matches.subcommand_matches("replay") {
+        let path = Path::new(matches.value_of("file").unwrap());
+        debug::replay(config_set, path, !matches.is_present("fast"));
+    }else{
+        println!("Invalid debug command, run 'espanso debug --help' for the available ones.");
+        exit(1);
+    }
+}
+
 fn path_main(_config_set: ConfigSet, matches: &ArgMatches) {
     let config = crate::context::get_config_dir();
     let packages = crate::context::get_package_dir();
@@ -0,0 +1,164 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use std::fmt;
+use crate::event::{KeyModifier, ActionType};
+use super::{Hotkey, parse_key_modifier};
+
+/// Key gestures that can be bound to an action, written in the configs as
+/// `double alt`, `triple ctrl`, `hold shift` or a chord such as `ctrl+shift+space`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gesture {
+    // The modifier pressed the given number of times, each within toggle_interval
+    Press { modifier: KeyModifier, count: usize },
+
+    // The modifier held alone for at least hold_interval, then released
+    Hold(KeyModifier),
+
+    // A key typed while holding the modifiers, as for the match hotkeys
+    Chord(Hotkey),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GestureBinding {
+    pub gesture: Gesture,
+    pub action: ActionType,
+}
+
+impl Gesture {
+    pub fn parse(gesture: &str) -> Result<Gesture, String> {
+        let tokens: Vec<String> = gesture.split_whitespace()
+            .map(|token| token.to_lowercase())
+            .collect();
+
+        match tokens.as_slice() {
+            [kind, modifier] => {
+                let modifier = match parse_key_modifier(modifier) {
+                    Some(modifier) => modifier,
+                    None => return Err(format!("unknown modifier '{}'", modifier)),
+                };
+
+                match kind.as_str() {
+                    "double" => Ok(Gesture::Press { modifier, count: 2 }),
+                    "triple" => Ok(Gesture::Press { modifier, count: 3 }),
+                    "hold" => Ok(Gesture::Hold(modifier)),
+                    _ => Err(format!("unknown gesture '{}', expected one of double, triple or hold", kind)),
+                }
+            },
+            [chord] if chord.contains('+') => {
+                Ok(Gesture::Chord(Hotkey::parse(chord)?))
+            },
+            _ => Err(format!("invalid gesture '{}'", gesture)),
+        }
+    }
+}
+
+impl fmt::Display for Gesture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Gesture::Press { modifier, count } => {
+                let kind = if *count == 3 { "triple" } else { "double" };
+                write!(f, "{} {}", kind, Hotkey::modifier_name(modifier))
+            },
+            Gesture::Hold(modifier) => write!(f, "hold {}", Hotkey::modifier_name(modifier)),
+            Gesture::Chord(hotkey) => write!(f, "{}", hotkey),
+        }
+    }
+}
+
+impl Serialize for Gesture {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where
+        S: Serializer {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl <'de> Deserialize<'de> for Gesture {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where
+        D: Deserializer<'de> {
+
+        let gesture = String::deserialize(deserializer)?;
+        Gesture::parse(&gesture).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gesture_parse_press() {
+        assert_eq!(Gesture::parse("double ALT").unwrap(), Gesture::Press { modifier: KeyModifier::ALT, count: 2 });
+        assert_eq!(Gesture::parse("triple ctrl").unwrap(), Gesture::Press { modifier: KeyModifier::CTRL, count: 3 });
+    }
+
+    #[test]
+    fn test_gesture_parse_hold() {
+        assert_eq!(Gesture::parse("hold shift").unwrap(), Gesture::Hold(KeyModifier::SHIFT));
+    }
+
+    #[test]
+    fn test_gesture_parse_chord() {
+        match Gesture::parse("ctrl+shift+space").unwrap() {
+            Gesture::Chord(hotkey) => {
+                assert_eq!(hotkey.modifiers, vec![KeyModifier::CTRL, KeyModifier::SHIFT]);
+                assert_eq!(hotkey.key, ' ');
+            },
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_gesture_parse_invalid() {
+        assert!(Gesture::parse("").is_err());
+        assert!(Gesture::parse("quadruple alt").is_err());
+        assert!(Gesture::parse("double hyper").is_err());
+        assert!(Gesture::parse("alt").is_err());
+        assert!(Gesture::parse("shift+e").is_err());
+    }
+
+    #[test]
+    fn test_gesture_display_roundtrip() {
+        for gesture in ["double alt", "triple meta", "hold ctrl", "ctrl+shift+space", "alt+e"].iter() {
+            assert_eq!(Gesture::parse(gesture).unwrap().to_string(), *gesture);
+        }
+    }
+
+    #[test]
+    fn test_gesture_binding_deserialize() {
+        let binding: GestureBinding = serde_yaml::from_str(r###"
+        gesture: "double ctrl"
+        action: reload_config
+        "###).unwrap();
+
+        assert_eq!(binding.gesture, Gesture::Press { modifier: KeyModifier::CTRL, count: 2 });
+        assert_eq!(binding.action, ActionType::ReloadConfig);
+    }
+
+    #[test]
+    fn test_gesture_binding_invalid_gesture() {
+        let binding: Result<GestureBinding, _> = serde_yaml::from_str(r###"
+        gesture: "double hyper"
+        action: toggle
+        "###);
+
+        assert!(binding.is_err());
+    }
+}
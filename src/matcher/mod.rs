@@ -17,15 +17,20 @@
  * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use serde::{Serialize, Deserialize, Deserializer};
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::ser::SerializeMap;
 use crate::event::{KeyEvent, KeyModifier};
-use crate::event::KeyEventReceiver;
+use crate::event::{KeyEventReceiver, ActionType};
 use serde_yaml::Mapping;
 use regex::Regex;
 use std::path::PathBuf;
 use std::fs;
+use std::fmt;
+use log::warn;
+use crate::config::BackendType;
 
 pub(crate) mod scrolling;
+pub(crate) mod gesture;
 
 #[derive(Debug, Serialize, Clone)]
 pub struct Match {
@@ -34,15 +39,215 @@ pub struct Match {
     pub word: bool,
     pub passive_only: bool,
 
+    // Overrides the backend of the config, if specified
+    pub backend: Option<BackendType>,
+
+    // Key combination that expands the match without typing the trigger
+    pub hotkey: Option<Hotkey>,
+
+    // Documentation of the match, shown by the search picker and by espanso dump
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+
+    // Disabled matches are never expanded. A disabled match without content
+    // turns off the one with the same trigger declared by its child configs.
+    pub enabled: bool,
+
     // Automatically calculated from the trigger, used by the matcher to check for correspondences.
     #[serde(skip_serializing)]
     pub _trigger_sequence: Vec<TriggerEntry>,
+
+    // Compiled from the filter_* fields, used to restrict the match to specific applications.
+    // Serialized back as the original filter_* fields.
+    #[serde(flatten)]
+    pub _filter: MatchFilter,
+
+    // Name of the config and package the match was declared in, used by profiles.
+    // Set when loading the ConfigSet.
+    #[serde(skip_serializing)]
+    pub _config: String,
+    #[serde(skip_serializing)]
+    pub _package: Option<String>,
+}
+
+impl Match {
+    /// Identifies the match when merging configs, hotkey-only matches have no trigger.
+    pub fn merge_key(&self) -> String {
+        match &self.hotkey {
+            Some(hotkey) if self.trigger.is_empty() => {
+                let modifiers: Vec<String> = hotkey.modifiers.iter()
+                    .map(|m| format!("{:?}", m))
+                    .collect();
+                format!("{}+{}", modifiers.join("+"), hotkey.key)
+            },
+            _ => self.trigger.clone(),
+        }
+    }
+
+    /// Name shown to the user, hotkey-only matches use their label or hotkey.
+    pub fn display_name(&self) -> String {
+        match &self.label {
+            Some(label) if self.trigger.is_empty() => label.clone(),
+            _ => self.merge_key(),
+        }
+    }
+}
+
+/// App filters restricting a single match to some windows, evaluated by the
+/// ConfigManager against the active window. Invalid regexes are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct MatchFilter {
+    pub title: Option<Regex>,
+    pub class: Option<Regex>,
+    pub exec: Option<Regex>,
+    pub exclude_title: Option<Regex>,
+    pub exclude_class: Option<Regex>,
+    pub exclude_exec: Option<Regex>,
+}
+
+impl MatchFilter {
+    fn compile(trigger: &str, field_name: &str, filter: &str) -> Option<Regex> {
+        if filter.is_empty() {
+            return None;
+        }
+
+        let res = Regex::new(filter);
+        if let Ok(regex) = res {
+            Some(regex)
+        }else{
+            warn!("Invalid regex in '{}' field of match {}, ignoring it...", field_name, trigger);
+            None
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.class.is_none() && self.exec.is_none() &&
+            self.exclude_title.is_none() && self.exclude_class.is_none() && self.exclude_exec.is_none()
+    }
+}
+
+impl Serialize for MatchFilter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where
+        S: Serializer {
+        let fields = [
+            ("filter_title", &self.title),
+            ("filter_class", &self.class),
+            ("filter_exec", &self.exec),
+            ("filter_exclude_title", &self.exclude_title),
+            ("filter_exclude_class", &self.exclude_class),
+            ("filter_exclude_exec", &self.exclude_exec),
+        ];
+
+        let mut map = serializer.serialize_map(None)?;
+        for (name, regex) in fields.iter() {
+            if let Some(regex) = regex {
+                map.serialize_entry(name, regex.as_str())?;
+            }
+        }
+        map.end()
+    }
+}
+
+/// Parse the name of a modifier, as used in hotkeys and gestures.
+pub fn parse_key_modifier(name: &str) -> Option<KeyModifier> {
+    match name.to_lowercase().as_str() {
+        "ctrl" | "control" => Some(KeyModifier::CTRL),
+        "alt" | "option" => Some(KeyModifier::ALT),
+        "shift" => Some(KeyModifier::SHIFT),
+        "meta" | "cmd" | "super" | "win" => Some(KeyModifier::META),
+        _ => None,
+    }
+}
+
+/// A key combination such as `ctrl+alt+e`, made of one or more modifiers and a single key.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Hotkey {
+    pub modifiers: Vec<KeyModifier>,
+    pub key: char,
+}
+
+impl Hotkey {
+    pub fn parse(hotkey: &str) -> Result<Hotkey, String> {
+        let tokens: Vec<String> = hotkey.split('+')
+            .map(|token| token.trim().to_lowercase())
+            .collect();
+
+        let (key, modifier_tokens) = match tokens.split_last() {
+            Some((key, modifier_tokens)) => (key, modifier_tokens),
+            None => return Err("empty hotkey".to_owned()),
+        };
+
+        let mut key_chars = key.chars();
+        let key = match (key_chars.next(), key_chars.next()) {
+            (Some(c), None) => c,
+            _ if key == "space" => ' ',
+            _ => return Err(format!("'{}' is not a single key", key)),
+        };
+
+        let mut modifiers = Vec::new();
+        for token in modifier_tokens.iter() {
+            let modifier = match parse_key_modifier(token) {
+                Some(modifier) => modifier,
+                None => return Err(format!("unknown modifier '{}'", token)),
+            };
+
+            if !modifiers.contains(&modifier) {
+                modifiers.push(modifier);
+            }
+        }
+
+        // Shift alone would fire every time a capital letter is typed
+        let has_command_modifier = modifiers.iter().any(|m| {
+            *m == KeyModifier::CTRL || *m == KeyModifier::ALT || *m == KeyModifier::META
+        });
+        if !has_command_modifier {
+            return Err("a hotkey needs at least one of ctrl, alt or meta".to_owned());
+        }
+
+        Ok(Hotkey {
+            modifiers,
+            key,
+        })
+    }
+
+    fn modifier_name(modifier: &KeyModifier) -> String {
+        format!("{:?}", modifier).to_lowercase()
+    }
+
+    /// Check if the hotkey corresponds to the given key, typed while exactly
+    /// the given modifiers were held.
+    pub fn is_triggered_by(&self, modifiers: &[KeyModifier], c: &str) -> bool {
+        if self.modifiers.len() != modifiers.len() ||
+            !self.modifiers.iter().all(|m| modifiers.contains(m)) {
+            return false;
+        }
+
+        let mut chars = c.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => {
+                // Holding ctrl turns letters into control codes on some platforms. Without
+                // ctrl they are regular keys, such as enter and tab.
+                let is_ctrl_held = modifiers.contains(&KeyModifier::CTRL);
+                let c = if is_ctrl_held && c >= '\u{1}' && c <= '\u{1a}' {
+                    (b'a' + (c as u8) - 1) as char
+                }else{
+                    c
+                };
+
+                c.to_lowercase().eq(self.key.to_lowercase())
+            },
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub enum MatchContentType {
     Text(TextContent),
     Image(ImageContent),
+    Html(TextContent),      // Pasted as rich text
+    Markdown(TextContent),  // Converted to HTML and pasted as rich text
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -76,29 +281,51 @@ impl<'a> From<&'a AutoMatch> for Match{
 
         // TODO: may need to replace windows newline (\r\n) with newline only (\n)
 
-        // Calculate the trigger sequence
+        let hotkey = match &other.hotkey {
+            Some(hotkey) => {
+                match Hotkey::parse(hotkey) {
+                    Ok(hotkey) => Some(hotkey),
+                    Err(e) => {
+                        warn!("Invalid hotkey '{}' in match {}: {}, ignoring it...", hotkey, other.trigger, e);
+                        None
+                    },
+                }
+            },
+            None => None,
+        };
+
+        if other.trigger.is_empty() && hotkey.is_none() {
+            eprintln!("ERROR: no trigger specified for match, please specify one of 'trigger' or 'hotkey'");
+            std::process::exit(2);
+        }
+
+        // Calculate the trigger sequence, empty for hotkey-only matches
         let mut trigger_sequence = Vec::new();
         let trigger_chars : Vec<char> = other.trigger.chars().collect();
         trigger_sequence.extend(trigger_chars.into_iter().map(|c| {
             TriggerEntry::Char(c)
         }));
-        if other.word {  // If it's a word match, end with a word separator
+        if other.word && !trigger_sequence.is_empty() {  // If it's a word match, end with a word separator
             trigger_sequence.push(TriggerEntry::WordSeparator);
         }
 
-        let content = if let Some(replace) = &other.replace {  // Text match
-            let new_replace = replace.clone();
-
+        let text_content = |replace: &String| {
             // Check if the match contains variables
             let has_vars = VAR_REGEX.is_match(replace);
 
-            let content = TextContent {
-                replace: new_replace,
+            TextContent {
+                replace: replace.clone(),
                 vars: other.vars.clone(),
                 _has_vars: has_vars,
-            };
+            }
+        };
 
-            MatchContentType::Text(content)
+        let content = if let Some(replace) = &other.replace {  // Text match
+            MatchContentType::Text(text_content(replace))
+        }else if let Some(html) = &other.html {  // Rich text matches
+            MatchContentType::Html(text_content(html))
+        }else if let Some(markdown) = &other.markdown {
+            MatchContentType::Markdown(text_content(markdown))
         }else if let Some(image_path) = &other.image_path {  // Image match
             // On Windows, we have to replace the forward / with the backslash \ in the path
             let new_path = if cfg!(target_os = "windows") {
@@ -126,17 +353,50 @@ impl<'a> From<&'a AutoMatch> for Match{
             };
 
             MatchContentType::Image(content)
+        }else if !other.enabled {  // Only used to disable the match with the same trigger
+            MatchContentType::Text(text_content(&String::new()))
         }else {
-            eprintln!("ERROR: no action specified for match {}, please specify either 'replace' or 'image_path'", other.trigger);
+            eprintln!("ERROR: no action specified for match {}, please specify one of 'replace', 'html', 'markdown' or 'image_path'", other.trigger);
             std::process::exit(2);
         };
 
+        let filter = MatchFilter {
+            title: MatchFilter::compile(&other.trigger, "filter_title", &other.filter_title),
+            class: MatchFilter::compile(&other.trigger, "filter_class", &other.filter_class),
+            exec: MatchFilter::compile(&other.trigger, "filter_exec", &other.filter_exec),
+            exclude_title: MatchFilter::compile(&other.trigger, "filter_exclude_title", &other.filter_exclude_title),
+            exclude_class: MatchFilter::compile(&other.trigger, "filter_exclude_class", &other.filter_exclude_class),
+            exclude_exec: MatchFilter::compile(&other.trigger, "filter_exclude_exec", &other.filter_exclude_exec),
+        };
+
         Self {
             trigger: other.trigger.clone(),
             content,
             word: other.word,
             passive_only: other.passive_only,
+            backend: other.backend.clone(),
+            hotkey,
+            label: other.label.clone(),
+            description: other.description.clone(),
+            tags: other.tags.clone(),
+            enabled: other.enabled,
             _trigger_sequence: trigger_sequence,
+            _filter: filter,
+            _config: "".to_owned(),
+            _package: None,
+        }
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.modifiers.iter() {
+            write!(f, "{}+", Hotkey::modifier_name(modifier))?;
+        }
+        if self.key == ' ' {
+            write!(f, "space")
+        }else{
+            write!(f, "{}", self.key)
         }
     }
 }
@@ -144,11 +404,21 @@ impl<'a> From<&'a AutoMatch> for Match{
 /// Used to deserialize the Match struct before applying some custom elaboration.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct AutoMatch {
+    #[serde(default = "default_trigger")]
     pub trigger: String,
 
+    #[serde(default = "default_hotkey")]
+    pub hotkey: Option<String>,
+
     #[serde(default = "default_replace")]
     pub replace: Option<String>,
 
+    #[serde(default = "default_replace")]
+    pub html: Option<String>,
+
+    #[serde(default = "default_replace")]
+    pub markdown: Option<String>,
+
     #[serde(default = "default_image_path")]
     pub image_path: Option<String>,
 
@@ -160,13 +430,53 @@ struct AutoMatch {
 
     #[serde(default = "default_passive_only")]
     pub passive_only: bool,
+
+    #[serde(default = "default_backend")]
+    pub backend: Option<BackendType>,
+
+    #[serde(default = "default_label")]
+    pub label: Option<String>,
+
+    #[serde(default = "default_label")]
+    pub description: Option<String>,
+
+    #[serde(default = "default_tags")]
+    pub tags: Vec<String>,
+
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    #[serde(default = "default_filter")]
+    pub filter_title: String,
+
+    #[serde(default = "default_filter")]
+    pub filter_class: String,
+
+    #[serde(default = "default_filter")]
+    pub filter_exec: String,
+
+    #[serde(default = "default_filter")]
+    pub filter_exclude_title: String,
+
+    #[serde(default = "default_filter")]
+    pub filter_exclude_class: String,
+
+    #[serde(default = "default_filter")]
+    pub filter_exclude_exec: String,
 }
 
+fn default_trigger() -> String {"".to_owned()}
+fn default_hotkey() -> Option<String> {None}
+fn default_label() -> Option<String> {None}
+fn default_tags() -> Vec<String> {Vec::new()}
+fn default_enabled() -> bool {true}
 fn default_vars() -> Vec<MatchVariable> {Vec::new()}
 fn default_word() -> bool {false}
 fn default_passive_only() -> bool {false}
 fn default_replace() -> Option<String> {None}
 fn default_image_path() -> Option<String> {None}
+fn default_filter() -> String {"".to_owned()}
+fn default_backend() -> Option<BackendType> {None}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct MatchVariable {
@@ -186,13 +496,20 @@ pub enum TriggerEntry {
 
 pub trait MatchReceiver {
     fn on_match(&self, m: &Match, trailing_separator: Option<char>);
+    fn on_hotkey_match(&self, m: &Match);
     fn on_enable_update(&self, status: bool);
     fn on_passive(&self);
+    fn on_next_tab_stop(&self);
 }
 
 pub trait Matcher : KeyEventReceiver {
     fn handle_char(&self, c: &str);
     fn handle_modifier(&self, m: KeyModifier);
+    fn handle_modifier_hold(&self, m: KeyModifier, duration: u64);
+    fn handle_modifier_release(&self, m: KeyModifier);
+
+    // Actions bound to the gestures fired since the last call
+    fn take_gesture_actions(&self) -> Vec<ActionType>;
 }
 
 impl <M: Matcher> KeyEventReceiver for M {
@@ -204,8 +521,18 @@ impl <M: Matcher> KeyEventReceiver for M {
             KeyEvent::Modifier(m) => {
                 self.handle_modifier(m);
             },
+            KeyEvent::ModifierHold(m, duration) => {
+                self.handle_modifier_hold(m, duration);
+            },
+            KeyEvent::ModifierRelease(m) => {
+                self.handle_modifier_release(m);
+            },
         }
     }
+
+    fn take_actions(&self) -> Vec<ActionType> {
+        self.take_gesture_actions()
+    }
 }
 
 
@@ -304,6 +631,67 @@ mod tests {
         assert_eq!(_match._trigger_sequence[4], TriggerEntry::WordSeparator);
     }
 
+    #[test]
+    fn test_match_without_filters() {
+        let match_str = r###"
+        trigger: "test"
+        replace: "This is a test"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert!(_match._filter.is_empty());
+    }
+
+    #[test]
+    fn test_match_with_filters() {
+        let match_str = r###"
+        trigger: "test"
+        replace: "This is a test"
+        filter_class: "Slack"
+        filter_exclude_title: "Private"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert!(!_match._filter.is_empty());
+        assert!(_match._filter.class.is_some());
+        assert!(_match._filter.exclude_title.is_some());
+        assert!(_match._filter.title.is_none());
+        assert!(_match._filter.exec.is_none());
+    }
+
+    #[test]
+    fn test_match_with_malformed_filter_is_ignored() {
+        let match_str = r###"
+        trigger: "test"
+        replace: "This is a test"
+        filter_title: "[`-_]"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert!(_match._filter.is_empty());
+    }
+
+    #[test]
+    fn test_match_serializes_filters() {
+        let match_str = r###"
+        trigger: "test"
+        replace: "This is a test"
+        filter_class: "Slack"
+        filter_exclude_title: "random"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        let value = serde_yaml::to_value(&_match).unwrap();
+        assert_eq!(value["filter_class"].as_str(), Some("Slack"));
+        assert_eq!(value["filter_exclude_title"].as_str(), Some("random"));
+        assert!(value.get("filter_title").is_none());
+        assert!(value.get("_filter").is_none());
+    }
+
     #[test]
     fn test_match_with_image_content() {
         let match_str = r###"
@@ -322,4 +710,184 @@ mod tests {
             },
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_match_backend_override() {
+        let match_str = r###"
+        trigger: "test"
+        replace: "This is a test"
+        backend: Clipboard
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert_eq!(_match.backend, Some(BackendType::Clipboard));
+    }
+
+    #[test]
+    fn test_match_backend_default() {
+        let match_str = r###"
+        trigger: "test"
+        replace: "This is a test"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert_eq!(_match.backend, None);
+    }
+
+    #[test]
+    fn test_match_with_html_content() {
+        let match_str = r###"
+        trigger: "test"
+        html: "<b>Hello</b> {{name}}"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        match _match.content {
+            MatchContentType::Html(content) => {
+                assert_eq!(content.replace, "<b>Hello</b> {{name}}");
+                assert_eq!(content._has_vars, true);
+            },
+            _ => {
+                assert!(false);
+            },
+        }
+    }
+
+    #[test]
+    fn test_match_with_markdown_content() {
+        let match_str = r###"
+        trigger: "test"
+        markdown: "**Hello**"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        match _match.content {
+            MatchContentType::Markdown(content) => {
+                assert_eq!(content.replace, "**Hello**");
+                assert_eq!(content._has_vars, false);
+            },
+            _ => {
+                assert!(false);
+            },
+        }
+    }
+
+    #[test]
+    fn test_hotkey_parse() {
+        let hotkey = Hotkey::parse("Ctrl+Alt+E").unwrap();
+        assert_eq!(hotkey.modifiers, vec![KeyModifier::CTRL, KeyModifier::ALT]);
+        assert_eq!(hotkey.key, 'e');
+    }
+
+    #[test]
+    fn test_hotkey_parse_invalid() {
+        assert!(Hotkey::parse("").is_err());
+        assert!(Hotkey::parse("ctrl+").is_err());
+        assert!(Hotkey::parse("e").is_err());
+        assert!(Hotkey::parse("shift+e").is_err());
+        assert!(Hotkey::parse("hyper+e").is_err());
+        assert!(Hotkey::parse("ctrl+enter").is_err());
+    }
+
+    #[test]
+    fn test_hotkey_parse_named_key() {
+        let hotkey = Hotkey::parse("ctrl+shift+space").unwrap();
+        assert_eq!(hotkey.modifiers, vec![KeyModifier::CTRL, KeyModifier::SHIFT]);
+        assert_eq!(hotkey.key, ' ');
+    }
+
+    #[test]
+    fn test_hotkey_is_triggered_by() {
+        let hotkey = Hotkey::parse("ctrl+alt+e").unwrap();
+        assert!(hotkey.is_triggered_by(&[KeyModifier::ALT, KeyModifier::CTRL], "e"));
+        assert!(hotkey.is_triggered_by(&[KeyModifier::CTRL, KeyModifier::ALT], "\u{5}"));
+        assert!(!hotkey.is_triggered_by(&[KeyModifier::CTRL], "e"));
+        assert!(!hotkey.is_triggered_by(&[KeyModifier::CTRL, KeyModifier::ALT, KeyModifier::SHIFT], "E"));
+        assert!(!hotkey.is_triggered_by(&[KeyModifier::CTRL, KeyModifier::ALT], "f"));
+
+        // Control codes only stand for letters when ctrl is held
+        let hotkey = Hotkey::parse("alt+meta+m").unwrap();
+        assert!(!hotkey.is_triggered_by(&[KeyModifier::ALT, KeyModifier::META], "\r"));
+        let hotkey = Hotkey::parse("alt+i").unwrap();
+        assert!(!hotkey.is_triggered_by(&[KeyModifier::ALT], "\t"));
+        let hotkey = Hotkey::parse("ctrl+i").unwrap();
+        assert!(hotkey.is_triggered_by(&[KeyModifier::CTRL], "\t"));
+    }
+
+    #[test]
+    fn test_match_with_hotkey_only() {
+        let match_str = r###"
+        hotkey: "ctrl+alt+e"
+        replace: "Hello"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert_eq!(_match.trigger, "");
+        assert!(_match._trigger_sequence.is_empty());
+        assert!(_match.hotkey.is_some());
+        assert_eq!(_match.merge_key(), "CTRL+ALT+e");
+    }
+
+    #[test]
+    fn test_match_with_invalid_hotkey_is_ignored() {
+        let match_str = r###"
+        trigger: "test"
+        hotkey: "e"
+        replace: "Hello"
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert!(_match.hotkey.is_none());
+        assert_eq!(_match.merge_key(), "test");
+    }
+
+    #[test]
+    fn test_match_display_name() {
+        let typed : Match = serde_yaml::from_str("trigger: \":sig\"\nreplace: Hi\nlabel: Signature").unwrap();
+        assert_eq!(typed.display_name(), ":sig");
+
+        let hotkey : Match = serde_yaml::from_str("hotkey: \"ctrl+alt+e\"\nreplace: Hi").unwrap();
+        assert_eq!(hotkey.display_name(), "CTRL+ALT+e");
+
+        let labeled : Match = serde_yaml::from_str("hotkey: \"ctrl+alt+e\"\nreplace: Hi\nlabel: Signature").unwrap();
+        assert_eq!(labeled.display_name(), "Signature");
+    }
+
+    #[test]
+    fn test_match_metadata() {
+        let match_str = r###"
+        trigger: ":sig"
+        replace: "Best regards"
+        label: "Signature"
+        description: "Closing of the work emails"
+        tags: ["work", "email"]
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert_eq!(_match.label, Some("Signature".to_owned()));
+        assert_eq!(_match.description, Some("Closing of the work emails".to_owned()));
+        assert_eq!(_match.tags, vec!["work".to_owned(), "email".to_owned()]);
+        assert!(_match.enabled);
+    }
+
+    #[test]
+    fn test_disabled_match_without_content() {
+        let match_str = r###"
+        trigger: ":sig"
+        enabled: false
+        "###;
+
+        let _match : Match = serde_yaml::from_str(match_str).unwrap();
+
+        assert!(!_match.enabled);
+        assert!(_match.label.is_none());
+        assert!(_match.tags.is_empty());
+    }
+}
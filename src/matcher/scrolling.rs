@@ -18,8 +18,10 @@
  */
 
 use crate::matcher::{Match, MatchReceiver, TriggerEntry};
+use crate::matcher::gesture::{Gesture, GestureBinding};
 use std::cell::{RefCell, Ref};
-use crate::event::{KeyModifier, ActionEventReceiver, ActionType};
+use crate::event::{KeyModifier, ActionEventReceiver, ActionType, SystemEventReceiver, SystemEvent};
+use crate::protocol::DaemonStatus;
 use crate::config::ConfigManager;
 use crate::event::KeyModifier::BACKSPACE;
 use std::time::SystemTime;
@@ -29,10 +31,25 @@ pub struct ScrollingMatcher<'a, R: MatchReceiver, M: ConfigManager<'a>> {
     config_manager: &'a M,
     receiver: &'a R,
     current_set_queue: RefCell<VecDeque<Vec<MatchEntry<'a>>>>,
-    toggle_press_time: RefCell<SystemTime>,
-    passive_press_time: RefCell<SystemTime>,
+    tab_stop_press_time: RefCell<SystemTime>,
     is_enabled: RefCell<bool>,
     was_previous_char_word_separator: RefCell<bool>,
+
+    // Modifiers pressed since the last char, used to detect the match hotkeys
+    pressed_modifiers: RefCell<Vec<(KeyModifier, SystemTime)>>,
+
+    // Set once the context reports a modifier release, from then on the pressed
+    // modifiers are held until released
+    releases_reported: RefCell<bool>,
+
+    // The configured gestures, followed by the toggle and passive key ones
+    gestures: Vec<GestureBinding>,
+
+    // Last modifier pressed, with the number of consecutive presses
+    last_press: RefCell<Option<(KeyModifier, usize, SystemTime)>>,
+
+    // Actions of the fired gestures, waiting to be dispatched by the event manager
+    gesture_actions: RefCell<Vec<ActionType>>,
 }
 
 #[derive(Clone)]
@@ -45,34 +62,139 @@ struct MatchEntry<'a> {
 impl <'a, R: MatchReceiver, M: ConfigManager<'a>> ScrollingMatcher<'a, R, M> {
     pub fn new(config_manager: &'a M, receiver: &'a R) -> ScrollingMatcher<'a, R, M> {
         let current_set_queue = RefCell::new(VecDeque::new());
-        let toggle_press_time = RefCell::new(SystemTime::now());
-        let passive_press_time = RefCell::new(SystemTime::now());
+        let tab_stop_press_time = RefCell::new(SystemTime::now());
+
+        let gestures = config_manager.default_config().gesture_bindings();
 
         ScrollingMatcher{
             config_manager,
             receiver,
             current_set_queue,
-            toggle_press_time,
-            passive_press_time,
+            tab_stop_press_time,
             is_enabled: RefCell::new(true),
             was_previous_char_word_separator: RefCell::new(true),
+            pressed_modifiers: RefCell::new(Vec::new()),
+            releases_reported: RefCell::new(false),
+            gestures,
+            last_press: RefCell::new(None),
+            gesture_actions: RefCell::new(Vec::new()),
         }
     }
 
-    fn toggle(&self) {
-        let mut is_enabled = self.is_enabled.borrow_mut();
-        *is_enabled = !(*is_enabled);
+    pub fn is_enabled(&self) -> bool {
+        *self.is_enabled.borrow()
+    }
 
-        self.receiver.on_enable_update(*is_enabled);
+    /// Restore the status of a previous matcher, without notifying the receiver.
+    pub fn restore_enabled(&self, enabled: bool) {
+        *self.is_enabled.borrow_mut() = enabled;
+    }
+
+    fn toggle(&self) {
+        let enabled = !*self.is_enabled.borrow();
+        self.set_enabled(enabled);
     }
 
     fn set_enabled(&self, enabled: bool) {
         let mut is_enabled = self.is_enabled.borrow_mut();
         *is_enabled = enabled;
 
+        if !enabled {
+            self.current_set_queue.borrow_mut().clear();
+        }
+
         self.receiver.on_enable_update(*is_enabled);
     }
 
+    /// Queue the action bound to a gesture. The event manager dispatches it as if
+    /// it was sent by the context, so that the reload and search are handled too.
+    fn dispatch_action(&self, action: ActionType) {
+        self.gesture_actions.borrow_mut().push(action);
+    }
+
+    /// Count the consecutive presses of the modifier and dispatch the action bound
+    /// to the resulting press gesture, if any. Returns true if a gesture fired.
+    fn handle_press_gesture(&self, m: &KeyModifier) -> bool {
+        let interval = u128::from(self.config_manager.default_config().toggle_interval);
+
+        let count = {
+            let mut last_press = self.last_press.borrow_mut();
+            let count = match &*last_press {
+                Some((last_modifier, count, press_time)) if last_modifier == m => {
+                    match press_time.elapsed() {
+                        Ok(elapsed) if elapsed.as_millis() < interval => count + 1,
+                        _ => 1,
+                    }
+                },
+                _ => 1,
+            };
+            *last_press = Some((m.clone(), count, SystemTime::now()));
+            count
+        };
+
+        let press_count = |binding: &GestureBinding| {
+            match &binding.gesture {
+                Gesture::Press { modifier, count } if modifier == m => *count,
+                _ => 0,
+            }
+        };
+
+        let action = self.gestures.iter()
+            .find(|binding| press_count(binding) == count)
+            .map(|binding| binding.action.clone());
+
+        if let Some(action) = action {
+            // Start counting again, the longer sequences can't overlap with this one
+            *self.last_press.borrow_mut() = None;
+
+            self.dispatch_action(action);
+            true
+        }else{
+            false
+        }
+    }
+
+    /// Modifiers held while typing the current char. The contexts don't report the
+    /// modifiers release on every platform, in that case a modifier counts as held
+    /// for `hotkey_interval` after its press.
+    fn take_held_modifiers(&self) -> Vec<KeyModifier> {
+        if *self.releases_reported.borrow() {
+            return self.pressed_modifiers.borrow().iter()
+                .map(|(modifier, _)| modifier.clone())
+                .collect();
+        }
+
+        let pressed_modifiers: Vec<(KeyModifier, SystemTime)> = self.pressed_modifiers.borrow_mut()
+            .drain(..).collect();
+
+        let interval = u128::from(self.config_manager.default_config().hotkey_interval);
+        let mut held_modifiers = Vec::new();
+        for (modifier, press_time) in pressed_modifiers.into_iter() {
+            let is_held = match press_time.elapsed() {
+                Ok(elapsed) => elapsed.as_millis() < interval,
+                Err(_) => false,
+            };
+            if is_held && !held_modifiers.contains(&modifier) {
+                held_modifiers.push(modifier);
+            }
+        }
+
+        held_modifiers
+    }
+
+    /// Find the hotkey match fired by the given char, typed while holding the given modifiers
+    fn find_hotkey_match(&self, held_modifiers: &[KeyModifier], c: &str) -> Option<&'a Match> {
+        let active_config = self.config_manager.active_config();
+        active_config.matches.iter().find(|m| {
+            match &m.hotkey {
+                Some(hotkey) => {
+                    hotkey.is_triggered_by(held_modifiers, c) && self.config_manager.is_match_active(m)
+                },
+                None => false,
+            }
+        })
+    }
+
     fn is_matching(mtc: &Match, current_char: &str, start: usize, is_current_word_separator: bool) -> bool {
         match mtc._trigger_sequence[start] {
             TriggerEntry::Char(c) => {
@@ -87,11 +209,50 @@ impl <'a, R: MatchReceiver, M: ConfigManager<'a>> ScrollingMatcher<'a, R, M> {
 
 impl <'a, R: MatchReceiver, M: ConfigManager<'a>> super::Matcher for ScrollingMatcher<'a, R, M> {
     fn handle_char(&self, c: &str) {
+        // A typed char interrupts the modifier press sequences
+        *self.last_press.borrow_mut() = None;
+
+        let held_modifiers = self.take_held_modifiers();
+
+        // Chords are checked even when disabled, as they could be bound to the enable action
+        if !held_modifiers.is_empty() {
+            let action = self.gestures.iter().find(|binding| {
+                match &binding.gesture {
+                    Gesture::Chord(hotkey) => hotkey.is_triggered_by(&held_modifiers, c),
+                    _ => false,
+                }
+            }).map(|binding| binding.action.clone());
+
+            if let Some(action) = action {
+                self.current_set_queue.borrow_mut().clear();
+                *self.was_previous_char_word_separator.borrow_mut() = true;
+
+                self.dispatch_action(action);
+                return;
+            }
+        }
+
         // if not enabled, avoid any processing
         if !*(self.is_enabled.borrow()) {
             return;
         }
 
+        // Never buffer the chars typed in password fields or in the disabled apps
+        if self.config_manager.is_input_suspended() {
+            self.current_set_queue.borrow_mut().clear();
+            *self.was_previous_char_word_separator.borrow_mut() = true;
+            return;
+        }
+
+        if let Some(mtc) = self.find_hotkey_match(&held_modifiers, c) {
+            // The key was part of a combination, not typed text
+            self.current_set_queue.borrow_mut().clear();
+            *self.was_previous_char_word_separator.borrow_mut() = true;
+
+            self.receiver.on_hotkey_match(mtc);
+            return;
+        }
+
         // Obtain the configuration for the active application if present,
         // otherwise get the default one
         let active_config = self.config_manager.active_config();
@@ -115,7 +276,12 @@ impl <'a, R: MatchReceiver, M: ConfigManager<'a>> super::Matcher for ScrollingMa
         let new_matches: Vec<MatchEntry> = active_config.matches.iter()
             .filter(|&x| {
                 // only active-enabled matches are considered
-                if x.passive_only {
+                if x.passive_only || !x.enabled {
+                    return false;
+                }
+
+                // hotkey-only matches can't be typed
+                if x._trigger_sequence.is_empty() {
                     return false;
                 }
 
@@ -158,6 +324,11 @@ impl <'a, R: MatchReceiver, M: ConfigManager<'a>> super::Matcher for ScrollingMa
 
         for entry in combined_matches.iter() {
             if entry.start == entry.count {
+                // Skip the matches whose app filters are not satisfied by the active window
+                if !self.config_manager.is_match_active(entry._match) {
+                    continue;
+                }
+
                 found_match = Some(entry._match);
                 break;
             }
@@ -201,24 +372,12 @@ impl <'a, R: MatchReceiver, M: ConfigManager<'a>> super::Matcher for ScrollingMa
     fn handle_modifier(&self, m: KeyModifier) {
         let config = self.config_manager.default_config();
 
-        // TODO: at the moment, activating the passive key triggers the toggle key
-        // study a mechanism to avoid this problem
+        let gesture_fired = self.handle_press_gesture(&m);
 
-        if m == config.toggle_key {
-            check_interval(&self.toggle_press_time,
-                           u128::from(config.toggle_interval), || {
-                self.toggle();
-
-                let is_enabled = self.is_enabled.borrow();
-
-                if !*is_enabled {
-                    self.current_set_queue.borrow_mut().clear();
-                }
-            });
-        }else if m == config.passive_key {
-            check_interval(&self.passive_press_time,
+        if !gesture_fired && m == config.tab_stop_key {
+            check_interval(&self.tab_stop_press_time,
                            u128::from(config.toggle_interval), || {
-                self.receiver.on_passive();
+                self.receiver.on_next_tab_stop();
             });
         }
 
@@ -226,6 +385,50 @@ impl <'a, R: MatchReceiver, M: ConfigManager<'a>> super::Matcher for ScrollingMa
         if m == BACKSPACE {
             let mut current_set_queue = self.current_set_queue.borrow_mut();
             current_set_queue.pop_back();
+        }else if m != KeyModifier::OFF {
+            let mut pressed_modifiers = self.pressed_modifiers.borrow_mut();
+            if *self.releases_reported.borrow() {
+                // The auto repeat generates multiple presses
+                if !pressed_modifiers.iter().any(|(modifier, _)| *modifier == m) {
+                    pressed_modifiers.push((m, SystemTime::now()));
+                }
+            }else{
+                // Forget the modifiers that can no longer be part of a hotkey
+                let interval = u128::from(config.hotkey_interval);
+                pressed_modifiers.retain(|(_, press_time)| {
+                    match press_time.elapsed() {
+                        Ok(elapsed) => elapsed.as_millis() < interval,
+                        Err(_) => false,
+                    }
+                });
+                pressed_modifiers.push((m, SystemTime::now()));
+            }
+        }
+    }
+
+    fn handle_modifier_release(&self, m: KeyModifier) {
+        *self.releases_reported.borrow_mut() = true;
+        self.pressed_modifiers.borrow_mut().retain(|(modifier, _)| *modifier != m);
+    }
+
+    fn take_gesture_actions(&self) -> Vec<ActionType> {
+        self.gesture_actions.borrow_mut().drain(..).collect()
+    }
+
+    fn handle_modifier_hold(&self, m: KeyModifier, duration: u64) {
+        if duration < u64::from(self.config_manager.default_config().hold_interval) {
+            return;
+        }
+
+        let action = self.gestures.iter().find(|binding| {
+            binding.gesture == Gesture::Hold(m.clone())
+        }).map(|binding| binding.action.clone());
+
+        if let Some(action) = action {
+            // The release is not the start of a press sequence
+            *self.last_press.borrow_mut() = None;
+
+            self.dispatch_action(action);
         }
     }
 }
@@ -247,6 +450,28 @@ impl <'a, R: MatchReceiver, M: ConfigManager<'a>> ActionEventReceiver for Scroll
     }
 }
 
+impl <'a, R: MatchReceiver, M: ConfigManager<'a>> SystemEventReceiver for ScrollingMatcher<'a, R, M> {
+    fn on_system_event(&self, e: SystemEvent) {
+        match e {
+            SystemEvent::StatusRequest(sender) => {
+                let active_config = self.config_manager.active_config();
+
+                let status = DaemonStatus {
+                    enabled: *self.is_enabled.borrow(),
+                    active_config: active_config.name.clone(),
+                    active_profile: self.config_manager.active_profile(),
+                    match_count: active_config.matches.len(),
+                    uptime: 0,  // Calculated by the IPC server
+                };
+
+                // The requester could have timed out in the meantime, so errors are ignored
+                sender.send(status).unwrap_or_default();
+            },
+            _ => {}
+        }
+    }
+}
+
 fn check_interval<F>(state_var: &RefCell<SystemTime>, interval: u128, elapsed_callback: F) where F:Fn() {
     let mut press_time = state_var.borrow_mut();
     if let Ok(elapsed) = press_time.elapsed() {
@@ -18,12 +18,15 @@
  */
 
 use serde::{Deserialize, Serialize};
-use std::sync::mpsc::Sender;
+use std::sync::mpsc::{Sender, Receiver, SyncSender, TrySendError, channel, sync_channel};
+use std::sync::{Arc, Mutex};
 use crate::event::Event;
-use crate::event::ActionType;
-use std::io::{BufReader, Read, Write};
+use crate::event::{ActionType, SystemEvent};
+use std::io::{BufReader, BufRead, Read, Write, ErrorKind};
+use std::net::{TcpStream, Shutdown};
 use std::error::Error;
-use log::error;
+use std::time::{Duration, SystemTime};
+use log::{error, warn, info};
 use crate::config::ConfigSet;
 
 #[cfg(target_os = "windows")]
@@ -32,20 +35,185 @@ mod windows;
 #[cfg(not(target_os = "windows"))]
 mod unix;
 
+// Version of the IPC protocol, increment it when introducing breaking changes
+pub const IPC_PROTOCOL_VERSION: u32 = 1;
+
+// Maximum time the IPC server waits for the daemon to answer a query
+const QUERY_TIMEOUT: u64 = 1000;
+
+// Maximum time the IPC client waits for the daemon response
+const RESPONSE_TIMEOUT: u64 = 5000;
+
+// Events queued for each subscriber, the ones falling further behind are disconnected
+const SUBSCRIBER_QUEUE_SIZE: usize = 64;
+
+// Maximum time spent writing a single event to a subscriber
+const SUBSCRIBER_WRITE_TIMEOUT: u64 = 1000;
+
 pub trait IPCServer {
     fn start(&self);
 }
 
 pub trait IPCClient {
-    fn send_command(&self, command: IPCCommand) -> Result<(), String>;
+    fn send_command(&self, command: IPCCommand) -> Result<IPCResponse, String>;
+
+    // Keep the connection open, calling the callback for each event sent by the daemon
+    fn subscribe(&self, callback: &dyn Fn(IPCEvent)) -> Result<(), String>;
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct IPCCommand {
     pub id: String,
 
     #[serde(default)]
     pub payload: String,
+
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Wrapper sent over the socket, so that the daemon can detect the protocol
+/// used by the client. Legacy clients don't specify a version, defaulting to 0.
+#[derive(Serialize, Deserialize, Debug)]
+struct IPCRequest {
+    #[serde(default)]
+    version: u32,
+
+    #[serde(flatten)]
+    command: IPCCommand,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IPCResponse {
+    pub version: u32,
+    pub daemon_version: String,
+    pub success: bool,
+
+    #[serde(default)]
+    pub message: String,
+
+    #[serde(default)]
+    pub status: Option<DaemonStatus>,
+}
+
+impl IPCResponse {
+    fn ok() -> IPCResponse {
+        IPCResponse {
+            version: IPC_PROTOCOL_VERSION,
+            daemon_version: crate::VERSION.to_owned(),
+            success: true,
+            message: "".to_owned(),
+            status: None,
+        }
+    }
+
+    fn error(message: &str) -> IPCResponse {
+        IPCResponse {
+            success: false,
+            message: message.to_owned(),
+            ..IPCResponse::ok()
+        }
+    }
+}
+
+/// Status of the running daemon, returned by the "status" command
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DaemonStatus {
+    pub enabled: bool,
+    pub active_config: String,
+    pub active_profile: Option<String>,
+    pub match_count: usize,
+
+    // Seconds elapsed since the daemon started
+    #[serde(default)]
+    pub uptime: u64,
+}
+
+/// Events streamed by the daemon to the subscribed clients
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IPCEvent {
+    pub timestamp: String,
+
+    #[serde(flatten)]
+    pub kind: IPCEventKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IPCEventKind {
+    Expansion { trigger: String, config: String },
+    StatusChanged { enabled: bool },
+    ProfileSwitched { profile: Option<String> },
+    ConfigReloaded { match_count: usize },
+    Error { message: String },
+}
+
+impl IPCEvent {
+    pub fn new(kind: IPCEventKind) -> IPCEvent {
+        IPCEvent {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            kind,
+        }
+    }
+}
+
+// Queues of the clients subscribed to the daemon events
+type SubscriberList = Arc<Mutex<Vec<SyncSender<String>>>>;
+
+/// Spawn a thread forwarding the daemon events to all the subscribed clients,
+/// dropping the ones that disconnected or can't keep up with the events.
+fn start_event_broadcaster(event_receiver: Receiver<IPCEvent>) -> SubscriberList {
+    let subscribers: SubscriberList = Arc::new(Mutex::new(Vec::new()));
+    let thread_subscribers = subscribers.clone();
+
+    std::thread::Builder::new().name("ipc_broadcaster".to_string()).spawn(move || {
+        for event in event_receiver.iter() {
+            let json_str = match serde_json::to_string(&event) {
+                Ok(json_str) => json_str,
+                Err(e) => {
+                    error!("Error serializing IPC event: {}", e);
+                    continue;
+                },
+            };
+
+            // Never block on the subscribers, each one is served by its own thread
+            let mut subscribers = thread_subscribers.lock().expect("Poisoned IPC subscriber list");
+            subscribers.retain(|subscriber| {
+                match subscriber.try_send(format!("{}\n", json_str)) {
+                    Ok(_) => true,
+                    Err(TrySendError::Full(_)) => {
+                        warn!("IPC subscriber is not reading the events, disconnecting it");
+                        false
+                    },
+                    Err(TrySendError::Disconnected(_)) => {
+                        info!("IPC subscriber disconnected");
+                        false
+                    },
+                }
+            });
+        }
+    }).expect("Unable to spawn IPC broadcaster thread");
+
+    subscribers
+}
+
+/// Register a subscriber, spawning the thread writing the events to its stream.
+/// The thread terminates when a write fails, disconnecting the subscriber.
+fn add_subscriber<W: Write + Send + 'static>(subscribers: &SubscriberList, mut stream: W) {
+    let (sender, receiver) = sync_channel::<String>(SUBSCRIBER_QUEUE_SIZE);
+
+    let res = std::thread::Builder::new().name("ipc_subscriber".to_string()).spawn(move || {
+        for event in receiver.iter() {
+            if stream.write_all(event.as_bytes()).is_err() {
+                break;
+            }
+        }
+    });
+
+    match res {
+        Ok(_) => subscribers.lock().expect("Poisoned IPC subscriber list").push(sender),
+        Err(e) => error!("Unable to spawn IPC subscriber thread: {}", e),
+    }
 }
 
 impl IPCCommand {
@@ -63,30 +231,101 @@ impl IPCCommand {
             "disable" => {
                 Some(Event::Action(ActionType::Disable))
             },
+            "search" => {
+                Some(Event::Action(ActionType::Search))
+            },
+            "profile" => {
+                Some(Event::System(SystemEvent::SwitchProfile(self.payload.clone())))
+            },
+            "expand" => {
+                Some(Event::System(SystemEvent::ExpandMatch(self.payload.clone(), self.args.clone())))
+            },
+            "inject" => {
+                Some(Event::System(SystemEvent::InjectText(self.payload.clone())))
+            },
             _ => None
         }
     }
 }
 
-fn process_event<R: Read, E: Error>(event_channel: &Sender<Event>, stream: Result<R, E>) {
+/// Ask the daemon for its current status, waiting for the answer
+fn query_status(event_channel: &Sender<Event>, start_time: SystemTime) -> IPCResponse {
+    let (status_sender, status_receiver) = channel();
+    event_channel.send(Event::System(SystemEvent::StatusRequest(status_sender)))
+        .expect("Broken event channel");
+
+    match status_receiver.recv_timeout(Duration::from_millis(QUERY_TIMEOUT)) {
+        Ok(mut status) => {
+            status.uptime = start_time.elapsed().map(|e| e.as_secs()).unwrap_or_default();
+
+            let mut response = IPCResponse::ok();
+            response.status = Some(status);
+            response
+        },
+        Err(e) => {
+            IPCResponse::error(&format!("Daemon did not answer the status request: {}", e))
+        },
+    }
+}
+
+fn process_event<S: IPCStream + Send + 'static, E: Error>(event_channel: &Sender<Event>, start_time: SystemTime,
+                                                           subscribers: &SubscriberList, stream: Result<S, E>) {
     match stream {
-        Ok(stream) => {
+        Ok(mut stream) => {
             let mut json_str= String::new();
-            let mut buf_reader = BufReader::new(stream);
-            let res = buf_reader.read_to_string(&mut json_str);
+            let res = {
+                let mut buf_reader = BufReader::new(&mut stream);
+                buf_reader.read_line(&mut json_str)
+            };
 
             if res.is_ok() {
-                let command : Result<IPCCommand, serde_json::Error> = serde_json::from_str(&json_str);
-                match command {
-                    Ok(command) => {
-                        let event = command.to_event();
-                        if let Some(event) = event {
-                            event_channel.send(event).expect("Broken event channel");
+                let mut is_subscription = false;
+
+                let request : Result<IPCRequest, serde_json::Error> = serde_json::from_str(&json_str);
+                let (response, event) = match request {
+                    Ok(request) => {
+                        if request.version > IPC_PROTOCOL_VERSION {
+                            (IPCResponse::error(&format!("Unsupported IPC protocol version {}, the daemon supports up to {}",
+                                                         request.version, IPC_PROTOCOL_VERSION)), None)
+                        }else if request.command.id == "status" {
+                            (query_status(event_channel, start_time), None)
+                        }else if request.command.id == "subscribe" {
+                            is_subscription = true;
+                            (IPCResponse::ok(), None)
+                        }else{
+                            match request.command.to_event() {
+                                Some(event) => (IPCResponse::ok(), Some(event)),
+                                None => (IPCResponse::error(&format!("Unknown command: {}", request.command.id)), None),
+                            }
                         }
                     },
                     Err(e) => {
                         error!("Error deserializing JSON command: {}", e);
+                        (IPCResponse::error(&format!("Invalid JSON command: {}", e)), None)
                     },
+                };
+
+                // Reply before dispatching the event, as some commands (such as exit)
+                // could terminate the daemon before the response is sent.
+                // Legacy clients don't wait for the response, so errors are ignored.
+                if let Ok(json_response) = serde_json::to_string(&response) {
+                    let res = stream.write_all(format!("{}\n", json_response).as_bytes());
+                    if let Err(e) = res {
+                        warn!("Unable to send IPC response: {}", e);
+                    }
+                }
+
+                if let Some(event) = event {
+                    event_channel.send(event).expect("Broken event channel");
+                }
+
+                // Keep the stream open, the events will be sent by the broadcaster
+                if is_subscription {
+                    info!("New IPC subscriber connected");
+                    if let Err(e) = stream.set_send_timeout(Some(Duration::from_millis(SUBSCRIBER_WRITE_TIMEOUT))) {
+                        warn!("Unable to set IPC subscriber timeout: {}", e);
+                    }
+                    add_subscriber(subscribers, stream);
                 }
             }
         }
@@ -96,29 +335,142 @@ fn process_event<R: Read, E: Error>(event_channel: &Sender<Event>, stream: Resul
     }
 }
 
-fn send_command<W: Write, E: Error>(command: IPCCommand, stream: Result<W, E>) -> Result<(), String>{
-    match stream {
-        Ok(mut stream) => {
-            let json_str = serde_json::to_string(&command);
-            if let Ok(json_str) = json_str {
-                stream.write_all(json_str.as_bytes()).unwrap_or_else(|e| {
-                    println!("Can't write to IPC socket: {}", e);
-                });
-                return Ok(())
-            }
-        },
-        Err(e) => {
-            return Err(format!("Can't connect to daemon: {}", e))
+/// Socket connecting the client to the daemon
+pub trait IPCStream: Read + Write {
+    fn set_response_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()>;
+    fn set_send_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()>;
+    fn close_write(&self) -> std::io::Result<()>;
+}
+
+impl IPCStream for TcpStream {
+    fn set_response_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.set_read_timeout(timeout)
+    }
+
+    fn set_send_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.set_write_timeout(timeout)
+    }
+
+    fn close_write(&self) -> std::io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+impl IPCStream for std::os::unix::net::UnixStream {
+    fn set_response_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.set_read_timeout(timeout)
+    }
+
+    fn set_send_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        self.set_write_timeout(timeout)
+    }
+
+    fn close_write(&self) -> std::io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+fn write_request<W: Write>(command: IPCCommand, stream: &mut W) -> Result<(), String> {
+    let request = IPCRequest {
+        version: IPC_PROTOCOL_VERSION,
+        command,
+    };
+
+    let json_str = serde_json::to_string(&request).map_err(|e| {
+        format!("Can't serialize command: {}", e)
+    })?;
+
+    stream.write_all(format!("{}\n", json_str).as_bytes()).map_err(|e| {
+        format!("Can't write to IPC socket: {}", e)
+    })
+}
+
+fn read_response<R: BufRead>(reader: &mut R) -> Result<IPCResponse, String> {
+    let mut response_str = String::new();
+    reader.read_line(&mut response_str).map_err(|e| {
+        match e.kind() {
+            ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+                "The daemon did not reply in time, it may be running an older espanso version. \
+                 Please restart it with 'espanso restart'".to_owned()
+            },
+            _ => format!("Can't read from IPC socket: {}", e),
+        }
+    })?;
+
+    if response_str.is_empty() {
+        return Err("The daemon did not reply, it may be running an older espanso version. \
+                    Please restart it with 'espanso restart'".to_owned())
+    }
+
+    let response : IPCResponse = serde_json::from_str(&response_str).map_err(|e| {
+        format!("Invalid response from daemon: {}", e)
+    })?;
+
+    if response.version != IPC_PROTOCOL_VERSION {
+        return Err(format!("The daemon (version {}) uses IPC protocol v{}, while this client uses v{}. \
+                            Please restart it with 'espanso restart'",
+                           response.daemon_version, response.version, IPC_PROTOCOL_VERSION))
+    }
+
+    if !response.success {
+        return Err(response.message)
+    }
+
+    Ok(response)
+}
+
+fn send_command<S: IPCStream, E: Error>(command: IPCCommand, stream: Result<S, E>) -> Result<IPCResponse, String>{
+    let mut stream = stream.map_err(|e| format!("Can't connect to daemon: {}", e))?;
+    stream.set_response_timeout(Some(Duration::from_millis(RESPONSE_TIMEOUT)))
+        .map_err(|e| format!("Can't set IPC socket timeout: {}", e))?;
+
+    write_request(command, &mut stream)?;
+
+    // Signal the end of the request, as older daemons read the command until EOF
+    stream.close_write().map_err(|e| format!("Can't close IPC socket: {}", e))?;
+
+    // Wait for the daemon response
+    let mut buf_reader = BufReader::new(&mut stream);
+    read_response(&mut buf_reader)
+}
+
+fn subscribe<S: IPCStream, E: Error>(stream: Result<S, E>, callback: &dyn Fn(IPCEvent)) -> Result<(), String> {
+    let mut stream = stream.map_err(|e| format!("Can't connect to daemon: {}", e))?;
+    stream.set_response_timeout(Some(Duration::from_millis(RESPONSE_TIMEOUT)))
+        .map_err(|e| format!("Can't set IPC socket timeout: {}", e))?;
+
+    write_request(IPCCommand {
+        id: "subscribe".to_owned(),
+        ..Default::default()
+    }, &mut stream)?;
+
+    let mut buf_reader = BufReader::new(&mut stream);
+    read_response(&mut buf_reader)?;
+
+    // Events can be arbitrarily apart
+    buf_reader.get_ref().set_response_timeout(None)
+        .map_err(|e| format!("Can't set IPC socket timeout: {}", e))?;
+
+    // Events are sent one per line until the daemon terminates
+    for line in buf_reader.lines() {
+        let line = line.map_err(|e| format!("Can't read from IPC socket: {}", e))?;
+
+        match serde_json::from_str::<IPCEvent>(&line) {
+            Ok(event) => callback(event),
+            Err(e) => warn!("Unable to parse IPC event: {}", e),
         }
     }
 
-    Err("Can't send command".to_owned())
+    Ok(())
 }
 
 // UNIX IMPLEMENTATION
 #[cfg(not(target_os = "windows"))]
-pub fn get_ipc_server(_: ConfigSet, event_channel: Sender<Event>) -> impl IPCServer {
-    unix::UnixIPCServer::new(event_channel)
+pub fn get_ipc_server(_: ConfigSet, event_channel: Sender<Event>,
+                      ipc_event_receiver: Receiver<IPCEvent>) -> impl IPCServer {
+    let subscribers = start_event_broadcaster(ipc_event_receiver);
+    unix::UnixIPCServer::new(event_channel, subscribers)
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -128,11 +480,177 @@ pub fn get_ipc_client(_: ConfigSet) -> impl IPCClient {
 
 // WINDOWS IMPLEMENTATION
 #[cfg(target_os = "windows")]
-pub fn get_ipc_server(config_set: ConfigSet, event_channel: Sender<Event>) -> impl IPCServer {
-    windows::WindowsIPCServer::new(config_set, event_channel)
+pub fn get_ipc_server(config_set: ConfigSet, event_channel: Sender<Event>,
+                      ipc_event_receiver: Receiver<IPCEvent>) -> impl IPCServer {
+    let subscribers = start_event_broadcaster(ipc_event_receiver);
+    windows::WindowsIPCServer::new(config_set, event_channel, subscribers)
 }
 
 #[cfg(target_os = "windows")]
 pub fn get_ipc_client(config_set: ConfigSet) -> impl IPCClient {
     windows::WindowsIPCClient::new(config_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_request_defaults_to_version_zero() {
+        let request : IPCRequest = serde_json::from_str(r#"{"id":"exit","payload":""}"#).unwrap();
+        assert_eq!(request.version, 0);
+        assert_eq!(request.command.id, "exit");
+    }
+
+    #[test]
+    fn test_request_is_flattened() {
+        let request = IPCRequest {
+            version: IPC_PROTOCOL_VERSION,
+            command: IPCCommand {
+                id: "profile".to_owned(),
+                payload: "work".to_owned(),
+                ..Default::default()
+            },
+        };
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["version"], IPC_PROTOCOL_VERSION);
+        assert_eq!(json["id"], "profile");
+        assert_eq!(json["payload"], "work");
+    }
+
+    #[test]
+    fn test_response_without_status() {
+        let response : IPCResponse = serde_json::from_str(r#"{"version":1,"daemon_version":"0.5.0","success":true}"#).unwrap();
+        assert!(response.success);
+        assert!(response.status.is_none());
+        assert_eq!(response.message, "");
+    }
+
+    #[test]
+    fn test_expand_command_to_event() {
+        let request : IPCRequest = serde_json::from_str(r#"{"version":1,"id":"expand","payload":":greet","args":["John"]}"#).unwrap();
+        match request.command.to_event() {
+            Some(Event::System(SystemEvent::ExpandMatch(trigger, args))) => {
+                assert_eq!(trigger, ":greet");
+                assert_eq!(args, vec!["John".to_owned()]);
+            },
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_inject_command_to_event() {
+        let request : IPCRequest = serde_json::from_str(r#"{"version":1,"id":"inject","payload":"hello"}"#).unwrap();
+        match request.command.to_event() {
+            Some(Event::System(SystemEvent::InjectText(text))) => {
+                assert_eq!(text, "hello");
+            },
+            _ => assert!(false),
+        }
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let event = IPCEvent::new(IPCEventKind::Expansion {
+            trigger: ":hello".to_owned(),
+            config: "default".to_owned(),
+        });
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "expansion");
+        assert_eq!(json["trigger"], ":hello");
+        assert_eq!(json["config"], "default");
+        assert!(json["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_send_command_to_legacy_daemon_does_not_hang() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        // Older daemons read the command until EOF and never reply
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut command = String::new();
+            stream.read_to_string(&mut command).unwrap();
+            command
+        });
+
+        let result = send_command(IPCCommand {
+            id: "status".to_owned(),
+            ..Default::default()
+        }, TcpStream::connect(address));
+
+        assert!(result.unwrap_err().contains("did not reply"));
+        assert!(server.join().unwrap().contains("\"id\":\"status\""));
+    }
+
+    #[test]
+    fn test_config_reloaded_event_serialization() {
+        let event = IPCEvent::new(IPCEventKind::ConfigReloaded { match_count: 3 });
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event"], "config_reloaded");
+        assert_eq!(json["match_count"], 3);
+    }
+
+    // Writer blocked until the test ends, simulating a client that stopped reading
+    struct StalledWriter(Receiver<()>);
+
+    impl Write for StalledWriter {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            self.0.recv().unwrap_or_default();
+            Err(std::io::Error::new(ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct ChannelWriter(Sender<Vec<u8>>);
+
+    impl Write for ChannelWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.send(buf.to_vec()).unwrap();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_stalled_subscriber_does_not_block_the_others() {
+        let (event_sender, event_receiver) = channel();
+        let subscribers = start_event_broadcaster(event_receiver);
+
+        let (_stall_sender, stall_receiver) = channel();
+        add_subscriber(&subscribers, StalledWriter(stall_receiver));
+
+        let (output_sender, output_receiver) = channel();
+        add_subscriber(&subscribers, ChannelWriter(output_sender));
+
+        // The other subscriber keeps receiving the events, while the queue of the stalled one fills up
+        for _ in 0..SUBSCRIBER_QUEUE_SIZE * 2 {
+            event_sender.send(IPCEvent::new(IPCEventKind::StatusChanged { enabled: true })).unwrap();
+
+            let line = output_receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(String::from_utf8(line).unwrap().contains("status_changed"));
+        }
+
+        // The stalled subscriber fell behind and was dropped
+        assert_eq!(subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_event_deserialization() {
+        let event : IPCEvent = serde_json::from_str(r#"{"timestamp":"2020-01-01T00:00:00+00:00","event":"status_changed","enabled":false}"#).unwrap();
+        match event.kind {
+            IPCEventKind::StatusChanged { enabled } => assert!(!enabled),
+            _ => assert!(false),
+        }
+    }
 }
\ No newline at end of file
@@ -20,27 +20,31 @@
 use std::os::unix::net::{UnixStream,UnixListener};
 use log::{info, warn};
 use std::sync::mpsc::Sender;
-use super::IPCCommand;
+use std::time::SystemTime;
+use super::{IPCCommand, IPCResponse, IPCEvent, SubscriberList};
 
 use crate::context;
 use crate::event::*;
-use crate::protocol::{process_event, send_command};
+use crate::protocol::{process_event, send_command, subscribe};
 
 const UNIX_SOCKET_NAME : &str = "espanso.sock";
 
 pub struct UnixIPCServer {
     event_channel: Sender<Event>,
+    subscribers: SubscriberList,
 }
 
 impl UnixIPCServer {
-    pub fn new(event_channel: Sender<Event>) -> UnixIPCServer {
-        UnixIPCServer {event_channel}
+    pub fn new(event_channel: Sender<Event>, subscribers: SubscriberList) -> UnixIPCServer {
+        UnixIPCServer {event_channel, subscribers}
     }
 }
 
 impl super::IPCServer for UnixIPCServer {
     fn start(&self) {
         let event_channel = self.event_channel.clone();
+        let subscribers = self.subscribers.clone();
+        let start_time = SystemTime::now();
         std::thread::Builder::new().name("ipc_server".to_string()).spawn(move || {
             let espanso_dir = context::get_data_dir();
             let unix_socket = espanso_dir.join(UNIX_SOCKET_NAME);
@@ -53,7 +57,7 @@ impl super::IPCServer for UnixIPCServer {
             info!("Binded to IPC unix socket: {}", unix_socket.as_path().display());
 
             for stream in listener.incoming() {
-                process_event(&event_channel, stream);
+                process_event(&event_channel, start_time, &subscribers, stream);
             }
         }).expect("Unable to spawn IPC server thread");
     }
@@ -70,7 +74,7 @@ impl UnixIPCClient {
 }
 
 impl super::IPCClient for UnixIPCClient {
-    fn send_command(&self, command: IPCCommand) -> Result<(), String> {
+    fn send_command(&self, command: IPCCommand) -> Result<IPCResponse, String> {
         let espanso_dir = context::get_data_dir();
         let unix_socket = espanso_dir.join(UNIX_SOCKET_NAME);
 
@@ -79,4 +83,13 @@ impl super::IPCClient for UnixIPCClient {
 
         send_command(command, stream)
     }
+
+    fn subscribe(&self, callback: &dyn Fn(IPCEvent)) -> Result<(), String> {
+        let espanso_dir = context::get_data_dir();
+        let unix_socket = espanso_dir.join(UNIX_SOCKET_NAME);
+
+        let stream = UnixStream::connect(unix_socket);
+
+        subscribe(stream, callback)
+    }
 }
\ No newline at end of file
@@ -20,27 +20,31 @@
 use log::{info};
 use std::sync::mpsc::Sender;
 use std::net::{TcpListener, TcpStream};
-use super::IPCCommand;
+use std::time::SystemTime;
+use super::{IPCCommand, IPCResponse, IPCEvent, SubscriberList};
 
 use crate::event::*;
-use crate::protocol::{process_event, send_command};
+use crate::protocol::{process_event, send_command, subscribe};
 use crate::config::ConfigSet;
 
 pub struct WindowsIPCServer {
     config_set: ConfigSet,
     event_channel: Sender<Event>,
+    subscribers: SubscriberList,
 }
 
 impl WindowsIPCServer {
-    pub fn new(config_set: ConfigSet, event_channel: Sender<Event>) -> WindowsIPCServer {
-        WindowsIPCServer {config_set, event_channel}
+    pub fn new(config_set: ConfigSet, event_channel: Sender<Event>, subscribers: SubscriberList) -> WindowsIPCServer {
+        WindowsIPCServer {config_set, event_channel, subscribers}
     }
 }
 
 impl super::IPCServer for WindowsIPCServer {
     fn start(&self) {
         let event_channel = self.event_channel.clone();
-        let server_port = self.config_set.default.ipc_server_port;
+        let server_port = self.config_set.default.ipc_port();
+        let subscribers = self.subscribers.clone();
+        let start_time = SystemTime::now();
         std::thread::Builder::new().name("ipc_server".to_string()).spawn(move || {
             let listener = TcpListener::bind(
                 format!("127.0.0.1:{}", server_port)
@@ -49,7 +53,7 @@ impl super::IPCServer for WindowsIPCServer {
             info!("Binded to IPC tcp socket: {}", listener.local_addr().unwrap().to_string());
 
             for stream in listener.incoming() {
-                process_event(&event_channel, stream);
+                process_event(&event_channel, start_time, &subscribers, stream);
             }
         }).expect("Unable to spawn IPC server thread");
     }
@@ -66,11 +70,19 @@ impl WindowsIPCClient {
 }
 
 impl super::IPCClient for WindowsIPCClient {
-    fn send_command(&self, command: IPCCommand) -> Result<(), String> {
+    fn send_command(&self, command: IPCCommand) -> Result<IPCResponse, String> {
         let stream = TcpStream::connect(
-            ("127.0.0.1", self.config_set.default.ipc_server_port as u16)
+            ("127.0.0.1", self.config_set.default.ipc_port())
         );
 
         send_command(command, stream)
     }
+
+    fn subscribe(&self, callback: &dyn Fn(IPCEvent)) -> Result<(), String> {
+        let stream = TcpStream::connect(
+            ("127.0.0.1", self.config_set.default.ipc_port())
+        );
+
+        subscribe(stream, callback)
+    }
 }
\ No newline at end of file
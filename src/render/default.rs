@@ -23,7 +23,7 @@ use std::collections::HashMap;
 use regex::{Regex, Captures};
 use log::{warn, error};
 use super::*;
-use crate::matcher::{Match, MatchContentType};
+use crate::matcher::{Match, MatchContentType, TextContent};
 use crate::config::Configs;
 use crate::extension::Extension;
 
@@ -73,83 +73,173 @@ impl DefaultRenderer {
     }
 }
 
+impl DefaultRenderer {
+    /// Render the variables and arguments of a text content. Inner image matches
+    /// split the output into a sequence of text and image fragments.
+    fn render_fragments(&self, content: &TextContent, config: &Configs, args: &Vec<String>) -> Vec<RenderFragment> {
+        let (output_map, image_map) = self.render_variables(content, config, args);
+        DefaultRenderer::replace_variables(content, &output_map, &image_map, args, str::to_owned)
+    }
+
+    /// Calculate the value of each variable, the images of the inner matches are kept apart.
+    fn render_variables(&self, content: &TextContent, config: &Configs, args: &Vec<String>)
+                        -> (HashMap<String, String>, HashMap<String, PathBuf>) {
+        let mut output_map = HashMap::new();
+        let mut image_map = HashMap::new();
+
+        if content._has_vars || !config.global_vars.is_empty() {
+            // Cycle through both the local and global variables
+            for variable in config.global_vars.iter().chain(&content.vars) {
+                // In case of variables of type match, we need to recursively call
+                // the render function
+                if variable.var_type == "match" {
+                    // Extract the match trigger from the variable params
+                    let trigger = variable.params.get(&Value::from("trigger"));
+                    if trigger.is_none() {
+                        warn!("Missing param 'trigger' in match variable: {}", variable.name);
+                        continue;
+                    }
+                    let trigger = trigger.unwrap();
+
+                    // Find the given match from the active configs
+                    let inner_match = DefaultRenderer::find_match(config, trigger.as_str().unwrap_or(""));
+
+                    if inner_match.is_none() {
+                        warn!("Could not find inner match with trigger: '{}'", trigger.as_str().unwrap_or("undefined"));
+                        continue
+                    }
+
+                    let inner_match = inner_match.unwrap();
+
+                    // Render the inner match
+                    // TODO: inner arguments
+                    let result = self.render_match(&inner_match, config, vec![]);
+
+                    // Inner matches are only supported for text and image expansions, warn the user otherwise
+                    match result {
+                        RenderResult::Text(inner_content) => {
+                            output_map.insert(variable.name.clone(), inner_content);
+                        },
+                        RenderResult::Image(image_path) => {
+                            image_map.insert(variable.name.clone(), image_path);
+                        },
+                        _ => {
+                            warn!("Inner matches must be of TEXT or IMAGE type, ignoring variable: {}", variable.name)
+                        },
+                    }
+                }else{  // Normal extension variables
+                    let extension = self.extension_map.get(&variable.var_type);
+                    if let Some(extension) = extension {
+                        let ext_out = extension.calculate(&variable.params, args);
+                        if let Some(output) = ext_out {
+                            output_map.insert(variable.name.clone(), output);
+                        }else{
+                            output_map.insert(variable.name.clone(), "".to_owned());
+                            warn!("Could not generate output for variable: {}", variable.name);
+                        }
+                    }else{
+                        error!("No extension found for variable type: {}", variable.var_type);
+                    }
+                }
+            }
+        }
+
+        (output_map, image_map)
+    }
+
+    /// Replace the variables and arguments in the content, splitting the text at each
+    /// image. Their values are passed through the given escape function.
+    fn replace_variables(content: &TextContent, output_map: &HashMap<String, String>,
+                         image_map: &HashMap<String, PathBuf>, args: &Vec<String>,
+                         escape: fn(&str) -> String) -> Vec<RenderFragment> {
+        let args: Vec<String> = args.iter().map(|arg| escape(arg)).collect();
+        let args = &args;
+
+        let mut fragments = Vec::new();
+        let mut current = String::new();
+        let mut last_end = 0;
+        for caps in VAR_REGEX.captures_iter(&content.replace) {
+            let var_match = caps.get(0).unwrap();
+            current.push_str(&content.replace[last_end..var_match.start()]);
+            last_end = var_match.end();
+
+            let var_name = caps.name("name").unwrap().as_str();
+            if let Some(image_path) = image_map.get(var_name) {
+                if !current.is_empty() {
+                    fragments.push(RenderFragment::Text(utils::render_args(&current, args)));
+                    current.clear();
+                }
+                fragments.push(RenderFragment::Image(image_path.clone()));
+            }else if let Some(output) = output_map.get(var_name) {
+                current.push_str(&escape(output));
+            }else{
+                warn!("Could not find variable: {}", var_name);
+            }
+        }
+        current.push_str(&content.replace[last_end..]);
+
+        // Render any argument that may be present
+        if !current.is_empty() || fragments.is_empty() {
+            fragments.push(RenderFragment::Text(utils::render_args(&current, args)));
+        }
+
+        fragments
+    }
+
+    /// Join the fragments of a content that cannot contain images
+    fn join_text(fragments: Vec<RenderFragment>) -> String {
+        let mut output = String::new();
+        for fragment in fragments {
+            match fragment {
+                RenderFragment::Text(text) => output.push_str(&text),
+                RenderFragment::Image(image_path) => {
+                    warn!("Images are not supported in rich text matches, ignoring: {:?}", image_path)
+                },
+            }
+        }
+        output
+    }
+}
+
 impl super::Renderer for DefaultRenderer {
     fn render_match(&self, m: &Match, config: &Configs, args: Vec<String>) -> RenderResult {
         // Manage the different types of matches
         match &m.content {
             // Text Match
             MatchContentType::Text(content) => {
-                let target_string = if content._has_vars || !config.global_vars.is_empty(){
-                    let mut output_map = HashMap::new();
-
-                    // Cycle through both the local and global variables
-                    for variable in config.global_vars.iter().chain(&content.vars) {
-                        // In case of variables of type match, we need to recursively call
-                        // the render function
-                        if variable.var_type == "match" {
-                            // Extract the match trigger from the variable params
-                            let trigger = variable.params.get(&Value::from("trigger"));
-                            if trigger.is_none() {
-                                warn!("Missing param 'trigger' in match variable: {}", variable.name);
-                                continue;
-                            }
-                            let trigger = trigger.unwrap();
-
-                            // Find the given match from the active configs
-                            let inner_match = DefaultRenderer::find_match(config, trigger.as_str().unwrap_or(""));
-
-                            if inner_match.is_none() {
-                                warn!("Could not find inner match with trigger: '{}'", trigger.as_str().unwrap_or("undefined"));
-                                continue
-                            }
-
-                            let inner_match = inner_match.unwrap();
-
-                            // Render the inner match
-                            // TODO: inner arguments
-                            let result = self.render_match(&inner_match, config, vec![]);
-
-                            // Inner matches are only supported for text-expansions, warn the user otherwise
-                            match result {
-                                RenderResult::Text(inner_content) => {
-                                    output_map.insert(variable.name.clone(), inner_content);
-                                },
-                                _ => {
-                                    warn!("Inner matches must be of TEXT type. Mixing images is not supported yet.")
-                                },
-                            }
-                        }else{  // Normal extension variables
-                            let extension = self.extension_map.get(&variable.var_type);
-                            if let Some(extension) = extension {
-                                let ext_out = extension.calculate(&variable.params, &args);
-                                if let Some(output) = ext_out {
-                                    output_map.insert(variable.name.clone(), output);
-                                }else{
-                                    output_map.insert(variable.name.clone(), "".to_owned());
-                                    warn!("Could not generate output for variable: {}", variable.name);
-                                }
-                            }else{
-                                error!("No extension found for variable type: {}", variable.var_type);
-                            }
-                        }
-                    }
-
-                    // Replace the variables
-                    let result = VAR_REGEX.replace_all(&content.replace, |caps: &Captures| {
-                        let var_name = caps.name("name").unwrap().as_str();
-                        let output = output_map.get(var_name);
-                        output.unwrap()
-                    });
+                let mut fragments = self.render_fragments(content, config, &args);
 
-                    result.to_string()
-                }else{  // No variables, simple text substitution
-                    content.replace.clone()
+                // Plain text matches, the most common case, are not wrapped in a sequence
+                let is_plain_text = match fragments.as_slice() {
+                    [RenderFragment::Text(_)] => true,
+                    _ => false,
                 };
+                if is_plain_text {
+                    if let Some(RenderFragment::Text(text)) = fragments.pop() {
+                        return RenderResult::Text(text);
+                    }
+                }
 
-                // Render any argument that may be present
-                let target_string = utils::render_args(&target_string, &args);
+                RenderResult::Sequence(fragments)
+            },
 
-                RenderResult::Text(target_string)
+            // Rich text matches, the variables are rendered before the conversion and
+            // their values escaped, so that they are inserted as plain text
+            MatchContentType::Html(content) => {
+                let (output_map, image_map) = self.render_variables(content, config, &args);
+                let html = DefaultRenderer::join_text(DefaultRenderer::replace_variables(
+                    content, &output_map, &image_map, &args, utils::escape_html));
+                let text = utils::html_to_plain_text(&html);
+                RenderResult::Html { html, text }
+            },
+            MatchContentType::Markdown(content) => {
+                let (output_map, image_map) = self.render_variables(content, config, &args);
+                let text = DefaultRenderer::join_text(DefaultRenderer::replace_variables(
+                    content, &output_map, &image_map, &args, str::to_owned));
+                let markdown = DefaultRenderer::join_text(DefaultRenderer::replace_variables(
+                    content, &output_map, &image_map, &args, utils::escape_markdown));
+                let html = utils::markdown_to_html(&markdown);
+                RenderResult::Html { html, text }
             },
 
             // Image Match
@@ -481,4 +571,183 @@ mod tests {
 
         verify_render(rendered, "this is my local");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_render_match_markdown() {
+        let config = get_config_for(r###"
+        matches:
+            - trigger: ':test'
+              markdown: "Hello **{{output}}**"
+              vars:
+                - name: "output"
+                  type: dummy
+                  params:
+                    echo: "world"
+        "###);
+
+        let renderer = get_renderer(config.clone());
+
+        let rendered = renderer.render_match(&config.matches[0], &config, vec![]);
+
+        match rendered {
+            RenderResult::Html { html, text } => {
+                assert_eq!(html, "<p>Hello <strong>world</strong></p>");
+                assert_eq!(text, "Hello **world**");
+            },
+            _ => {
+                assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_match_html() {
+        let config = get_config_for(r###"
+        matches:
+            - trigger: ':test'
+              html: "<a href='https://espanso.org'>espanso</a> is $0$"
+        "###);
+
+        let renderer = get_renderer(config.clone());
+
+        let rendered = renderer.render_match(&config.matches[0], &config, vec!["great".to_owned()]);
+
+        match rendered {
+            RenderResult::Html { html, text } => {
+                assert_eq!(html, "<a href='https://espanso.org'>espanso</a> is great");
+                assert_eq!(text, "espanso is great");
+            },
+            _ => {
+                assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_match_html_escapes_values() {
+        let config = get_config_for(r###"
+        matches:
+            - trigger: ':test'
+              html: "<b>{{output}}</b> $0$"
+              vars:
+                - name: "output"
+                  type: dummy
+                  params:
+                    echo: "Tom & <Jerry>"
+        "###);
+
+        let renderer = get_renderer(config.clone());
+
+        let rendered = renderer.render_match(&config.matches[0], &config, vec!["<i>".to_owned()]);
+
+        match rendered {
+            RenderResult::Html { html, text } => {
+                assert_eq!(html, "<b>Tom &amp; &lt;Jerry&gt;</b> &lt;i&gt;");
+                assert_eq!(text, "Tom & <Jerry> <i>");
+            },
+            _ => {
+                assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_match_markdown_escapes_values() {
+        let config = get_config_for(r###"
+        matches:
+            - trigger: ':test'
+              markdown: "Hello **{{output}}**"
+              vars:
+                - name: "output"
+                  type: dummy
+                  params:
+                    echo: "*a* <script>"
+        "###);
+
+        let renderer = get_renderer(config.clone());
+
+        let rendered = renderer.render_match(&config.matches[0], &config, vec![]);
+
+        match rendered {
+            RenderResult::Html { html, text } => {
+                assert_eq!(html, "<p>Hello <strong>*a* &lt;script&gt;</strong></p>");
+                assert_eq!(text, "Hello ***a* <script>**");
+            },
+            _ => {
+                assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_match_with_inner_image() {
+        let image = tempfile::NamedTempFile::new().unwrap();
+        let config = get_config_for(&format!(r###"
+        matches:
+            - trigger: ':logo'
+              image_path: '{}'
+            - trigger: ':sig'
+              replace: "Best regards,\n{{{{logo}}}}\nJon"
+              vars:
+                - name: logo
+                  type: match
+                  params:
+                    trigger: ":logo"
+        "###, image.path().to_string_lossy()));
+
+        let renderer = get_renderer(config.clone());
+
+        let rendered = renderer.render_match(&config.matches[1], &config, vec![]);
+
+        match rendered {
+            RenderResult::Sequence(fragments) => {
+                assert_eq!(fragments.len(), 3);
+                match (&fragments[0], &fragments[1], &fragments[2]) {
+                    (RenderFragment::Text(first), RenderFragment::Image(path), RenderFragment::Text(last)) => {
+                        assert_eq!(first, "Best regards,\n");
+                        assert_eq!(path, image.path());
+                        assert_eq!(last, "\nJon");
+                    },
+                    _ => assert!(false),
+                }
+            },
+            _ => {
+                assert!(false)
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_match_only_inner_image() {
+        let image = tempfile::NamedTempFile::new().unwrap();
+        let config = get_config_for(&format!(r###"
+        matches:
+            - trigger: ':logo'
+              image_path: '{}'
+            - trigger: ':wrapper'
+              replace: "{{{{logo}}}}"
+              vars:
+                - name: logo
+                  type: match
+                  params:
+                    trigger: ":logo"
+        "###, image.path().to_string_lossy()));
+
+        let renderer = get_renderer(config.clone());
+
+        let rendered = renderer.render_match(&config.matches[1], &config, vec![]);
+
+        match rendered {
+            RenderResult::Sequence(fragments) => {
+                assert_eq!(fragments.len(), 1);
+                match &fragments[0] {
+                    RenderFragment::Image(path) => assert_eq!(path, image.path()),
+                    _ => assert!(false),
+                }
+            },
+            _ => {
+                assert!(false)
+            }
+        }
+    }
+}
@@ -23,6 +23,7 @@ use crate::config::Configs;
 
 pub(crate) mod default;
 pub(crate) mod utils;
+pub(crate) mod preview;
 
 pub trait Renderer {
     // Render a match output
@@ -35,5 +36,12 @@ pub trait Renderer {
 pub enum RenderResult {
     Text(String),
     Image(PathBuf),
+    Html { html: String, text: String },  // Rich text, with a plain text fallback
+    Sequence(Vec<RenderFragment>),        // Text mixed with images, injected in order
     Error
+}
+
+pub enum RenderFragment {
+    Text(String),
+    Image(PathBuf),
 }
\ No newline at end of file
@@ -0,0 +1,148 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::config::{ConfigSet, ConfigManager};
+use crate::config::runtime::RuntimeConfigManager;
+use crate::extension;
+use crate::system::StaticSystemManager;
+use super::{Renderer, RenderResult};
+use super::default::DefaultRenderer;
+
+/// Render the match with the given trigger using the config the daemon would select
+/// for the given window, without querying the windowing system.
+/// Return the name of the selected config along with the result.
+pub fn render_trigger(config_set: ConfigSet, trigger: &str, args: Vec<String>,
+                      window: StaticSystemManager) -> Result<(String, RenderResult), String> {
+    let config_manager = RuntimeConfigManager::new(config_set, window);
+    let config = config_manager.active_config();
+
+    let m = config.matches.iter().find(|m| {
+        m.trigger == trigger && config_manager.is_match_active(m)
+    });
+    let m = match m {
+        Some(m) => m,
+        None => return Err(format!("No match found with trigger '{}' in config '{}'", trigger, config.name)),
+    };
+
+    let renderer = DefaultRenderer::new(extension::get_extensions(),
+                                        config_manager.default_config().clone());
+
+    Ok((config.name.clone(), renderer.render_match(m, config, args)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::tests::{create_temp_espanso_directories_with_default_content, create_user_config_file};
+
+    fn load_config_set() -> ConfigSet {
+        let (data_dir, package_dir) = create_temp_espanso_directories_with_default_content(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+          - trigger: ":greet"
+            replace: "hello {{name}}"
+            vars:
+              - name: name
+                type: dummy
+                params:
+                  echo: "world"
+        "###);
+
+        create_user_config_file(data_dir.path(), "terminal.yml", r###"
+        name: terminal
+        filter_class: "Terminal"
+        matches:
+          - trigger: ":hi"
+            replace: "hello from the terminal"
+        "###);
+
+        create_user_config_file(data_dir.path(), "vim.yml", r###"
+        name: vim
+        filter_exec: "vim"
+        matches:
+          - trigger: ":wq"
+            replace: "save and quit"
+        "###);
+
+        create_user_config_file(data_dir.path(), "jira.yml", r###"
+        name: jira
+        filter_title: "Jira"
+        matches:
+          - trigger: ":bug"
+            replace: "Steps to reproduce"
+        "###);
+
+        ConfigSet::load(data_dir.path(), package_dir.path()).unwrap()
+    }
+
+    fn render_text(window: StaticSystemManager, trigger: &str) -> Result<(String, String), String> {
+        let (config, result) = render_trigger(load_config_set(), trigger, vec![], window)?;
+        match result {
+            RenderResult::Text(text) => Ok((config, text)),
+            _ => Err("not a text result".to_owned()),
+        }
+    }
+
+    fn window(title: Option<&str>, class: Option<&str>, exec: Option<&str>) -> StaticSystemManager {
+        StaticSystemManager::new(title.map(String::from), class.map(String::from), exec.map(String::from))
+    }
+
+    #[test]
+    fn test_render_default_config_without_app() {
+        let result = render_text(window(None, None, None), ":hi");
+        assert_eq!(result, Ok(("default".to_owned(), "hello".to_owned())));
+    }
+
+    #[test]
+    fn test_render_with_variables() {
+        let result = render_text(window(None, None, None), ":greet");
+        assert_eq!(result, Ok(("default".to_owned(), "hello world".to_owned())));
+    }
+
+    #[test]
+    fn test_render_app_class_selects_specific_config() {
+        let result = render_text(window(None, Some("Terminal"), None), ":hi");
+        assert_eq!(result, Ok(("terminal".to_owned(), "hello from the terminal".to_owned())));
+    }
+
+    #[test]
+    fn test_render_app_exec_selects_specific_matches() {
+        let result = render_text(window(None, None, Some("/usr/bin/vim")), ":wq");
+        assert_eq!(result, Ok(("vim".to_owned(), "save and quit".to_owned())));
+
+        // The match is only available in the specific config
+        assert!(render_text(window(None, None, Some("/usr/bin/nano")), ":wq").is_err());
+    }
+
+    #[test]
+    fn test_render_app_title_selects_specific_matches() {
+        let result = render_text(window(Some("PROJ-1 - Jira"), None, None), ":bug");
+        assert_eq!(result, Ok(("jira".to_owned(), "Steps to reproduce".to_owned())));
+
+        // Default matches are still available in the specific configs
+        let result = render_text(window(Some("PROJ-1 - Jira"), None, None), ":hi");
+        assert_eq!(result, Ok(("jira".to_owned(), "hello".to_owned())));
+    }
+
+    #[test]
+    fn test_render_unknown_trigger() {
+        assert!(render_text(window(None, None, None), ":missing").is_err());
+    }
+}
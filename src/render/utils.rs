@@ -21,6 +21,8 @@ use regex::{Regex, Captures};
 
 lazy_static! {
     static ref ARG_REGEX: Regex = Regex::new("\\$(?P<pos>\\d+)\\$").unwrap();
+    static ref HTML_BREAK_REGEX: Regex = Regex::new("(?i)<br\\s*/?>|</(p|div|li|h[1-6]|tr)>").unwrap();
+    static ref HTML_TAG_REGEX: Regex = Regex::new("<[^>]*>").unwrap();
 }
 
 pub fn render_args(text: &str, args: &Vec<String>) -> String {
@@ -72,6 +74,53 @@ pub fn split_args(text: &str, delimiter: char, escape: char) -> Vec<String> {
     output
 }
 
+/// Convert the given markdown text to HTML
+pub fn markdown_to_html(text: &str) -> String {
+    let parser = pulldown_cmark::Parser::new(text);
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, parser);
+    html.trim_end().to_owned()
+}
+
+/// Escape the given text so that it's inserted as is in an HTML document
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Escape the given text so that it's inserted as is in a markdown document
+pub fn escape_markdown(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_{}[]()#+-.!<>&|~".contains(c) {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+    output
+}
+
+/// Calculate a plain text version of the given HTML, used as a fallback by the
+/// applications that don't support rich text. This is not a complete HTML parser,
+/// but it handles the simple documents used in the matches.
+pub fn html_to_plain_text(html: &str) -> String {
+    // Line breaks and block elements are converted to newlines
+    let text = HTML_BREAK_REGEX.replace_all(html, "\n");
+    let text = HTML_TAG_REGEX.replace_all(&text, "");
+
+    let text = text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    text.trim_end().to_owned()
+}
+
 // TESTS
 
 #[cfg(test)]
@@ -127,4 +176,32 @@ mod tests {
         let empty_vec : Vec<String> = vec![];
         assert_eq!(split_args("", '/', '\\'), empty_vec)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_markdown_to_html() {
+        assert_eq!(markdown_to_html("Hello **world**"), "<p>Hello <strong>world</strong></p>");
+        assert_eq!(markdown_to_html("[espanso](https://espanso.org)"),
+                   "<p><a href=\"https://espanso.org\">espanso</a></p>");
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<b>Tom & \"Jerry's\"</b>"),
+                   "&lt;b&gt;Tom &amp; &quot;Jerry&#39;s&quot;&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_escape_markdown() {
+        assert_eq!(escape_markdown("**bold** <i>"), "\\*\\*bold\\*\\* \\<i\\>");
+        assert_eq!(markdown_to_html(&escape_markdown("*a* & <script>")),
+                   "<p>*a* &amp; &lt;script&gt;</p>");
+    }
+
+    #[test]
+    fn test_html_to_plain_text() {
+        assert_eq!(html_to_plain_text("<b>Hello</b> <i>world</i>"), "Hello world");
+        assert_eq!(html_to_plain_text("first<br>second<BR/>third"), "first\nsecond\nthird");
+        assert_eq!(html_to_plain_text("<p>one</p><p>two</p>"), "one\ntwo");
+        assert_eq!(html_to_plain_text("Tom &amp; Jerry &lt;3"), "Tom & Jerry <3");
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Entries of the snippet search picker, the fuzzy matching is left to the picker
+
+pub(crate) mod picker;
+
+use crate::matcher::{Match, MatchContentType};
+
+// Maximum number of chars of the replacement shown in the picker
+const PREVIEW_LENGTH: usize = 60;
+
+/// A match as shown in the picker, one per line
+pub struct SearchEntry<'a> {
+    pub line: String,
+    pub m: &'a Match,
+}
+
+impl <'a> SearchEntry<'a> {
+    pub fn new(m: &'a Match) -> SearchEntry<'a> {
+        let name = match &m.hotkey {
+            Some(hotkey) if m.trigger.is_empty() => hotkey.to_string(),
+            _ => m.trigger.clone(),
+        };
+
+        // The label replaces the content preview, the description and tags are
+        // included so that they can be searched too
+        let mut line = match &m.label {
+            Some(label) => format!("{} - {}", name, label),
+            None => format!("{} - {}", name, preview(m)),
+        };
+        if let Some(description) = &m.description {
+            line.push_str(&format!(": {}", description.lines().next().unwrap_or_default()));
+        }
+        for tag in m.tags.iter() {
+            line.push_str(&format!(" #{}", tag));
+        }
+
+        SearchEntry {
+            line,
+            m,
+        }
+    }
+}
+
+/// First line of the replacement, shortened to fit in the picker
+fn preview(m: &Match) -> String {
+    let content = match &m.content {
+        MatchContentType::Text(content) | MatchContentType::Html(content) |
+        MatchContentType::Markdown(content) => content.replace.clone(),
+        MatchContentType::Image(content) => format!("[image] {}", content.path.to_string_lossy()),
+    };
+
+    let first_line = content.lines().next().unwrap_or_default();
+    let mut preview: String = first_line.chars().take(PREVIEW_LENGTH).collect();
+    if first_line.chars().count() > PREVIEW_LENGTH || content.lines().count() > 1 {
+        preview.push_str("...");
+    }
+    preview
+}
+
+/// Find the entry chosen in the picker. Pickers like dmenu return the typed query
+/// when it doesn't correspond to a line, in that case nothing is expanded.
+pub fn resolve<'a, 'b>(entries: &'b [SearchEntry<'a>], output: &str) -> Option<&'b SearchEntry<'a>> {
+    let output = output.trim_end_matches(|c: char| c == '\n' || c == '\r');
+    if output.trim().is_empty() {
+        return None;
+    }
+
+    entries.iter().find(|entry| entry.line == output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_matches(yaml: &str) -> Vec<Match> {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_search_entry_line() {
+        let matches = create_matches(r###"
+        - trigger: ":sig"
+          replace: "Best regards,\nJon"
+        - hotkey: "ctrl+alt+e"
+          replace: "jon@example.com"
+        - trigger: ":addr"
+          replace: "Main Street 1"
+          label: "Address"
+          description: "Office address"
+          tags: ["work", "contact"]
+        "###);
+
+        assert_eq!(SearchEntry::new(&matches[0]).line, ":sig - Best regards,...");
+        assert_eq!(SearchEntry::new(&matches[1]).line, "ctrl+alt+e - jon@example.com");
+        assert_eq!(SearchEntry::new(&matches[2]).line, ":addr - Address: Office address #work #contact");
+    }
+
+    #[test]
+    fn test_resolve_picker_output() {
+        let matches = create_matches(r###"
+        - trigger: ":addr"
+          replace: "Main Street 1"
+        - trigger: ":phone"
+          replace: "555-1234"
+        "###);
+        let entries: Vec<SearchEntry> = matches.iter().map(SearchEntry::new).collect();
+
+        assert_eq!(resolve(&entries, ":phone - 555-1234\n").unwrap().m.trigger, ":phone");
+        assert_eq!(resolve(&entries, ":addr - Main Street 1").unwrap().m.trigger, ":addr");
+
+        // Typed queries that don't correspond to a line are not expanded
+        assert!(resolve(&entries, "phn").is_none());
+        assert!(resolve(&entries, ":phone").is_none());
+        assert!(resolve(&entries, "").is_none());
+        assert!(resolve(&entries, "\n").is_none());
+        assert!(resolve(&entries, "xyz").is_none());
+    }
+}
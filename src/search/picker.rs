@@ -0,0 +1,123 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use log::{error, info};
+
+/// Run the picker command, passing the entries one per line through its stdin.
+/// Returns the picker output, None if it was dismissed or it could not be started.
+pub fn run_picker_command(command: &str, entries: &[String]) -> Option<String> {
+    let child = if cfg!(target_os = "windows") {
+        Command::new("cmd")
+            .args(&["/C", command])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+    } else {
+        Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+    };
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            error!("Unable to start the search picker '{}': {}", command, e);
+            return None;
+        },
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = entries.join("\n");
+
+        // The picker could exit before reading all the entries, so errors are only logged
+        if let Err(e) = stdin.write_all(input.as_bytes()) {
+            info!("Unable to send all the entries to the search picker: {}", e);
+        }
+    }
+
+    match child.wait_with_output() {
+        Ok(output) => {
+            // Pickers exit with an error code when dismissed
+            if output.status.success() {
+                Some(String::from_utf8_lossy(&output.stdout).into_owned())
+            }else{
+                None
+            }
+        },
+        Err(e) => {
+            error!("Search picker '{}' failed: {}", command, e);
+            None
+        },
+    }
+}
+
+/// The first picker available on the system, used when search_command is not set
+#[cfg(target_os = "linux")]
+pub fn default_command() -> Option<String> {
+    let mut candidates = Vec::new();
+    if crate::clipboard::is_wayland_session() {
+        candidates.push(("wofi", "wofi --dmenu -i -p espanso"));
+    }
+    candidates.push(("rofi", "rofi -dmenu -i -p espanso"));
+    candidates.push(("dmenu", "dmenu -i -l 20 -p espanso"));
+
+    candidates.into_iter()
+        .find(|(name, _)| is_command_available(name))
+        .map(|(_, command)| command.to_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn default_command() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn is_command_available(name: &str) -> bool {
+    Command::new("sh")
+        .arg("-c")
+        .arg(format!("command -v {}", name))
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_run_picker_command_returns_output() {
+        let entries = vec![":addr - Main Street".to_owned(), ":phone - 555-1234".to_owned()];
+        let output = run_picker_command("grep phone", &entries);
+        assert_eq!(output, Some(":phone - 555-1234\n".to_owned()));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_run_picker_command_dismissed() {
+        let entries = vec![":addr - Main Street".to_owned()];
+        assert_eq!(run_picker_command("exit 1", &entries), None);
+    }
+}
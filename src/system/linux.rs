@@ -19,8 +19,13 @@
 
 use std::os::raw::c_char;
 
-use crate::bridge::linux::{get_active_window_name, get_active_window_class, get_active_window_executable};
+use crate::bridge::linux::{get_active_window_name, get_active_window_class, get_active_window_executable,
+                           start_secure_input_monitor, is_password_field_focused};
 use std::ffi::CStr;
+use std::sync::Once;
+use log::warn;
+
+static SECURE_INPUT_MONITOR: Once = Once::new();
 
 pub struct LinuxSystemManager {}
 
@@ -78,10 +83,32 @@ impl super::SystemManager for LinuxSystemManager {
 
         None
     }
+
+    fn is_secure_input_focused(&self) -> bool {
+        self.start_secure_input_monitor();
+
+        unsafe {
+            is_password_field_focused() > 0
+        }
+    }
 }
 
 impl LinuxSystemManager {
     pub fn new() -> LinuxSystemManager {
         LinuxSystemManager{}
     }
+
+    /// The AT-SPI monitor is started the first time it's needed, as it's disabled by default
+    fn start_secure_input_monitor(&self) {
+        SECURE_INPUT_MONITOR.call_once(|| {
+            std::thread::Builder::new().name("secure_input_monitor".to_string()).spawn(|| {
+                let res = unsafe { start_secure_input_monitor() };
+                if res == -1 {
+                    warn!("secure_input_atspi is enabled, but espanso was built without the 'atspi' feature");
+                }else if res < 0 {
+                    warn!("Unable to monitor the focused password fields through AT-SPI: {}", res);
+                }
+            }).expect("Unable to spawn secure input monitor thread");
+        });
+    }
 }
\ No newline at end of file
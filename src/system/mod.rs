@@ -30,6 +30,43 @@ pub trait SystemManager {
     fn get_current_window_title(&self) -> Option<String>;
     fn get_current_window_class(&self) -> Option<String>;
     fn get_current_window_executable(&self) -> Option<String>;
+
+    // Check if the focused element is a password field, on the platforms able to detect it
+    fn is_secure_input_focused(&self) -> bool {
+        false
+    }
+}
+
+/// SystemManager returning fixed window properties, used to simulate
+/// an application without querying the windowing system.
+pub struct StaticSystemManager {
+    title: Option<String>,
+    class: Option<String>,
+    exec: Option<String>,
+}
+
+impl StaticSystemManager {
+    pub fn new(title: Option<String>, class: Option<String>, exec: Option<String>) -> StaticSystemManager {
+        StaticSystemManager {
+            title,
+            class,
+            exec,
+        }
+    }
+}
+
+impl SystemManager for StaticSystemManager {
+    fn get_current_window_title(&self) -> Option<String> {
+        self.title.clone()
+    }
+
+    fn get_current_window_class(&self) -> Option<String> {
+        self.class.clone()
+    }
+
+    fn get_current_window_executable(&self) -> Option<String> {
+        self.exec.clone()
+    }
 }
 
 // LINUX IMPLEMENTATION
@@ -0,0 +1,286 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use regex::{Regex, Captures};
+use crate::keyboard::CursorKey;
+
+lazy_static! {
+    // The legacy $|$ cursor hint is equivalent to the $0 tab stop.
+    // $$ is used to escape a literal dollar sign when numbered tab stops are enabled.
+    static ref LEGACY_HINT_REGEX: Regex = Regex::new("\\$\\|\\$").unwrap();
+    static ref TAB_STOP_REGEX: Regex = Regex::new("\\$\\|\\$|\\$\\$|\\$(?P<index>\\d+)").unwrap();
+}
+
+const LEGACY_HINT_INDEX : usize = 0;
+
+/// Position inside the injected text, the column is measured in chars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Remove the tab stops from the given text, returning the cleaned text and the positions
+/// of the stops in the order they should be visited: $1, $2, ..., and $0 as the last one.
+/// Numbered tab stops are parsed only if enabled, otherwise only the $|$ hint is considered.
+pub fn extract_tab_stops(text: &str, numbered: bool) -> (String, Vec<Position>) {
+    let regex : &Regex = if numbered { &TAB_STOP_REGEX } else { &LEGACY_HINT_REGEX };
+
+    let mut stops : Vec<(usize, usize)> = Vec::new();  // Index and char offset
+    let mut output = String::new();
+    let mut last_end = 0;
+    for caps in regex.captures_iter(text) {
+        let whole : regex::Match = caps.get(0).unwrap();
+        output.push_str(&text[last_end..whole.start()]);
+        last_end = whole.end();
+
+        if whole.as_str() == "$$" {
+            output.push('$');
+            continue;
+        }
+
+        let index = parse_index(&caps);
+
+        // Only the first occurrence of each tab stop is considered
+        if !stops.iter().any(|(i, _)| *i == index) {
+            stops.push((index, output.chars().count()));
+        }
+    }
+    output.push_str(&text[last_end..]);
+
+    // Visit $1, $2, ... in order, and $0 as the last one
+    stops.sort_by_key(|(index, _)| if *index == 0 { usize::max_value() } else { *index });
+
+    let positions = stops.into_iter().map(|(_, offset)| {
+        position_of(&output, offset)
+    }).collect();
+
+    (output, positions)
+}
+
+fn parse_index(caps: &Captures) -> usize {
+    match caps.name("index") {
+        Some(index) => index.as_str().parse().unwrap_or(LEGACY_HINT_INDEX),
+        None => LEGACY_HINT_INDEX,
+    }
+}
+
+fn position_of(text: &str, char_offset: usize) -> Position {
+    let mut line = 0;
+    let mut column = 0;
+    for c in text.chars().take(char_offset) {
+        if c == '\n' {
+            line += 1;
+            column = 0;
+        }else{
+            column += 1;
+        }
+    }
+
+    Position { line, column }
+}
+
+/// Keep track of the cursor while the user jumps between the tab stops of the last expansion.
+pub struct TabStopSession {
+    stops: Vec<Position>,
+    line_lengths: Vec<usize>,
+    current: Position,
+    next_stop: usize,
+}
+
+impl TabStopSession {
+    /// Create a session for the given injected text, the cursor is expected at the end of it
+    pub fn new(text: &str, stops: Vec<Position>) -> TabStopSession {
+        let line_lengths : Vec<usize> = text.split('\n').map(|line| line.chars().count()).collect();
+        let current = Position {
+            line: line_lengths.len() - 1,
+            column: *line_lengths.last().unwrap(),
+        };
+
+        TabStopSession {
+            stops,
+            line_lengths,
+            current,
+            next_stop: 0,
+        }
+    }
+
+    /// Return true if there are still tab stops to visit
+    pub fn has_next(&self) -> bool {
+        self.next_stop < self.stops.len()
+    }
+
+    /// Record that the user typed (or deleted, if negative) some chars at the current position.
+    pub fn on_typed(&mut self, count: i32) {
+        let line = self.current.line;
+        let column = self.current.column;
+
+        let shift = |value: usize| (value as i32 + count).max(0) as usize;
+
+        // Shift the following stops on the same line
+        for stop in self.stops[self.next_stop..].iter_mut() {
+            if stop.line == line && stop.column >= column {
+                stop.column = shift(stop.column);
+            }
+        }
+
+        self.line_lengths[line] = shift(self.line_lengths[line]);
+        self.current.column = shift(column);
+    }
+
+    /// Return the key presses needed to reach the next tab stop, or None if there are no more.
+    pub fn next(&mut self) -> Option<Vec<(CursorKey, i32)>> {
+        if !self.has_next() {
+            return None;
+        }
+
+        let target = self.stops[self.next_stop];
+        self.next_stop += 1;
+
+        let movements = calculate_movements(self.current, target, &self.line_lengths);
+        self.current = target;
+        Some(movements)
+    }
+}
+
+/// Calculate the key presses needed to move the cursor between two positions of the injected
+/// text. The first line of the text may not start at the beginning of the line in the editor,
+/// so it is always reached from the line below.
+fn calculate_movements(from: Position, to: Position, line_lengths: &[usize]) -> Vec<(CursorKey, i32)> {
+    let mut movements = Vec::new();
+
+    if from.line == to.line {
+        if to.column < from.column {
+            movements.push((CursorKey::Left, (from.column - to.column) as i32));
+        }else if to.column > from.column {
+            movements.push((CursorKey::Right, (to.column - from.column) as i32));
+        }
+    }else if to.line < from.line {
+        // Go to the start of the line below the target, then to the end of the target line
+        movements.push((CursorKey::Left, from.column as i32));
+        if from.line - to.line > 1 {
+            movements.push((CursorKey::Up, (from.line - to.line - 1) as i32));
+        }
+        movements.push((CursorKey::Left, (1 + line_lengths[to.line] - to.column) as i32));
+    }else{
+        // Go to the start of the line after the current one, then down to the target line
+        movements.push((CursorKey::Right, (line_lengths[from.line] - from.column + 1) as i32));
+        if to.line - from.line > 1 {
+            movements.push((CursorKey::Down, (to.line - from.line - 1) as i32));
+        }
+        if to.column > 0 {
+            movements.push((CursorKey::Right, to.column as i32));
+        }
+    }
+
+    // Remove the empty movements
+    movements.into_iter().filter(|(_, count)| *count > 0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+
+    #[test]
+    fn test_extract_legacy_cursor_hint() {
+        let (text, stops) = extract_tab_stops("Hello $|$world", false);
+        assert_eq!(text, "Hello world");
+        assert_eq!(stops, vec![pos(0, 6)]);
+    }
+
+    #[test]
+    fn test_extract_numbered_disabled() {
+        let (text, stops) = extract_tab_stops("It costs $1", false);
+        assert_eq!(text, "It costs $1");
+        assert!(stops.is_empty());
+    }
+
+    #[test]
+    fn test_extract_numbered_tab_stops() {
+        let (text, stops) = extract_tab_stops("Dear $1,\n$0\nBest, $2", true);
+        assert_eq!(text, "Dear ,\n\nBest, ");
+        assert_eq!(stops, vec![pos(0, 5), pos(2, 6), pos(1, 0)]);
+    }
+
+    #[test]
+    fn test_extract_escaped_dollar() {
+        let (text, stops) = extract_tab_stops("Price: $$10 $1", true);
+        assert_eq!(text, "Price: $10 ");
+        assert_eq!(stops, vec![pos(0, 11)]);
+    }
+
+    #[test]
+    fn test_extract_duplicated_tab_stop() {
+        let (text, stops) = extract_tab_stops("$1 and $1", true);
+        assert_eq!(text, " and ");
+        assert_eq!(stops, vec![pos(0, 0)]);
+    }
+
+    #[test]
+    fn test_session_single_line() {
+        let (text, stops) = extract_tab_stops("Hello $|$world", false);
+        let mut session = TabStopSession::new(&text, stops);
+        assert_eq!(session.next(), Some(vec![(CursorKey::Left, 5)]));
+        assert!(!session.has_next());
+        assert_eq!(session.next(), None);
+    }
+
+    #[test]
+    fn test_session_moves_up_to_first_line() {
+        let (text, stops) = extract_tab_stops("Hi $1!\nsecond\nthird", true);
+        let mut session = TabStopSession::new(&text, stops);
+
+        // From the end of "third" to the start of "second", then to the end of the first line
+        // and back to the tab stop
+        assert_eq!(session.next(), Some(vec![
+            (CursorKey::Left, 5),
+            (CursorKey::Up, 1),
+            (CursorKey::Left, 2),
+        ]));
+    }
+
+    #[test]
+    fn test_session_moves_down() {
+        let (text, stops) = extract_tab_stops("Hi $1!\nsecond\nthird $2", true);
+        let mut session = TabStopSession::new(&text, stops);
+        session.next();
+
+        // From the first line to the start of "second", then down to "third"
+        assert_eq!(session.next(), Some(vec![
+            (CursorKey::Right, 2),
+            (CursorKey::Down, 1),
+            (CursorKey::Right, 6),
+        ]));
+    }
+
+    #[test]
+    fn test_session_typed_chars_shift_stops_on_the_same_line() {
+        let (text, stops) = extract_tab_stops("$1 and $2", true);
+        let mut session = TabStopSession::new(&text, stops);
+        assert_eq!(session.next(), Some(vec![(CursorKey::Left, 5)]));
+
+        session.on_typed(3);
+
+        assert_eq!(session.next(), Some(vec![(CursorKey::Right, 5)]));
+    }
+}
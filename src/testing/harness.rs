@@ -0,0 +1,698 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fs;
+use tempfile::TempDir;
+use crate::config::{ConfigSet, DEFAULT_CONFIG_FILE_NAME, USER_CONFIGS_FOLDER_NAME};
+use crate::event::{Event, KeyEvent, KeyModifier};
+use crate::extension;
+use super::run_with_mocks;
+use super::mock::*;
+
+// Options prepended to the default config, unless already specified by the test.
+// Without the first, the engine would ignore the events sent right after its creation,
+// while the second makes the recorded actions independent of the platform default.
+const HARNESS_DEFAULT_OPTIONS : &[(&str, &str)] = &[
+    ("action_noop_interval", "0"),
+    ("backend", "Inject"),
+];
+
+/// Run the matcher and the engine against the mock backends, with the given configs,
+/// recording all the actions espanso performs in response to the simulated events.
+pub struct EngineHarness {
+    data_dir: TempDir,
+    package_dir: TempDir,
+    pub recorder: Recorder,
+    pub clipboard: MockClipboardManager,
+    pub system: MockSystemManager,
+    pub ui: MockUIManager,
+    pub history: MockHistoryManager,
+}
+
+impl EngineHarness {
+    pub fn new(default_config: &str) -> EngineHarness {
+        let data_dir = TempDir::new().expect("unable to create data directory");
+        let package_dir = TempDir::new().expect("unable to create package directory");
+
+        let mut default_config = unindent(default_config);
+        for (key, value) in HARNESS_DEFAULT_OPTIONS {
+            let is_specified = default_config.lines()
+                .any(|line| line.starts_with(&format!("{}:", key)));
+            if !is_specified {
+                default_config = format!("{}: {}\n{}", key, value, default_config);
+            }
+        }
+        fs::write(data_dir.path().join(DEFAULT_CONFIG_FILE_NAME), default_config)
+            .expect("unable to write default config");
+
+        let recorder = Recorder::new();
+        let clipboard = MockClipboardManager::new(recorder.clone());
+        let ui = MockUIManager::new(recorder.clone());
+
+        EngineHarness {
+            data_dir,
+            package_dir,
+            recorder,
+            clipboard,
+            system: MockSystemManager::new(),
+            ui,
+            history: MockHistoryManager::new(),
+        }
+    }
+
+    pub fn add_user_config(&self, name: &str, content: &str) {
+        let user_dir = self.data_dir.path().join(USER_CONFIGS_FOLDER_NAME);
+        fs::create_dir_all(&user_dir).expect("unable to create user config directory");
+        fs::write(user_dir.join(name), content).expect("unable to write user config");
+    }
+
+    /// Deliver the events as the daemon would, returning the actions they caused
+    pub fn run(&self, events: Vec<Event>) -> Vec<RecordedAction> {
+        let config_set = ConfigSet::load(self.data_dir.path(), self.package_dir.path())
+            .expect("unable to load harness configs");
+
+        self.recorder.clear();
+        run_with_mocks(config_set, &self.system, &self.recorder, &self.clipboard, &self.ui,
+                       &self.history, extension::get_extensions(), |event_manager, _| {
+            for event in events {
+                event_manager.dispatch(event);
+            }
+        });
+
+        self.recorder.actions()
+    }
+
+    /// Queue the events and run the daemon event loop, returning the actions they caused.
+    /// The loop returns when a reload is requested, it panics if the events run out before.
+    pub fn run_until_reload(&self, events: Vec<Event>) -> Vec<RecordedAction> {
+        let config_set = ConfigSet::load(self.data_dir.path(), self.package_dir.path())
+            .expect("unable to load harness configs");
+
+        self.recorder.clear();
+        run_with_mocks(config_set, &self.system, &self.recorder, &self.clipboard, &self.ui,
+                       &self.history, extension::get_extensions(), |event_manager, send_channel| {
+            for event in events {
+                send_channel.send(event).expect("unable to queue harness event");
+            }
+            drop(send_channel);
+
+            event_manager.eventloop();
+        });
+
+        self.recorder.actions()
+    }
+}
+
+/// Remove the indentation shared by all the lines, so that the harness defaults
+/// can be prepended to configs written inline in the tests.
+fn unindent(content: &str) -> String {
+    let indent = content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    content.lines()
+        .map(|line| if line.len() >= indent { &line[indent..] } else { "" })
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Key events generated by typing the given text, one char at a time
+pub fn type_text(text: &str) -> Vec<Event> {
+    text.chars().map(|c| Event::Key(KeyEvent::Char(c.to_string()))).collect()
+}
+
+pub fn press(modifier: KeyModifier) -> Event {
+    Event::Key(KeyEvent::Modifier(modifier))
+}
+
+pub fn release(modifier: KeyModifier) -> Event {
+    Event::Key(KeyEvent::ModifierRelease(modifier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keyboard::PasteShortcut;
+    use crate::clipboard::{ClipboardSnapshot, ClipboardEntry};
+    use crate::event::{ActionType, SystemEvent};
+    use crate::history::ExpansionSource;
+    use crate::config::BackendType;
+
+    #[test]
+    fn test_simple_expansion() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        let actions = harness.run(type_text("abc :hi"));
+        assert_eq!(actions, vec![
+            RecordedAction::Delete(3),
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_word_expansion_keeps_trailing_separator() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: "hi"
+            replace: "hello"
+            word: true
+        "###);
+
+        let actions = harness.run(type_text("this hi "));
+        assert_eq!(actions, vec![
+            RecordedAction::Delete(3),
+            RecordedAction::SendString("hello ".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_backspace_cancels_match() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        let mut events = type_text(":h");
+        events.push(press(KeyModifier::BACKSPACE));
+        events.extend(type_text("i"));
+
+        assert!(harness.run(events).is_empty());
+    }
+
+    #[test]
+    fn test_clipboard_backend_preserves_clipboard() {
+        let harness = EngineHarness::new(r###"
+        backend: Clipboard
+        restore_clipboard_delay: 0
+        preserve_clipboard: true
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+        harness.clipboard.set_content("previous");
+
+        let actions = harness.run(type_text(":hi"));
+        assert_eq!(actions, vec![
+            RecordedAction::Delete(3),
+            RecordedAction::SetClipboard("hello".to_owned()),
+            RecordedAction::Paste(PasteShortcut::Default),
+            RecordedAction::RestoreClipboard(ClipboardSnapshot {
+                entries: vec![ClipboardEntry {
+                    format: "text/plain".to_owned(),
+                    data: b"previous".to_vec(),
+                }],
+            }),
+        ]);
+    }
+
+    #[test]
+    fn test_sequence_restores_clipboard_once() {
+        let image = tempfile::NamedTempFile::new().unwrap();
+        let harness = EngineHarness::new(&format!(r###"
+        backend: Inject
+        restore_clipboard_delay: 0
+        preserve_clipboard: true
+        matches:
+          - trigger: ":logo"
+            image_path: "{}"
+          - trigger: ":sig"
+            replace: "Regards,\n{{{{logo}}}}\nJon"
+            vars:
+              - name: logo
+                type: match
+                params:
+                  trigger: ":logo"
+        "###, image.path().to_string_lossy()));
+        harness.clipboard.set_content("previous");
+
+        let actions = harness.run(type_text(":sig"));
+        assert_eq!(actions, vec![
+            RecordedAction::Delete(4),
+            RecordedAction::SendString("Regards,\n".to_owned()),
+            RecordedAction::SetClipboardImage(image.path().to_path_buf()),
+            RecordedAction::Paste(PasteShortcut::Default),
+            RecordedAction::SendString("\nJon".to_owned()),
+            RecordedAction::RestoreClipboard(ClipboardSnapshot {
+                entries: vec![ClipboardEntry {
+                    format: "text/plain".to_owned(),
+                    data: b"previous".to_vec(),
+                }],
+            }),
+        ]);
+
+        // The text fragments were typed
+        assert_eq!(harness.history.entries()[0].backend, BackendType::Inject);
+    }
+
+    #[test]
+    fn test_app_specific_config() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+        harness.add_user_config("terminal.yml", r###"
+        filter_class: "Terminal"
+        backend: Clipboard
+        "###);
+
+        let actions = harness.run(type_text(":hi"));
+        assert_eq!(actions[1], RecordedAction::SendString("hello".to_owned()));
+
+        harness.system.set_window("Shell", "Terminal", "terminal");
+        let actions = harness.run(type_text(":hi"));
+        assert_eq!(actions[1], RecordedAction::SetClipboard("hello".to_owned()));
+    }
+
+    #[test]
+    fn test_disabled_engine_ignores_triggers() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        let mut events = vec![Event::Action(ActionType::Toggle)];
+        events.extend(type_text(":hi"));
+
+        let actions = harness.run(events);
+        assert_eq!(actions, vec![
+            RecordedAction::Notify("espanso disabled".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_reload_gesture_ends_the_event_loop() {
+        let harness = EngineHarness::new(r###"
+        gestures:
+          - gesture: "double ctrl"
+            action: reload_config
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        // The daemon rebuilds the event loop after the reload, so the keys queued
+        // behind the gesture are not handled by this one
+        let mut events = vec![press(KeyModifier::CTRL), press(KeyModifier::CTRL)];
+        events.extend(type_text(":hi"));
+        assert!(harness.run_until_reload(events).is_empty());
+    }
+
+    #[test]
+    fn test_password_prompt_suspends_matching() {
+        let harness = EngineHarness::new(r###"
+        disable_in: ["keepassxc"]
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        harness.system.set_window("Passphrase", "Pinentry-gtk-2", "/usr/bin/pinentry-gtk-2");
+        assert!(harness.run(type_text(":hi")).is_empty());
+
+        // Titles are only considered with the secure_input_title_filters option
+        harness.system.set_window("Enter your password", "Dialog", "polkit-agent");
+        assert_eq!(harness.run(type_text(":hi")).len(), 2);
+
+        harness.system.set_window("Database", "KeePassXC", "/usr/bin/keepassxc");
+        assert!(harness.run(type_text(":hi")).is_empty());
+
+        harness.system.set_window("Editor", "Code", "/usr/bin/code");
+        assert_eq!(harness.run(type_text(":hi")).len(), 2);
+    }
+
+    #[test]
+    fn test_password_title_suspends_matching_when_enabled() {
+        let harness = EngineHarness::new(r###"
+        secure_input_title_filters: ["(?i)password"]
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        harness.system.set_window("Enter your password", "Dialog", "polkit-agent");
+        assert!(harness.run(type_text(":hi")).is_empty());
+    }
+
+    fn expand_event(trigger: &str) -> Event {
+        Event::System(SystemEvent::ExpandMatch(trigger.to_owned(), vec![]))
+    }
+
+    #[test]
+    fn test_ipc_expand_match() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        assert_eq!(harness.run(vec![expand_event(":hi")]), vec![
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+
+        let history = harness.history.entries();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].trigger, ":hi");
+        assert_eq!(history[0].source, ExpansionSource::Ipc);
+    }
+
+    #[test]
+    fn test_ipc_expand_rejects_empty_trigger() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - hotkey: "ctrl+alt+e"
+            replace: "hello"
+        "###);
+
+        assert!(harness.run(vec![expand_event("")]).is_empty());
+        assert!(harness.history.entries().is_empty());
+    }
+
+    #[test]
+    fn test_ipc_expand_ignored_while_disabled() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        let events = vec![
+            Event::Action(ActionType::Disable),
+            expand_event(":hi"),
+            Event::System(SystemEvent::InjectText("secret".to_owned())),
+        ];
+        assert_eq!(harness.run(events), vec![
+            RecordedAction::Notify("espanso disabled".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_ipc_injection_suspended_in_password_prompt() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        harness.system.set_window("Unlock", "pinentry", "/usr/bin/pinentry-gtk-2");
+        let events = vec![
+            expand_event(":hi"),
+            Event::System(SystemEvent::InjectText("secret".to_owned())),
+        ];
+        assert!(harness.run(events).is_empty());
+        assert!(harness.history.entries().is_empty());
+    }
+
+    #[test]
+    fn test_ipc_inject_text_recorded_in_history() {
+        let harness = EngineHarness::new("");
+
+        let actions = harness.run(vec![Event::System(SystemEvent::InjectText("hello".to_owned()))]);
+        assert_eq!(actions, vec![
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+
+        let history = harness.history.entries();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].length, 5);
+        assert_eq!(history[0].source, ExpansionSource::Inject);
+    }
+
+    #[test]
+    fn test_ipc_inject_text_is_not_parsed_for_tab_stops() {
+        let harness = EngineHarness::new(r###"
+        enable_tab_stops: true
+        "###);
+
+        let text = "costs $1, cursor $|$ here";
+        let actions = harness.run(vec![Event::System(SystemEvent::InjectText(text.to_owned()))]);
+        assert_eq!(actions, vec![
+            RecordedAction::SendString(text.to_owned()),
+        ]);
+        assert_eq!(harness.history.entries()[0].length, text.chars().count());
+    }
+
+    #[test]
+    fn test_hotkey_expands_without_deleting() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - hotkey: "ctrl+alt+e"
+            replace: "hello"
+        "###);
+
+        // Holding ctrl turns the 'e' into a control code on Linux
+        let events = vec![
+            press(KeyModifier::CTRL),
+            press(KeyModifier::ALT),
+            Event::Key(KeyEvent::Char("\u{5}".to_owned())),
+        ];
+        assert_eq!(harness.run(events), vec![
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+
+        let history = harness.history.entries();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].trigger, "CTRL+ALT+e");
+        assert_eq!(history[0].source, ExpansionSource::Hotkey);
+
+        // The key alone, or with other modifiers, is just typed text
+        assert!(harness.run(type_text("e")).is_empty());
+        assert!(harness.run(vec![press(KeyModifier::CTRL), Event::Key(KeyEvent::Char("e".to_owned()))]).is_empty());
+    }
+
+    #[test]
+    fn test_hotkey_held_until_released() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - hotkey: "ctrl+alt+e"
+            replace: "hello"
+        "###);
+
+        // Once a release is reported, the modifiers are held until released
+        let events = vec![
+            press(KeyModifier::SHIFT),
+            release(KeyModifier::SHIFT),
+            press(KeyModifier::CTRL),
+            press(KeyModifier::ALT),
+            Event::Key(KeyEvent::Char("\u{5}".to_owned())),
+            Event::Key(KeyEvent::Char("\u{5}".to_owned())),
+            release(KeyModifier::ALT),
+            Event::Key(KeyEvent::Char("\u{5}".to_owned())),
+        ];
+        assert_eq!(harness.run(events), vec![
+            RecordedAction::SendString("hello".to_owned()),
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_hotkey_not_triggered_by_enter_without_ctrl() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - hotkey: "alt+meta+m"
+            replace: "hello"
+        "###);
+
+        let events = vec![
+            press(KeyModifier::ALT),
+            press(KeyModifier::META),
+            Event::Key(KeyEvent::Char("\r".to_owned())),
+        ];
+        assert!(harness.run(events).is_empty());
+    }
+
+    #[test]
+    fn test_toggle_key_double_press() {
+        let harness = EngineHarness::new(r###"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        // A third press doesn't toggle espanso back
+        let mut events = vec![press(KeyModifier::ALT), press(KeyModifier::ALT), press(KeyModifier::ALT)];
+        events.extend(type_text(":hi"));
+
+        assert_eq!(harness.run(events), vec![
+            RecordedAction::Notify("espanso disabled".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_press_gestures() {
+        let harness = EngineHarness::new(r###"
+        toggle_key: OFF
+        gestures:
+          - gesture: "double ctrl"
+            action: disable
+          - gesture: "triple shift"
+            action: enable
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        let events = vec![press(KeyModifier::CTRL), press(KeyModifier::CTRL)];
+        assert_eq!(harness.run(events), vec![
+            RecordedAction::Notify("espanso disabled".to_owned()),
+        ]);
+
+        // The triple press doesn't fire anything on the second press
+        let mut events = vec![press(KeyModifier::CTRL), press(KeyModifier::CTRL),
+                              press(KeyModifier::SHIFT), press(KeyModifier::SHIFT)];
+        events.extend(type_text(":hi"));
+        events.push(press(KeyModifier::SHIFT));
+        events.push(press(KeyModifier::SHIFT));
+        events.push(press(KeyModifier::SHIFT));
+        events.extend(type_text(":hi"));
+        assert_eq!(harness.run(events), vec![
+            RecordedAction::Notify("espanso disabled".to_owned()),
+            RecordedAction::Notify("espanso enabled".to_owned()),
+            RecordedAction::Delete(3),
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+
+        // Typing between the presses interrupts the sequence
+        let mut events = vec![press(KeyModifier::CTRL)];
+        events.extend(type_text("a"));
+        events.push(press(KeyModifier::CTRL));
+        assert!(harness.run(events).is_empty());
+    }
+
+    #[test]
+    fn test_chord_gesture_works_while_disabled() {
+        let harness = EngineHarness::new(r###"
+        gestures:
+          - gesture: "ctrl+shift+space"
+            action: toggle
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        let chord = vec![press(KeyModifier::CTRL), press(KeyModifier::SHIFT), Event::Key(KeyEvent::Char(" ".to_owned()))];
+        let mut events = chord.clone();
+        events.extend(type_text(":hi"));
+        events.extend(chord);
+        events.extend(type_text(":hi"));
+
+        assert_eq!(harness.run(events), vec![
+            RecordedAction::Notify("espanso disabled".to_owned()),
+            RecordedAction::Notify("espanso enabled".to_owned()),
+            RecordedAction::Delete(3),
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_hold_gesture() {
+        let harness = EngineHarness::new(r###"
+        hold_interval: 1000
+        gestures:
+          - gesture: "hold shift"
+            action: disable
+        "###);
+
+        let hold = |duration| Event::Key(KeyEvent::ModifierHold(KeyModifier::SHIFT, duration));
+
+        assert!(harness.run(vec![hold(300)]).is_empty());
+        assert_eq!(harness.run(vec![hold(1200)]), vec![
+            RecordedAction::Notify("espanso disabled".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_search_expands_chosen_match() {
+        let harness = EngineHarness::new(r###"
+        search_command: "picker"
+        matches:
+          - trigger: ":addr"
+            replace: "Main Street 1"
+          - trigger: ":phone"
+            replace: "555-1234"
+        "###);
+
+        harness.ui.set_picker_output(Some(":phone - 555-1234\n"));
+        assert_eq!(harness.run(vec![Event::Action(ActionType::Search)]), vec![
+            RecordedAction::ShowPicker(vec![":addr - Main Street 1".to_owned(), ":phone - 555-1234".to_owned()]),
+            RecordedAction::SendString("555-1234".to_owned()),
+        ]);
+
+        // Like dmenu, the picker returns the typed query if no line was selected
+        harness.ui.set_picker_output(Some("phn\n"));
+        assert_eq!(harness.run(vec![Event::Action(ActionType::Search)]).len(), 1);
+
+        // Dismissed picker
+        harness.ui.set_picker_output(None);
+        assert_eq!(harness.run(vec![Event::Action(ActionType::Search)]).len(), 1);
+    }
+
+    #[test]
+    fn test_keys_typed_in_search_picker_opened_by_gesture_are_discarded() {
+        let harness = EngineHarness::new(r###"
+        search_command: "picker"
+        gestures:
+          - gesture: "double ctrl"
+            action: search
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+        "###);
+
+        // The keys are queued while the picker blocks the event loop
+        harness.ui.set_picker_output(Some(":hi - hello\n"));
+        let mut events = vec![press(KeyModifier::CTRL), press(KeyModifier::CTRL)];
+        events.extend(type_text(":hi"));
+        events.push(Event::Action(ActionType::ReloadConfig));
+
+        assert_eq!(harness.run_until_reload(events), vec![
+            RecordedAction::ShowPicker(vec![":hi - hello".to_owned()]),
+            RecordedAction::SendString("hello".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_disabled_match_is_not_expanded_or_searched() {
+        let harness = EngineHarness::new(r###"
+        search_command: "picker"
+        matches:
+          - trigger: ":hi"
+            replace: "hello"
+            enabled: false
+          - trigger: ":bye"
+            replace: "goodbye"
+            label: "Farewell"
+        "###);
+
+        assert!(harness.run(type_text(":hi")).is_empty());
+
+        harness.ui.set_picker_output(None);
+        assert_eq!(harness.run(vec![Event::Action(ActionType::Search)]), vec![
+            RecordedAction::ShowPicker(vec![":bye - Farewell".to_owned()]),
+        ]);
+    }
+}
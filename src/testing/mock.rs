@@ -0,0 +1,323 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+use crate::keyboard::{KeyboardManager, PasteShortcut, CursorKey};
+use crate::clipboard::{ClipboardManager, ClipboardSnapshot, ClipboardEntry};
+use crate::ui::{UIManager, MenuItem};
+use crate::system::SystemManager;
+use crate::history::{HistoryManager, HistoryEntry};
+
+/// Action performed by espanso on the system
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedAction {
+    SendString(String),
+    SendEnter,
+    Delete(i32),
+    MoveCursor(CursorKey, i32),
+    Paste(PasteShortcut),
+    Copy,
+    SetClipboard(String),
+    SetClipboardImage(PathBuf),
+    SetClipboardHtml(String, String),
+    RestoreClipboard(ClipboardSnapshot),
+    Notify(String),
+    ShowMenu(Vec<String>),
+    ShowPicker(Vec<String>),
+}
+
+/// Shared log of the actions performed through the mocks, in the order they happened
+#[derive(Clone, Default)]
+pub struct Recorder {
+    actions: Rc<RefCell<Vec<RecordedAction>>>,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    pub fn record(&self, action: RecordedAction) {
+        self.actions.borrow_mut().push(action);
+    }
+
+    pub fn actions(&self) -> Vec<RecordedAction> {
+        self.actions.borrow().clone()
+    }
+
+    pub fn clear(&self) {
+        self.actions.borrow_mut().clear();
+    }
+}
+
+pub struct MockKeyboardManager {
+    recorder: Recorder,
+}
+
+impl MockKeyboardManager {
+    pub fn new(recorder: Recorder) -> MockKeyboardManager {
+        MockKeyboardManager { recorder }
+    }
+}
+
+impl KeyboardManager for MockKeyboardManager {
+    fn send_string(&self, s: &str) {
+        self.recorder.record(RecordedAction::SendString(s.to_owned()));
+    }
+
+    fn send_enter(&self) {
+        self.recorder.record(RecordedAction::SendEnter);
+    }
+
+    fn trigger_paste(&self, shortcut: &PasteShortcut) {
+        self.recorder.record(RecordedAction::Paste(shortcut.clone()));
+    }
+
+    fn delete_string(&self, count: i32) {
+        self.recorder.record(RecordedAction::Delete(count));
+    }
+
+    fn move_cursor(&self, key: CursorKey, count: i32) {
+        self.recorder.record(RecordedAction::MoveCursor(key, count));
+    }
+
+    fn trigger_copy(&self) {
+        self.recorder.record(RecordedAction::Copy);
+    }
+}
+
+/// Clipboard holding plain text only, the content is shared with the clones.
+/// Pastes are always confirmed immediately, so the tests don't have to wait.
+#[derive(Clone)]
+pub struct MockClipboardManager {
+    recorder: Recorder,
+    content: Rc<RefCell<Option<String>>>,
+}
+
+impl MockClipboardManager {
+    pub fn new(recorder: Recorder) -> MockClipboardManager {
+        MockClipboardManager {
+            recorder,
+            content: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Simulate a copy made by the user, without recording it
+    #[cfg(test)]
+    pub fn set_content(&self, content: &str) {
+        self.content.replace(Some(content.to_owned()));
+    }
+}
+
+impl ClipboardManager for MockClipboardManager {
+    fn get_clipboard(&self) -> Option<String> {
+        self.content.borrow().clone()
+    }
+
+    fn set_clipboard(&self, payload: &str) {
+        self.content.replace(Some(payload.to_owned()));
+        self.recorder.record(RecordedAction::SetClipboard(payload.to_owned()));
+    }
+
+    fn set_clipboard_image(&self, image_path: &Path) {
+        self.content.replace(None);
+        self.recorder.record(RecordedAction::SetClipboardImage(image_path.to_owned()));
+    }
+
+    fn set_clipboard_html(&self, html: &str, text_fallback: &str) {
+        self.content.replace(Some(text_fallback.to_owned()));
+        self.recorder.record(RecordedAction::SetClipboardHtml(html.to_owned(), text_fallback.to_owned()));
+    }
+
+    fn get_snapshot(&self) -> Option<ClipboardSnapshot> {
+        self.content.borrow().as_ref().map(|content| ClipboardSnapshot {
+            entries: vec![ClipboardEntry {
+                format: "text/plain".to_owned(),
+                data: content.as_bytes().to_vec(),
+            }],
+        })
+    }
+
+    fn restore_snapshot(&self, snapshot: &ClipboardSnapshot) {
+        let content = snapshot.entries.iter()
+            .find(|entry| entry.format == "text/plain")
+            .map(|entry| String::from_utf8_lossy(&entry.data).into_owned());
+        self.content.replace(content);
+        self.recorder.record(RecordedAction::RestoreClipboard(snapshot.clone()));
+    }
+
+    fn wait_for_paste(&self, _timeout: Duration) -> bool {
+        true
+    }
+}
+
+pub struct MockUIManager {
+    recorder: Recorder,
+    picker_output: Rc<RefCell<Option<String>>>,
+}
+
+impl MockUIManager {
+    pub fn new(recorder: Recorder) -> MockUIManager {
+        MockUIManager {
+            recorder,
+            picker_output: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Simulate the choice made by the user in the next pickers
+    #[cfg(test)]
+    pub fn set_picker_output(&self, output: Option<&str>) {
+        self.picker_output.replace(output.map(String::from));
+    }
+}
+
+impl UIManager for MockUIManager {
+    fn notify(&self, message: &str) {
+        self.recorder.record(RecordedAction::Notify(message.to_owned()));
+    }
+
+    fn show_menu(&self, menu: Vec<MenuItem>) {
+        let names = menu.into_iter().map(|item| item.item_name).collect();
+        self.recorder.record(RecordedAction::ShowMenu(names));
+    }
+
+    fn cleanup(&self) {}
+
+    fn pick(&self, _command: &str, entries: &[String]) -> Option<String> {
+        self.recorder.record(RecordedAction::ShowPicker(entries.to_vec()));
+        self.picker_output.borrow().clone()
+    }
+}
+
+/// Keep the recorded expansions in memory, the entries are shared with the clones
+#[derive(Clone, Default)]
+pub struct MockHistoryManager {
+    entries: Rc<RefCell<Vec<HistoryEntry>>>,
+}
+
+impl MockHistoryManager {
+    pub fn new() -> MockHistoryManager {
+        MockHistoryManager::default()
+    }
+
+    #[cfg(test)]
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.entries.borrow().clone()
+    }
+}
+
+impl HistoryManager for MockHistoryManager {
+    fn record(&self, entry: HistoryEntry) {
+        self.entries.borrow_mut().push(entry);
+    }
+}
+
+/// Window properties reported by the MockSystemManager
+#[derive(Debug, Clone, Default)]
+pub struct MockWindow {
+    pub title: Option<String>,
+    pub class: Option<String>,
+    pub exec: Option<String>,
+}
+
+/// SystemManager simulating the active window, which can be switched through the clones
+#[derive(Clone, Default)]
+pub struct MockSystemManager {
+    window: Rc<RefCell<MockWindow>>,
+}
+
+impl MockSystemManager {
+    pub fn new() -> MockSystemManager {
+        MockSystemManager::default()
+    }
+
+    pub fn set_active_window(&self, window: MockWindow) {
+        self.window.replace(window);
+    }
+
+    #[cfg(test)]
+    pub fn set_window(&self, title: &str, class: &str, exec: &str) {
+        self.set_active_window(MockWindow {
+            title: Some(title.to_owned()),
+            class: Some(class.to_owned()),
+            exec: Some(exec.to_owned()),
+        });
+    }
+}
+
+impl SystemManager for MockSystemManager {
+    fn get_current_window_title(&self) -> Option<String> {
+        self.window.borrow().title.clone()
+    }
+
+    fn get_current_window_class(&self) -> Option<String> {
+        self.window.borrow().class.clone()
+    }
+
+    fn get_current_window_executable(&self) -> Option<String> {
+        self.window.borrow().exec.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_shared_between_mocks() {
+        let recorder = Recorder::new();
+        let keyboard = MockKeyboardManager::new(recorder.clone());
+        let ui = MockUIManager::new(recorder.clone());
+
+        keyboard.delete_string(3);
+        ui.notify("hello");
+        keyboard.send_string("world");
+
+        assert_eq!(recorder.actions(), vec![
+            RecordedAction::Delete(3),
+            RecordedAction::Notify("hello".to_owned()),
+            RecordedAction::SendString("world".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_clipboard_snapshot_restore() {
+        let clipboard = MockClipboardManager::new(Recorder::new());
+        clipboard.set_content("previous");
+
+        let snapshot = clipboard.get_snapshot().unwrap();
+        clipboard.set_clipboard("expansion");
+        assert_eq!(clipboard.get_clipboard().unwrap(), "expansion");
+
+        clipboard.restore_snapshot(&snapshot);
+        assert_eq!(clipboard.get_clipboard().unwrap(), "previous");
+    }
+
+    #[test]
+    fn test_system_manager_switch_window() {
+        let system = MockSystemManager::new();
+        assert!(system.get_current_window_class().is_none());
+
+        system.clone().set_window("Title", "Class", "exec");
+        assert_eq!(system.get_current_window_class().unwrap(), "Class");
+    }
+}
@@ -0,0 +1,74 @@
+/*
+ * This file is part of espanso.
+ *
+ * Copyright (C) 2019 Federico Terzi
+ *
+ * espanso is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * espanso is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with espanso.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Utilities to run espanso without depending on the platform backends,
+// used by the tests and to replay the debug traces
+
+pub(crate) mod mock;
+
+#[cfg(test)]
+pub(crate) mod harness;
+
+use std::sync::mpsc;
+use std::sync::mpsc::Sender;
+use crate::config::{ConfigSet, ConfigManager};
+use crate::config::runtime::RuntimeConfigManager;
+use crate::engine::Engine;
+use crate::event::Event;
+use crate::event::manager::{DefaultEventManager, EventManager};
+use crate::extension::Extension;
+use crate::matcher::scrolling::ScrollingMatcher;
+use crate::render::default::DefaultRenderer;
+use mock::*;
+
+/// Create the matcher and the engine on top of the mock backends, wired as in the daemon,
+/// and pass the resulting event manager to the callback, along with the sender of its event
+/// channel. The actions end up in the recorder.
+pub fn run_with_mocks<F>(config_set: ConfigSet, system: &MockSystemManager, recorder: &Recorder,
+                         clipboard: &MockClipboardManager, ui_manager: &MockUIManager,
+                         history_manager: &MockHistoryManager, extensions: Vec<Box<dyn Extension>>,
+                         callback: F) where F: FnOnce(&dyn EventManager, Sender<Event>) {
+    let config_manager = RuntimeConfigManager::new(config_set, system.clone());
+
+    let keyboard_manager = MockKeyboardManager::new(recorder.clone());
+    let renderer = DefaultRenderer::new(extensions,
+                                        config_manager.default_config().clone());
+    let (ipc_event_sender, _ipc_event_receiver) = mpsc::channel();
+
+    let engine = Engine::new(&keyboard_manager,
+                             clipboard,
+                             &config_manager,
+                             ui_manager,
+                             &renderer,
+                             history_manager,
+                             ipc_event_sender,
+    );
+
+    let matcher = ScrollingMatcher::new(&config_manager, &engine);
+
+    let (send_channel, receive_channel) = mpsc::channel();
+    let event_manager = DefaultEventManager::new(
+        &receive_channel,
+        vec!(&matcher, &engine),
+        vec!(&engine, &matcher),
+        vec!(&engine, &matcher),
+    );
+
+    callback(&event_manager, send_channel);
+}
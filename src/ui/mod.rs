@@ -30,6 +30,12 @@ pub trait UIManager {
     fn notify(&self, message: &str);
     fn show_menu(&self, menu: Vec<MenuItem>);
     fn cleanup(&self);
+
+    // Let the user choose one of the entries through the given picker command,
+    // returning the picker output. Blocks until the picker is closed.
+    fn pick(&self, command: &str, entries: &[String]) -> Option<String> {
+        crate::search::picker::run_picker_command(command, entries)
+    }
 }
 
 pub enum MenuItemType {